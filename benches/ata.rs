@@ -0,0 +1,95 @@
+//! Per-update cost of tagging the owner edge of a token account with `WEIGHT_ATA`.
+//!
+//! Run with `cargo bench --bench ata`. `derive` is `associated_token_address` alone, which the
+//! filter used to run on every token account update. `edge` times `SolToken::edge` twice: over
+//! accounts it has not seen, where the address is derived, and over the same accounts again,
+//! where the answer comes from the memo because the owner and mint did not change.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use catscope_edge_generator::primitive::{
+    guest::GuestFilter,
+    header::AccountHeader,
+    soltoken::{associated_token_address, SolToken},
+};
+use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
+use spl_token::state::{Account, AccountState};
+
+const COUNT: usize = 2_000;
+const ROUND: u32 = 20;
+
+struct Sample {
+    header: AccountHeader,
+    owner: Pubkey,
+    mint: Pubkey,
+    data: Vec<u8>,
+}
+
+// half of the accounts are associated token accounts
+fn sample() -> Vec<Sample> {
+    (0..COUNT)
+        .map(|k| {
+            let owner = Pubkey::new_unique();
+            let mint = Pubkey::new_unique();
+            let pubkey = if k % 2 == 0 {
+                associated_token_address(&owner, &mint, &spl_token::ID)
+            } else {
+                Pubkey::new_unique()
+            };
+            let mut data = vec![0; Account::LEN];
+            Account {
+                mint,
+                owner,
+                amount: k as u64,
+                state: AccountState::Initialized,
+                ..Default::default()
+            }
+            .pack_into_slice(&mut data);
+            Sample {
+                header: AccountHeader {
+                    pubkey,
+                    owner: spl_token::ID,
+                    lamports: 1,
+                    slot: 1,
+                    ..Default::default()
+                },
+                owner,
+                mint,
+                data,
+            }
+        })
+        .collect()
+}
+
+// nanoseconds per account
+fn time(round: u32, list: &[Sample], mut f: impl FnMut(&Sample)) -> f64 {
+    let start = Instant::now();
+    for _ in 0..round {
+        for x in list.iter() {
+            f(black_box(x));
+        }
+    }
+    let total: Duration = start.elapsed();
+    total.as_nanos() as f64 / (round as f64 * list.len() as f64)
+}
+
+fn main() {
+    let list = sample();
+    let derive = time(ROUND, &list, |x| {
+        black_box(associated_token_address(&x.owner, &x.mint, &spl_token::ID));
+    });
+    let filter = SolToken::default();
+    let first = time(1, &list, |x| {
+        black_box(filter.edge(&x.header, &x.data));
+    });
+    let again = time(ROUND, &list, |x| {
+        black_box(filter.edge(&x.header, &x.data));
+    });
+    println!(
+        "ata {} accounts  derive {:>8.1} ns/account  edge first seen {:>8.1} ns/account  edge again {:>7.1} ns/account",
+        COUNT, derive, first, again
+    );
+}
@@ -0,0 +1,290 @@
+//! Per-account cost of routing an account to the code that parses it.
+//!
+//! Run with `cargo bench --bench dispatch`. For every filter with discriminators this times
+//! `node_type`, which is little more than the dispatch, and `edge` over one account of each
+//! kind plus one account with an unknown discriminator, the worst case of a linear scan.
+//! The `before` lines compare `node_type` of the Raydium CLMM and Solpipe filters with the
+//! `node_type` they had before the `DispatchTable`, copied into the `before` module below.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use catscope_edge_generator::{
+    meteora::{self, Meteora},
+    orca::{self, Orca},
+    primitive::{guest::GuestFilter, header::AccountHeader, node::NodeType},
+    raydium_clmm::{self, Raydium},
+    raydium_cpmm::RaydiumCpmm,
+    safejar::{self, Safejar},
+    solpipe::{self, Solpipe},
+    test_support::account,
+};
+use solana_sdk::pubkey::Pubkey;
+
+const SIZE: usize = 10_000;
+const ROUND: u32 = 20_000;
+
+fn sample(list: &[[u8; 8]]) -> Vec<Vec<u8>> {
+    let mut out: Vec<Vec<u8>> = list.iter().map(|d| account(*d, SIZE)).collect();
+    out.push(account([0xee; 8], SIZE));
+    out
+}
+
+// nanoseconds per account
+fn time(accounts: &[Vec<u8>], mut f: impl FnMut(&[u8])) -> f64 {
+    let start = Instant::now();
+    for _ in 0..ROUND {
+        for data in accounts.iter() {
+            f(black_box(data));
+        }
+    }
+    let total: Duration = start.elapsed();
+    total.as_nanos() as f64 / (ROUND as f64 * accounts.len() as f64)
+}
+
+fn bench(name: &str, filter: &dyn GuestFilter, accounts: &[Vec<u8>]) {
+    let header = AccountHeader {
+        pubkey: Pubkey::new_unique(),
+        owner: filter.program_id_list()[0],
+        lamports: 1,
+        slot: 1,
+        ..Default::default()
+    };
+    let node_type = time(accounts, |data| {
+        black_box(filter.node_type(&header, data));
+    });
+    let edge = time(accounts, |data| {
+        black_box(filter.edge(&header, data));
+    });
+    println!(
+        "{:<14} {:>2} kinds  node_type {:>7.1} ns/account  edge {:>8.1} ns/account",
+        name,
+        accounts.len() - 1,
+        node_type,
+        edge
+    );
+}
+
+// `node_type` of two filters as it was before the dispatch table, copied verbatim: one
+// `match_discriminator` per account kind, in the order the filters tested them.
+mod before {
+    use catscope_edge_generator::{
+        primitive::{common::match_discriminator, header::AccountHeader, node::*},
+        raydium_clmm::*,
+        solpipe::{
+            agent_discriminator, bidlist_discriminator, controller_discriminator,
+            controllerapi_discriminator, payout_discriminator, periodring_discriminator,
+            pipeline_discriminator, refunds_discriminator,
+        },
+    };
+
+    pub struct Raydium {
+        d_amm_config: [u8; 8],
+        d_observation_state: [u8; 8],
+        d_operation_state: [u8; 8],
+        d_personal_position_state: [u8; 8],
+        d_pool_state: [u8; 8],
+        d_protocol_position_state: [u8; 8],
+        d_support_mint_associated: [u8; 8],
+        d_tick_array_bitmap_extension: [u8; 8],
+        d_tick_array_state: [u8; 8],
+    }
+
+    impl Raydium {
+        pub fn new() -> Self {
+            Self {
+                d_amm_config: discriminator_amm_config(),
+                d_observation_state: discriminator_observation_state(),
+                d_operation_state: discriminator_operation_state(),
+                d_personal_position_state: discriminator_personal_position_state(),
+                d_pool_state: discriminator_pool_state(),
+                d_protocol_position_state: discriminator_protocol_position_state(),
+                d_support_mint_associated: discriminator_support_mint_associated(),
+                d_tick_array_bitmap_extension: discriminator_tick_array_bitmap_extension(),
+                d_tick_array_state: discriminator_tick_array_state(),
+            }
+        }
+
+        pub fn node_type(&self, _header: &AccountHeader, data: &[u8]) -> NodeType {
+            if match_discriminator(&self.d_amm_config, data) {
+                NODE_TYPE_RAYDIUM_CLMM_AMM_CONFIG
+            } else if match_discriminator(&self.d_protocol_position_state, data) {
+                NODE_TYPE_RAYDIUM_CLMM_PROTOCOL_POSITION
+            } else if match_discriminator(&self.d_support_mint_associated, data) {
+                NODE_TYPE_RAYDIUM_CLMM_SUPPORT_MINT_ASSOCIATED
+            } else if match_discriminator(&self.d_tick_array_state, data) {
+                NODE_TYPE_RAYDIUM_CLMM_TICK_ARRAY
+            } else if match_discriminator(&self.d_personal_position_state, data) {
+                NODE_TYPE_RAYDIUM_CLMM_PERSONAL_POSITION
+            } else if match_discriminator(&self.d_observation_state, data) {
+                NODE_TYPE_RAYDIUM_CLMM_OBSERVATION
+            } else if match_discriminator(&self.d_tick_array_bitmap_extension, data) {
+                NODE_TYPE_RAYDIUM_CLMM_TICK_ARRAY_BITMAP_EXTENSION
+            } else if match_discriminator(&self.d_pool_state, data) {
+                NODE_TYPE_RAYDIUM_CLMM_POOL_STATE
+            } else if match_discriminator(&self.d_operation_state, data) {
+                NODE_TYPE_RAYDIUM_CLMM_OPERATION_STATE
+            } else {
+                NODE_TYPE_UNKNOWN
+            }
+        }
+    }
+
+    pub struct Solpipe {
+        d_controller: [u8; 8],
+        d_controller_api: [u8; 8],
+        d_pipeline: [u8; 8],
+        d_payout: [u8; 8],
+        d_agent: [u8; 8],
+        d_bidlist: [u8; 8],
+        d_period_ring: [u8; 8],
+        d_refunds: [u8; 8],
+    }
+
+    impl Solpipe {
+        pub fn new() -> Self {
+            Self {
+                d_controller: controller_discriminator(),
+                d_controller_api: controllerapi_discriminator(),
+                d_pipeline: pipeline_discriminator(),
+                d_payout: payout_discriminator(),
+                d_agent: agent_discriminator(),
+                d_bidlist: bidlist_discriminator(),
+                d_period_ring: periodring_discriminator(),
+                d_refunds: refunds_discriminator(),
+            }
+        }
+
+        pub fn node_type(&self, _header: &AccountHeader, data: &[u8]) -> NodeType {
+            if match_discriminator(&self.d_controller, data) {
+                NODE_TYPE_SOLPIPE_CONTROLLER
+            } else if match_discriminator(&self.d_controller_api, data) {
+                NODE_TYPE_SOLPIPE_CONTROLLER_API
+            } else if match_discriminator(&self.d_pipeline, data) {
+                NODE_TYPE_SOLPIPE_PIPELINE
+            } else if match_discriminator(&self.d_payout, data) {
+                NODE_TYPE_SOLPIPE_PAYOUT
+            } else if match_discriminator(&self.d_period_ring, data) {
+                NODE_TYPE_SOLPIPE_PERIOD_RING
+            } else if match_discriminator(&self.d_refunds, data) {
+                NODE_TYPE_SOLPIPE_REFUNDS
+            } else if match_discriminator(&self.d_bidlist, data) {
+                NODE_TYPE_SOLPIPE_BID_LIST
+            } else if match_discriminator(&self.d_agent, data) {
+                NODE_TYPE_SOLPIPE_AGENT
+            } else {
+                NODE_TYPE_UNKNOWN
+            }
+        }
+    }
+}
+
+// the old and the current node_type must agree before their times are compared
+fn compare(
+    name: &str,
+    old: impl Fn(&AccountHeader, &[u8]) -> NodeType,
+    new: impl Fn(&AccountHeader, &[u8]) -> NodeType,
+    accounts: &[Vec<u8>],
+) {
+    let header = AccountHeader::default();
+    for data in accounts.iter() {
+        assert_eq!(old(&header, data), new(&header, data), "{}", name);
+    }
+    let chain = time(accounts, |data| {
+        black_box(old(&header, data));
+    });
+    let table = time(accounts, |data| {
+        black_box(new(&header, data));
+    });
+    println!(
+        "{:<14} {:>2} kinds  before    {:>7.1} ns/account  table {:>7.1} ns/account",
+        name,
+        accounts.len() - 1,
+        chain,
+        table
+    );
+}
+
+fn main() {
+    let program_id = Pubkey::new_unique();
+    bench(
+        "safejar",
+        &Safejar::new(&program_id),
+        &sample(&[
+            safejar::controller_discriminator(),
+            safejar::delegation_discriminator(),
+        ]),
+    );
+    let mut accounts = sample(&[
+        solpipe::controller_discriminator(),
+        solpipe::controllerapi_discriminator(),
+        solpipe::pipeline_discriminator(),
+        solpipe::payout_discriminator(),
+        solpipe::periodring_discriminator(),
+        solpipe::agent_discriminator(),
+        solpipe::bidlist_discriminator(),
+        solpipe::refunds_discriminator(),
+    ]);
+    // keep the vec lengths of the bid list and the refunds small
+    accounts[6][41..45].copy_from_slice(&2u32.to_le_bytes());
+    accounts[7][40..44].copy_from_slice(&2u32.to_le_bytes());
+    let filter = Solpipe::new(&program_id);
+    bench("solpipe", &filter, &accounts);
+    let old = before::Solpipe::new();
+    compare(
+        "solpipe",
+        |h, d| old.node_type(h, d),
+        |h, d| filter.node_type(h, d),
+        &accounts,
+    );
+    bench(
+        "orca",
+        &Orca::new(&program_id),
+        &sample(&[
+            orca::whirlpoolconfig_discriminator(),
+            orca::whirlpool_discriminator(),
+            orca::tickarray_discriminator(),
+        ]),
+    );
+    bench(
+        "raydium_cpmm",
+        &RaydiumCpmm::new(&program_id),
+        &sample(&[
+            raydium_clmm::discriminator_amm_config(),
+            raydium_clmm::discriminator_pool_state(),
+        ]),
+    );
+    let accounts = sample(&[
+        raydium_clmm::discriminator_amm_config(),
+        raydium_clmm::discriminator_observation_state(),
+        raydium_clmm::discriminator_operation_state(),
+        raydium_clmm::discriminator_personal_position_state(),
+        raydium_clmm::discriminator_pool_state(),
+        raydium_clmm::discriminator_protocol_position_state(),
+        raydium_clmm::discriminator_support_mint_associated(),
+        raydium_clmm::discriminator_tick_array_bitmap_extension(),
+        raydium_clmm::discriminator_tick_array_state(),
+    ]);
+    let filter = Raydium::new(&program_id);
+    bench("raydium_clmm", &filter, &accounts);
+    let old = before::Raydium::new();
+    compare(
+        "raydium_clmm",
+        |h, d| old.node_type(h, d),
+        |h, d| filter.node_type(h, d),
+        &accounts,
+    );
+    bench(
+        "meteora",
+        &Meteora::new(&program_id),
+        &sample(&[
+            meteora::lbpair_discriminator(),
+            meteora::binarray_discriminator(),
+            meteora::binarraybitmapextension_discriminator(),
+            meteora::position_discriminator(),
+            meteora::positionv2_discriminator(),
+        ]),
+    );
+}
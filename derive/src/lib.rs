@@ -0,0 +1,263 @@
+//! `#[derive(EdgeSource)]` for the account structs of edge filters.
+//!
+//! The struct lists the fields of an account in the order they are laid out after the 8 byte
+//! Anchor discriminator, up to the last field that produces an edge. Fields annotated with
+//! `#[edge(...)]` become edges between the account and the pubkey held in the field:
+//!
+//! ```ignore
+//! #[derive(EdgeSource)]
+//! #[edge(program, weight = PROGRAM, label = SAFEJAR_CONTROLLER_PROGRAM)]
+//! pub struct Controller {
+//!     pub bump: u8,
+//!     #[edge(out, weight = DIRECT, label = SAFEJAR_CONTROLLER_OWNER)]
+//!     pub owner: Pubkey,
+//! }
+//! ```
+//!
+//! - `out`: an edge from the account to the pubkey; `in`: an edge from the pubkey to the account.
+//! - `weight = A | B`: the `tree::WEIGHT_A | tree::WEIGHT_B` weight of the edge.
+//! - `label = X`: the `label::LABEL_X` label of the edge; `LABEL_NONE` if omitted.
+//! - `skip_default`: no edge when the field holds the default pubkey (the system program).
+//!
+//! On the struct, `program` adds an edge from the program id to the account ahead of the
+//! field edges. Edges are produced in declaration order.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Data, DeriveInput, Error, Fields, Ident, Result, Token,
+};
+
+#[proc_macro_derive(EdgeSource, attributes(edge))]
+pub fn derive_edge_source(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    In,
+    Out,
+    Program,
+}
+
+/// The arguments of one `#[edge(...)]` attribute.
+struct EdgeArgs {
+    span: Span,
+    direction: Option<Direction>,
+    weight: Vec<Ident>,
+    label: Option<Ident>,
+    skip_default: bool,
+}
+
+impl Parse for EdgeArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut args = EdgeArgs {
+            span: input.span(),
+            direction: None,
+            weight: Vec::new(),
+            label: None,
+            skip_default: false,
+        };
+        while !input.is_empty() {
+            if input.peek(Token![in]) {
+                let token: Token![in] = input.parse()?;
+                args.set_direction(Direction::In, token.span)?;
+            } else {
+                let key: Ident = input.parse()?;
+                match key.to_string().as_str() {
+                    "out" => args.set_direction(Direction::Out, key.span())?,
+                    "program" => args.set_direction(Direction::Program, key.span())?,
+                    "skip_default" => args.skip_default = true,
+                    "weight" => {
+                        input.parse::<Token![=]>()?;
+                        let list = Punctuated::<Ident, Token![|]>::parse_separated_nonempty(input)?;
+                        args.weight = list.into_iter().collect();
+                    }
+                    "label" => {
+                        input.parse::<Token![=]>()?;
+                        args.label = Some(input.parse()?);
+                    }
+                    _ => return Err(Error::new(
+                        key.span(),
+                        "expected one of `in`, `out`, `program`, `weight`, `label`, `skip_default`",
+                    )),
+                }
+            }
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(args)
+    }
+}
+
+impl EdgeArgs {
+    fn set_direction(&mut self, direction: Direction, span: Span) -> Result<()> {
+        if self.direction.is_some() {
+            return Err(Error::new(span, "an edge has exactly one direction"));
+        }
+        self.direction = Some(direction);
+        Ok(())
+    }
+
+    fn weight(&self) -> Result<TokenStream2> {
+        if self.weight.is_empty() {
+            return Err(Error::new(self.span, "missing `weight = ...`"));
+        }
+        let list = self.weight.iter().map(|w| format_ident!("WEIGHT_{}", w));
+        Ok(quote! { #(::catscope_edge_generator::primitive::tree::#list)|* })
+    }
+
+    fn label(&self) -> TokenStream2 {
+        let label = match &self.label {
+            Some(l) => format_ident!("LABEL_{}", l),
+            None => format_ident!("LABEL_NONE"),
+        };
+        quote! { ::catscope_edge_generator::primitive::label::#label }
+    }
+}
+
+fn edge_args(attrs: &[syn::Attribute]) -> Result<Option<EdgeArgs>> {
+    let mut out = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("edge")) {
+        if out.is_some() {
+            return Err(Error::new_spanned(
+                attr,
+                "duplicate `#[edge(...)]` attribute",
+            ));
+        }
+        out = Some(attr.parse_args::<EdgeArgs>()?);
+    }
+    Ok(out)
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(f) => &f.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    name,
+                    "EdgeSource needs a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "EdgeSource can only be derived for structs",
+            ))
+        }
+    };
+    let source = quote! { ::catscope_edge_generator::primitive::source };
+    let edge_type = quote! { ::catscope_edge_generator::primitive::tree::FilterEdge };
+
+    let mut l_size = Vec::new();
+    let mut l_read = Vec::new();
+    let mut l_name = Vec::new();
+    let mut l_edge = Vec::new();
+
+    if let Some(args) = edge_args(&input.attrs)? {
+        if args.direction != Some(Direction::Program) {
+            return Err(Error::new(
+                args.span,
+                "only `program` edges can be declared on the struct",
+            ));
+        }
+        if args.skip_default {
+            return Err(Error::new(
+                args.span,
+                "`skip_default` does not apply to `program` edges",
+            ));
+        }
+        let weight = args.weight()?;
+        let label = args.label();
+        l_edge.push(quote! {
+            list.push_back(#edge_type {
+                slot: header.slot,
+                weight: #weight,
+                from: *program_id,
+                to: header.pubkey,
+                label: #label,
+            });
+        });
+    }
+
+    for field in fields.iter() {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        l_size.push(quote! { <#ty as #source::AccountField>::SIZE });
+        l_read.push(quote! { let #ident = <#ty as #source::AccountField>::read(&mut reader)?; });
+        l_name.push(ident);
+        let args = match edge_args(&field.attrs)? {
+            Some(args) => args,
+            None => continue,
+        };
+        let (from, to) = match args.direction {
+            Some(Direction::Out) => (quote! { header.pubkey }, quote! { self.#ident }),
+            Some(Direction::In) => (quote! { self.#ident }, quote! { header.pubkey }),
+            Some(Direction::Program) => {
+                return Err(Error::new(
+                    args.span,
+                    "`program` edges are declared on the struct",
+                ))
+            }
+            None => return Err(Error::new(args.span, "missing `in` or `out`")),
+        };
+        let weight = args.weight()?;
+        let label = args.label();
+        let push = quote! {
+            list.push_back(#edge_type {
+                slot: header.slot,
+                weight: #weight,
+                from: #from,
+                to: #to,
+                label: #label,
+            });
+        };
+        l_edge.push(if args.skip_default {
+            quote! {
+                if self.#ident != ::catscope_edge_generator::primitive::source::Pubkey::default() {
+                    #push
+                }
+            }
+        } else {
+            push
+        });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #source::EdgeSource for #name #ty_generics #where_clause {
+            const SIZE: usize = 0 #(+ #l_size)*;
+
+            fn parse(data: &[u8]) -> ::core::result::Result<Self, ::catscope_edge_generator::primitive::err::CatscopeWasmError> {
+                let mut reader = ::catscope_edge_generator::primitive::common::AccountReader::new(data);
+                reader.require(#source::FIELD_OFFSET + <Self as #source::EdgeSource>::SIZE)?;
+                reader.seek(#source::FIELD_OFFSET)?;
+                #(#l_read)*
+                ::core::result::Result::Ok(Self { #(#l_name),* })
+            }
+
+            fn edge(
+                &self,
+                header: &::catscope_edge_generator::primitive::header::AccountHeader,
+                program_id: &#source::Pubkey,
+            ) -> ::std::collections::VecDeque<#edge_type> {
+                let _ = program_id;
+                let mut list = ::std::collections::VecDeque::new();
+                #(#l_edge)*
+                list
+            }
+        }
+    })
+}
@@ -0,0 +1,131 @@
+//! Run account dumps through the filters built by `init` and print the edges.
+//!
+//! ```text
+//! catscope-edges [--config program.txt] [--format jsonl|csv|dot]
+//!                [--owner PUBKEY] [--slot SLOT] [--pubkey PUBKEY] FILE...
+//! ```
+//!
+//! Files ending in `.json` are read as `solana account --output json` dumps. Any other file
+//! is raw account data from `solana account --output-file`; its owner comes from `--owner` and
+//! its pubkey from `--pubkey` (which applies to the next file only) or from the file name.
+
+use std::{path::Path, process::ExitCode};
+
+use catscope_edge_generator::{
+    config::{parse_filter_config, FilterConfig},
+    dump::{format_edge_list, AccountDump, OutputFormat},
+    filter_list,
+    primitive::{err::CatscopeWasmError, filter::FilterSet, guest::GuestFilter},
+};
+use solana_sdk::pubkey::Pubkey;
+
+const USAGE: &str = "usage: catscope-edges [--config program.txt] [--format jsonl|csv|dot] \
+[--owner PUBKEY] [--slot SLOT] [--pubkey PUBKEY] FILE...";
+
+fn invalid(msg: String) -> CatscopeWasmError {
+    CatscopeWasmError::InvalidInput(msg)
+}
+
+fn parse_pubkey(flag: &str, value: &str) -> Result<Pubkey, CatscopeWasmError> {
+    Pubkey::try_from(value).map_err(|e| invalid(format!("bad {} {}: {}", flag, value, e)))
+}
+
+fn read_dump(
+    path: &str,
+    owner: Option<Pubkey>,
+    pubkey: Option<Pubkey>,
+    slot: u64,
+) -> Result<AccountDump, CatscopeWasmError> {
+    if path.ends_with(".json") {
+        let input = std::fs::read_to_string(path)
+            .map_err(|e| invalid(format!("failed to read {}: {}", path, e)))?;
+        let mut dump = AccountDump::from_json(&input)?;
+        if dump.header.slot == 0 {
+            dump.header.slot = slot;
+        }
+        return Ok(dump);
+    }
+    let data =
+        std::fs::read(path).map_err(|e| invalid(format!("failed to read {}: {}", path, e)))?;
+    let owner = owner.ok_or_else(|| invalid(format!("raw dump {} needs --owner", path)))?;
+    let pubkey = match pubkey {
+        Some(x) => x,
+        None => Path::new(path)
+            .file_stem()
+            .and_then(|x| x.to_str())
+            .and_then(|x| Pubkey::try_from(x).ok())
+            .ok_or_else(|| {
+                invalid(format!(
+                    "raw dump {} needs --pubkey or a pubkey as its file name",
+                    path
+                ))
+            })?,
+    };
+    Ok(AccountDump::from_raw(pubkey, owner, slot, data))
+}
+
+fn run(args: Vec<String>) -> Result<String, CatscopeWasmError> {
+    let mut config = FilterConfig::default();
+    let mut format = OutputFormat::JsonLines;
+    let mut owner = None;
+    let mut pubkey = None;
+    let mut slot = 0;
+    let mut dump_list = Vec::new();
+    let mut iter = args.into_iter();
+    while let Some(arg) = iter.next() {
+        if !arg.starts_with("--") {
+            dump_list.push(read_dump(&arg, owner, pubkey.take(), slot)?);
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| invalid(format!("{} needs a value", arg)))?;
+        match arg.as_str() {
+            "--config" => {
+                let input = std::fs::read(&value)
+                    .map_err(|e| invalid(format!("failed to read {}: {}", value, e)))?;
+                config = parse_filter_config(&input)?;
+            }
+            "--format" => {
+                format = OutputFormat::from_name(&value)
+                    .ok_or_else(|| invalid(format!("unknown format `{}`", value)))?;
+            }
+            "--owner" => owner = Some(parse_pubkey(&arg, &value)?),
+            "--pubkey" => pubkey = Some(parse_pubkey(&arg, &value)?),
+            "--slot" => {
+                slot = value
+                    .parse()
+                    .map_err(|e| invalid(format!("bad --slot {}: {}", value, e)))?
+            }
+            _ => return Err(invalid(format!("unknown option {}", arg))),
+        }
+    }
+    if dump_list.is_empty() {
+        return Err(invalid("no account files given".to_string()));
+    }
+    let filter = FilterSet::new(filter_list(&config));
+    let mut list = Vec::new();
+    for dump in dump_list.iter() {
+        list.extend(filter.edge(&dump.header, &dump.data));
+    }
+    Ok(format_edge_list(format, &list))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|x| x == "--help" || x == "-h") {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    }
+    match run(args) {
+        Ok(out) => {
+            print!("{}", out);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("catscope-edges: {}", e);
+            eprintln!("{}", USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
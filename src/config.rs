@@ -0,0 +1,518 @@
+use std::fmt;
+
+use solana_sdk::{
+    epoch_schedule::{EpochSchedule, MINIMUM_SLOTS_PER_EPOCH},
+    pubkey::Pubkey,
+};
+
+use crate::{
+    idl::Idl,
+    primitive::{
+        err::CatscopeWasmError,
+        filter::EDGE_CACHE_CAPACITY,
+        guest::GuestFilter,
+        soltoken::SolToken,
+        tree::{parse_program_list, MAX_PROGRAM_COUNT},
+    },
+    rule::OffsetRule,
+};
+
+/// The config entry name for offset rules.
+pub const RULE_NAME: &str = "rule";
+/// The config entry name for Anchor IDLs.
+pub const IDL_NAME: &str = "idl";
+/// The config entry name for the epoch schedule of the cluster.
+pub const EPOCH_SCHEDULE_NAME: &str = "epoch_schedule";
+/// The config entry name for the number of accounts the edge cache remembers.
+pub const EDGE_CACHE_NAME: &str = "edge_cache";
+
+/// The filters that can be enabled through the init args.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterKind {
+    Safejar,
+    Solpipe,
+    Orca,
+    RaydiumCpmm,
+    RaydiumAmm,
+    RaydiumClmm,
+    Meteora,
+    Stake,
+    Vote,
+    LookupTable,
+}
+
+impl FilterKind {
+    pub const ALL: [FilterKind; 10] = [
+        FilterKind::Safejar,
+        FilterKind::Solpipe,
+        FilterKind::Orca,
+        FilterKind::RaydiumCpmm,
+        FilterKind::RaydiumAmm,
+        FilterKind::RaydiumClmm,
+        FilterKind::Meteora,
+        FilterKind::Stake,
+        FilterKind::Vote,
+        FilterKind::LookupTable,
+    ];
+    /// The order used by the legacy positional program list; filters added later are named only.
+    pub const POSITIONAL: [FilterKind; 7] = [
+        FilterKind::Safejar,
+        FilterKind::Solpipe,
+        FilterKind::Orca,
+        FilterKind::RaydiumCpmm,
+        FilterKind::RaydiumAmm,
+        FilterKind::RaydiumClmm,
+        FilterKind::Meteora,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FilterKind::Safejar => "safejar",
+            FilterKind::Solpipe => "solpipe",
+            FilterKind::Orca => "orca",
+            FilterKind::RaydiumCpmm => "raydium_cpmm",
+            FilterKind::RaydiumAmm => "raydium_amm",
+            FilterKind::RaydiumClmm => "raydium_clmm",
+            FilterKind::Meteora => "meteora",
+            FilterKind::Stake => "stake",
+            FilterKind::Vote => "vote",
+            FilterKind::LookupTable => "lookup_table",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.name() == name)
+    }
+}
+
+impl fmt::Display for FilterKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The filters to run, each bound to the program id it tracks.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FilterConfig {
+    pub list: Vec<(FilterKind, Pubkey)>,
+    /// Offset rules for programs that have no dedicated filter.
+    pub rule_list: Vec<OffsetRule>,
+    /// Anchor IDLs, each bound to the program id it describes.
+    pub idl_list: Vec<(Pubkey, Idl)>,
+    /// The epoch schedule of the cluster; `None` is the Mainnet schedule, which has no warmup.
+    pub epoch_schedule: Option<EpochSchedule>,
+    /// The number of accounts whose edges are remembered for removals; `None` is
+    /// [`EDGE_CACHE_CAPACITY`].
+    pub edge_cache_capacity: Option<usize>,
+}
+
+impl FilterConfig {
+    pub fn program_id_list(&self, kind: FilterKind) -> Vec<Pubkey> {
+        self.list
+            .iter()
+            .filter(|(k, _)| *k == kind)
+            .map(|(_, pk)| *pk)
+            .collect()
+    }
+    /// Find the config entry that already tracks the program id.
+    fn owner_name(&self, program_id: &Pubkey) -> Option<&'static str> {
+        if let Some((kind, _)) = self.list.iter().find(|(_, pk)| pk.eq(program_id)) {
+            Some(kind.name())
+        } else if self.rule_list.iter().any(|r| r.owner.eq(program_id)) {
+            Some(RULE_NAME)
+        } else if self.idl_list.iter().any(|(pk, _)| pk.eq(program_id)) {
+            Some(IDL_NAME)
+        } else {
+            None
+        }
+    }
+    fn check_owner(
+        &self,
+        program_id: &Pubkey,
+        name: &'static str,
+        allow_repeat: bool,
+    ) -> Result<(), CatscopeWasmError> {
+        match self.owner_name(program_id) {
+            Some(other) if other == name && !allow_repeat => Err(CatscopeWasmError::InvalidInput(
+                format!("program id {} is listed twice for {}", program_id, name),
+            )),
+            Some(other) if other != name => Err(CatscopeWasmError::InvalidInput(format!(
+                "program id {} is assigned to both {} and {}",
+                program_id, other, name
+            ))),
+            _ => Ok(()),
+        }
+    }
+    fn push(&mut self, kind: FilterKind, program_id: Pubkey) -> Result<(), CatscopeWasmError> {
+        self.check_owner(&program_id, kind.name(), false)?;
+        self.list.push((kind, program_id));
+        Ok(())
+    }
+    fn push_rule(&mut self, rule: OffsetRule) -> Result<(), CatscopeWasmError> {
+        // one program may have many rules
+        self.check_owner(&rule.owner, RULE_NAME, true)?;
+        self.rule_list.push(rule);
+        Ok(())
+    }
+    fn push_idl(&mut self, program_id: Pubkey, idl: Idl) -> Result<(), CatscopeWasmError> {
+        self.check_owner(&program_id, IDL_NAME, false)?;
+        self.idl_list.push((program_id, idl));
+        Ok(())
+    }
+    /// The schedule that maps slots to epochs, eg for the authorized voter of a vote account.
+    pub fn epoch_schedule(&self) -> EpochSchedule {
+        self.epoch_schedule
+            .clone()
+            .unwrap_or_else(EpochSchedule::without_warmup)
+    }
+    /// The capacity of the edge cache of the guest, see [`EdgeCache`].
+    ///
+    /// [`EdgeCache`]: crate::primitive::filter::EdgeCache
+    pub fn edge_cache_capacity(&self) -> usize {
+        self.edge_cache_capacity.unwrap_or(EDGE_CACHE_CAPACITY)
+    }
+    /// Count the distinct program ids covered by the config.
+    pub fn program_count(&self) -> usize {
+        let mut list: Vec<Pubkey> = self.rule_list.iter().map(|r| r.owner).collect();
+        list.sort();
+        list.dedup();
+        self.list.len() + list.len() + self.idl_list.len()
+    }
+}
+
+/// Parse the init args into a filter configuration.
+///
+/// Each line enables one filter by name, followed by one or more comma separated program ids:
+/// ```text
+/// # comments and blank lines are ignored
+/// orca=whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc
+/// raydium_cpmm=CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C
+/// ```
+/// Programs without a dedicated filter can be tracked with `rule=` entries; see
+/// [`OffsetRule::parse`] for the fields.
+/// Anchor programs can also be tracked with `idl=[program_id,]{...}`, where the IDL JSON may
+/// span several lines. The program id defaults to the address in the IDL.
+/// Clusters other than Mainnet set their epoch schedule with
+/// `epoch_schedule=slots_per_epoch[,warmup]`, eg `epoch_schedule=8192,true` for a test validator.
+/// `edge_cache=accounts` sets how many accounts the edge cache remembers.
+/// Input without any `name=` entry is read as the legacy positional list; see
+/// [`FilterKind::POSITIONAL`] for the order.
+pub fn parse_filter_config(input: &[u8]) -> Result<FilterConfig, CatscopeWasmError> {
+    let input_str = match std::str::from_utf8(input) {
+        Ok(x) => x,
+        Err(e) => return Err(CatscopeWasmError::InvalidInput(e.to_string())),
+    };
+    let mut config = FilterConfig::default();
+    if !input_str.contains('=') {
+        let list = parse_program_list(input_str.trim().as_bytes())?;
+        if FilterKind::POSITIONAL.len() < list.len() {
+            return Err(CatscopeWasmError::InvalidInput(format!(
+                "positional program list has {} entries but only {} filters exist",
+                list.len(),
+                FilterKind::POSITIONAL.len()
+            )));
+        }
+        for (kind, program_id) in FilterKind::POSITIONAL.into_iter().zip(list) {
+            config.push(kind, program_id)?;
+        }
+    } else {
+        let mut rest = input_str;
+        while !rest.is_empty() {
+            let start = rest;
+            let (line, next) = rest.split_once('\n').unwrap_or((rest, ""));
+            rest = next;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, value) = match line.split_once('=') {
+                Some(x) => x,
+                None => {
+                    return Err(CatscopeWasmError::InvalidInput(format!(
+                        "expected name=program_id but got `{}`",
+                        line
+                    )))
+                }
+            };
+            let name = name.trim();
+            if name == RULE_NAME {
+                config.push_rule(OffsetRule::parse(value)?)?;
+                continue;
+            }
+            if name == EPOCH_SCHEDULE_NAME {
+                if config.epoch_schedule.is_some() {
+                    return Err(CatscopeWasmError::InvalidInput(format!(
+                        "{} is listed twice",
+                        EPOCH_SCHEDULE_NAME
+                    )));
+                }
+                config.epoch_schedule = Some(parse_epoch_schedule(value)?);
+                continue;
+            }
+            if name == EDGE_CACHE_NAME {
+                if config.edge_cache_capacity.is_some() {
+                    return Err(CatscopeWasmError::InvalidInput(format!(
+                        "{} is listed twice",
+                        EDGE_CACHE_NAME
+                    )));
+                }
+                let capacity = match value.trim().parse::<usize>() {
+                    Ok(x) if 0 < x => x,
+                    _ => {
+                        return Err(CatscopeWasmError::InvalidInput(format!(
+                            "bad {} {}: expected a number of accounts",
+                            EDGE_CACHE_NAME, value
+                        )))
+                    }
+                };
+                config.edge_cache_capacity = Some(capacity);
+                continue;
+            }
+            if name == IDL_NAME {
+                // the json may continue past the end of this line
+                let mut json = start[(start.find('=').unwrap_or_default() + 1)..].trim_start();
+                let mut o_program_id = None;
+                if !json.starts_with('{') {
+                    let (pk, _) = value.split_once(',').ok_or_else(|| {
+                        CatscopeWasmError::InvalidInput(String::from(
+                            "expected idl=program_id,{...}",
+                        ))
+                    })?;
+                    o_program_id = Some(pk.trim());
+                    json = &json[(json.find(',').unwrap_or_default() + 1)..];
+                }
+                let (idl, size) = Idl::parse_prefix(json)?;
+                rest = &json[size..];
+                let program_id = match (o_program_id, idl.address) {
+                    (Some(pk), _) => Pubkey::try_from(pk).map_err(|e| {
+                        CatscopeWasmError::InvalidInput(format!(
+                            "bad program id for {}: {}",
+                            IDL_NAME, e
+                        ))
+                    })?,
+                    (None, Some(pk)) => pk,
+                    (None, None) => {
+                        return Err(CatscopeWasmError::InvalidInput(String::from(
+                            "idl has no address; use idl=program_id,{...}",
+                        )))
+                    }
+                };
+                config.push_idl(program_id, idl)?;
+                continue;
+            }
+            let kind = match FilterKind::from_name(name) {
+                Some(x) => x,
+                None => {
+                    let mut known: Vec<&str> = FilterKind::ALL.iter().map(|k| k.name()).collect();
+                    known.push(RULE_NAME);
+                    known.push(IDL_NAME);
+                    known.push(EPOCH_SCHEDULE_NAME);
+                    known.push(EDGE_CACHE_NAME);
+                    return Err(CatscopeWasmError::InvalidInput(format!(
+                        "unknown filter `{}`; expected one of {}",
+                        name,
+                        known.join(", ")
+                    )));
+                }
+            };
+            let list = match parse_program_list(value.trim().as_bytes()) {
+                Ok(x) => x,
+                Err(e) => {
+                    return Err(CatscopeWasmError::InvalidInput(format!(
+                        "bad program id for {}: {}",
+                        kind, e
+                    )))
+                }
+            };
+            for program_id in list {
+                config.push(kind, program_id)?;
+            }
+        }
+    }
+    let count = config.program_count() + SolToken::default().program_id_list().len();
+    if MAX_PROGRAM_COUNT < count {
+        return Err(CatscopeWasmError::InvalidInput(format!(
+            "{} program ids exceed the maximum of {}",
+            count, MAX_PROGRAM_COUNT
+        )));
+    }
+    Ok(config)
+}
+
+/// Parse `slots_per_epoch[,warmup]`; the leader schedule is computed one epoch ahead, as in
+/// the genesis config of every cluster.
+fn parse_epoch_schedule(value: &str) -> Result<EpochSchedule, CatscopeWasmError> {
+    let (slots, warmup) = value.split_once(',').unwrap_or((value, "false"));
+    let bad = |e: String| {
+        CatscopeWasmError::InvalidInput(format!("bad {} {}: {}", EPOCH_SCHEDULE_NAME, value, e))
+    };
+    let slots_per_epoch = slots
+        .trim()
+        .parse::<u64>()
+        .map_err(|e| bad(e.to_string()))?;
+    let warmup = warmup
+        .trim()
+        .parse::<bool>()
+        .map_err(|e| bad(e.to_string()))?;
+    if slots_per_epoch < MINIMUM_SLOTS_PER_EPOCH {
+        return Err(bad(format!(
+            "an epoch has at least {} slots",
+            MINIMUM_SLOTS_PER_EPOCH
+        )));
+    }
+    Ok(EpochSchedule::custom(
+        slots_per_epoch,
+        slots_per_epoch,
+        warmup,
+    ))
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*; // import functions from the parent module
+
+    const SAFEJAR: &str = "TRSY7YgS3tcDoi6ZgTp2MmPJpXHyCVrGaFhL7HLdQc9";
+    const SOLPIPE: &str = "CBAidZ5BjA1BYi9WF6Ca1AaWakF2MPxkVgp7oo5tDyW3";
+    const ORCA: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+    const CPMM: &str = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
+
+    fn pk(s: &str) -> Pubkey {
+        Pubkey::try_from(s).unwrap()
+    }
+
+    #[test]
+    fn test_positional() {
+        let input = format!("{},{},{},{}\n", SAFEJAR, SOLPIPE, ORCA, CPMM);
+        let config = parse_filter_config(input.as_bytes()).unwrap();
+        assert_eq!(
+            config.list,
+            vec![
+                (FilterKind::Safejar, pk(SAFEJAR)),
+                (FilterKind::Solpipe, pk(SOLPIPE)),
+                (FilterKind::Orca, pk(ORCA)),
+                (FilterKind::RaydiumCpmm, pk(CPMM)),
+            ]
+        );
+    }
+    #[test]
+    fn test_named() {
+        let second = Pubkey::new_unique();
+        let input = format!(
+            "# orca only\n\n raydium_cpmm = {}\norca={}, {}\n",
+            CPMM, ORCA, second
+        );
+        let config = parse_filter_config(input.as_bytes()).unwrap();
+        assert_eq!(
+            config.program_id_list(FilterKind::Orca),
+            vec![pk(ORCA), second]
+        );
+        assert_eq!(
+            config.program_id_list(FilterKind::RaydiumCpmm),
+            vec![pk(CPMM)]
+        );
+        assert!(config.program_id_list(FilterKind::Safejar).is_empty());
+    }
+    #[test]
+    fn test_rule() {
+        let owner = Pubkey::new_unique();
+        let input = format!(
+            "orca={}\nrule={},-,752,336,out,DIRECT,true\nrule={},-,752,368,out,DIRECT,true\n",
+            ORCA, owner, owner
+        );
+        let config = parse_filter_config(input.as_bytes()).unwrap();
+        assert_eq!(config.rule_list.len(), 2);
+        assert_eq!(config.program_count(), 2);
+        let input = format!("orca={}\nrule={},-,0,0,out,DIRECT,true", ORCA, ORCA);
+        let err = parse_filter_config(input.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("both orca and rule"), "{}", err);
+    }
+    #[test]
+    fn test_idl() {
+        let other = Pubkey::new_unique();
+        let idl = r#"{
+            "address": "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc",
+            "accounts": [{"name": "WhirlpoolsConfig", "type": {"kind": "struct", "fields": [
+                {"name": "fee_authority", "type": "pubkey"}
+            ]}}]
+        }"#;
+        let input = format!(
+            "raydium_cpmm={}\nidl={}\n\nidl = {}, {}  \n# done\n",
+            CPMM, idl, other, idl
+        );
+        let config = parse_filter_config(input.as_bytes()).unwrap();
+        assert_eq!(config.idl_list.len(), 2);
+        assert_eq!(config.idl_list[0].0, pk(ORCA));
+        assert_eq!(config.idl_list[1].0, other);
+        assert_eq!(config.program_count(), 3);
+
+        let input = format!("orca={}\nidl={}", ORCA, idl);
+        let err = parse_filter_config(input.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("both orca and idl"), "{}", err);
+        let input = format!("idl={} trailing", idl);
+        assert!(parse_filter_config(input.as_bytes()).is_err());
+    }
+    #[test]
+    fn test_epoch_schedule() {
+        let config = parse_filter_config(format!("vote={}", CPMM).as_bytes()).unwrap();
+        assert_eq!(config.epoch_schedule(), EpochSchedule::without_warmup());
+        let input = format!("epoch_schedule = 8192, true\nvote={}", CPMM);
+        let config = parse_filter_config(input.as_bytes()).unwrap();
+        let schedule = config.epoch_schedule();
+        assert_eq!((schedule.slots_per_epoch, schedule.warmup), (8192, true));
+        // warmup starts with epochs of 32 slots
+        assert_eq!(schedule.get_epoch(40), 1);
+        let config = parse_filter_config(b"epoch_schedule=432000").unwrap();
+        assert_eq!(config.epoch_schedule(), EpochSchedule::without_warmup());
+        for input in [
+            "epoch_schedule=16",
+            "epoch_schedule=8192,yes",
+            "epoch_schedule=",
+            "epoch_schedule=8192\nepoch_schedule=8192",
+        ] {
+            assert!(parse_filter_config(input.as_bytes()).is_err(), "{}", input);
+        }
+    }
+    #[test]
+    fn test_edge_cache() {
+        let config = parse_filter_config(format!("orca={}", ORCA).as_bytes()).unwrap();
+        assert_eq!(config.edge_cache_capacity(), EDGE_CACHE_CAPACITY);
+        let input = format!("orca={}\nedge_cache = 1000000\n", ORCA);
+        let config = parse_filter_config(input.as_bytes()).unwrap();
+        assert_eq!(config.edge_cache_capacity(), 1_000_000);
+        for input in [
+            "edge_cache=0",
+            "edge_cache=-1",
+            "edge_cache=",
+            "edge_cache=10\nedge_cache=10",
+        ] {
+            assert!(parse_filter_config(input.as_bytes()).is_err(), "{}", input);
+        }
+    }
+    #[test]
+    fn test_errors() {
+        let err = parse_filter_config(format!("whirlpool={}", ORCA).as_bytes()).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown filter `whirlpool`"),
+            "{}",
+            err
+        );
+        let err = parse_filter_config(format!("orca={}\n{}", ORCA, CPMM).as_bytes()).unwrap_err();
+        assert!(
+            err.to_string().contains("expected name=program_id"),
+            "{}",
+            err
+        );
+        let err =
+            parse_filter_config(format!("orca={}\nmeteora={}", ORCA, ORCA).as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("both orca and meteora"), "{}", err);
+        let err =
+            parse_filter_config(format!("orca={}\norca={}", ORCA, ORCA).as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("listed twice for orca"), "{}", err);
+        assert!(parse_filter_config(b"orca=notapubkey").is_err());
+        let too_many: Vec<String> = (0..MAX_PROGRAM_COUNT)
+            .map(|_| Pubkey::new_unique().to_string())
+            .collect();
+        let input = format!("orca={}", too_many.join(","));
+        assert!(parse_filter_config(input.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,253 @@
+use std::fmt::Write;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::primitive::{
+    err::CatscopeWasmError,
+    header::AccountHeader,
+    label::label_name,
+    tree::{edge_is_outgoing, weight_names, FilterEdge},
+};
+
+/// An account read from a dump file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountDump {
+    pub header: AccountHeader,
+    pub data: Vec<u8>,
+}
+
+impl AccountDump {
+    /// Wrap the raw account data written by `solana account --output-file`.
+    /// Raw dumps carry no metadata, so the pubkey and owner are passed in.
+    pub fn from_raw(pubkey: Pubkey, owner: Pubkey, slot: u64, data: Vec<u8>) -> Self {
+        let header = AccountHeader {
+            pubkey,
+            owner,
+            slot,
+            data_size: data.len() as u32,
+            ..Default::default()
+        };
+        Self { header, data }
+    }
+
+    /// Parse the JSON written by `solana account --output json`:
+    /// `{"pubkey": .., "account": {"lamports": .., "data": [.., "base64"], "owner": .., ..}}`.
+    /// The fields may also sit at the top level, and an optional `slot` is read from either place.
+    pub fn from_json(input: &str) -> Result<Self, CatscopeWasmError> {
+        let root: Value = serde_json::from_str(input)
+            .map_err(|e| CatscopeWasmError::InvalidInput(format!("bad account json: {}", e)))?;
+        let account = root.get("account").unwrap_or(&root);
+        let field = |name: &str| account.get(name).or_else(|| root.get(name));
+        let pubkey = json_pubkey(root.get("pubkey"), "pubkey")?;
+        let owner = json_pubkey(field("owner"), "owner")?;
+        let lamports = json_u64(field("lamports"), "lamports")?.unwrap_or_default();
+        let slot = json_u64(field("slot"), "slot")?.unwrap_or_default();
+        let rent_epoch = json_u64(field("rentEpoch"), "rentEpoch")?.unwrap_or_default();
+        let executable = field("executable")
+            .and_then(|x| x.as_bool())
+            .unwrap_or_default();
+        let data = json_data(field("data"))?;
+        let header = AccountHeader {
+            pubkey,
+            lamports,
+            data_size: data.len() as u32,
+            owner,
+            rent_epoch,
+            slot,
+            executable,
+            ..Default::default()
+        };
+        Ok(Self { header, data })
+    }
+}
+
+fn json_pubkey(value: Option<&Value>, name: &str) -> Result<Pubkey, CatscopeWasmError> {
+    let x = value
+        .and_then(|x| x.as_str())
+        .ok_or_else(|| CatscopeWasmError::InvalidInput(format!("account json has no {}", name)))?;
+    Pubkey::try_from(x)
+        .map_err(|e| CatscopeWasmError::InvalidInput(format!("bad {} {}: {}", name, x, e)))
+}
+
+fn json_u64(value: Option<&Value>, name: &str) -> Result<Option<u64>, CatscopeWasmError> {
+    match value {
+        None => Ok(None),
+        Some(x) => x
+            .as_u64()
+            .map(Some)
+            .ok_or_else(|| CatscopeWasmError::InvalidInput(format!("bad {}: {}", name, x))),
+    }
+}
+
+// the data is a `[payload, encoding]` pair; only base64 is supported.
+fn json_data(value: Option<&Value>) -> Result<Vec<u8>, CatscopeWasmError> {
+    let pair = value
+        .and_then(|x| x.as_array())
+        .ok_or_else(|| CatscopeWasmError::InvalidInput("account json has no data".to_string()))?;
+    let (payload, encoding) = match pair.as_slice() {
+        [Value::String(p), Value::String(e)] => (p, e),
+        _ => {
+            return Err(CatscopeWasmError::InvalidInput(
+                "account data must be a [payload, encoding] pair".to_string(),
+            ))
+        }
+    };
+    if encoding != "base64" {
+        return Err(CatscopeWasmError::InvalidInput(format!(
+            "unsupported data encoding `{}`; expected base64",
+            encoding
+        )));
+    }
+    STANDARD
+        .decode(payload)
+        .map_err(|e| CatscopeWasmError::InvalidInput(format!("bad base64 data: {}", e)))
+}
+
+/// How edges are printed by offline tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// one JSON object per edge
+    JsonLines,
+    Csv,
+    /// a Graphviz digraph
+    Dot,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "jsonl" | "json" => Some(OutputFormat::JsonLines),
+            "csv" => Some(OutputFormat::Csv),
+            "dot" => Some(OutputFormat::Dot),
+            _ => None,
+        }
+    }
+}
+
+/// Print edges with the account weight bits decoded to names, eg `DIRECT|SYMLINK`, and the
+/// label decoded to its name; unlabeled edges have an empty label.
+pub fn format_edge_list(format: OutputFormat, list: &[FilterEdge]) -> String {
+    let mut out = String::new();
+    match format {
+        OutputFormat::JsonLines => {
+            for edge in list.iter() {
+                let line = json!({
+                    "slot": edge.slot,
+                    "from": edge.from.to_string(),
+                    "to": edge.to.to_string(),
+                    "weight": edge.weight,
+                    "names": weight_names(edge.weight),
+                    "outgoing": edge_is_outgoing(&edge.weight),
+                    "label": label_name(edge.label),
+                });
+                writeln!(out, "{}", line).unwrap();
+            }
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "slot,from,to,weight,names,outgoing,label").unwrap();
+            for edge in list.iter() {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{}",
+                    edge.slot,
+                    edge.from,
+                    edge.to,
+                    edge.weight,
+                    weight_names(edge.weight).join("|"),
+                    edge_is_outgoing(&edge.weight),
+                    label_name(edge.label).unwrap_or_default()
+                )
+                .unwrap();
+            }
+        }
+        OutputFormat::Dot => {
+            writeln!(out, "digraph catscope {{").unwrap();
+            for edge in list.iter() {
+                writeln!(
+                    out,
+                    "  \"{}\" -> \"{}\" [label=\"{}\"];",
+                    edge.from,
+                    edge.to,
+                    weight_names(edge.weight).join("|")
+                )
+                .unwrap();
+            }
+            writeln!(out, "}}").unwrap();
+        }
+    }
+    out
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::primitive::{
+        label::LABEL_ORCA_WHIRLPOOL_TOKEN_VAULT_A,
+        tree::{WEIGHT_DIRECT, WEIGHT_IS_OUTGOING, WEIGHT_SYMLINK},
+    };
+
+    use super::*; // import functions from the parent module
+
+    #[test]
+    fn test_from_json() {
+        let data = std::fs::read("tests/data/whirlpoolconfig1.bin").unwrap();
+        let input = format!(
+            r#"{{"pubkey": "2LecshUwdy9xi7meFgHtFJQNSKk4KdTrcpvaB56dP2NQ",
+            "account": {{"lamports": 488832, "data": ["{}", "base64"],
+            "owner": "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc", "executable": false,
+            "rentEpoch": 18446744073709551615, "space": 108}}, "slot": 7}}"#,
+            STANDARD.encode(&data)
+        );
+        let dump = AccountDump::from_json(&input).unwrap();
+        assert_eq!(dump.data, data);
+        assert_eq!(dump.header.lamports, 488832);
+        assert_eq!(dump.header.slot, 7);
+        assert_eq!(dump.header.data_size, 108);
+        assert_eq!(dump.header.rent_epoch, u64::MAX);
+        assert_eq!(
+            dump.header.owner.to_string(),
+            "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc"
+        );
+
+        let bad = r#"{"pubkey": "2LecshUwdy9xi7meFgHtFJQNSKk4KdTrcpvaB56dP2NQ",
+            "owner": "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc", "data": ["abc", "base58"]}"#;
+        let err = AccountDump::from_json(bad).unwrap_err();
+        assert!(err.to_string().contains("base58"), "{}", err);
+        assert!(AccountDump::from_json(r#"{"pubkey": "x"}"#).is_err());
+    }
+
+    #[test]
+    fn test_format_edge_list() {
+        let from = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+        let list = vec![FilterEdge {
+            slot: 3,
+            from,
+            to,
+            weight: WEIGHT_DIRECT | WEIGHT_SYMLINK | WEIGHT_IS_OUTGOING,
+            label: LABEL_ORCA_WHIRLPOOL_TOKEN_VAULT_A,
+        }];
+        let out = format_edge_list(OutputFormat::JsonLines, &list);
+        let line: Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(line["names"], json!(["DIRECT", "SYMLINK"]));
+        assert_eq!(line["outgoing"], json!(true));
+        assert_eq!(line["from"], json!(from.to_string()));
+        assert_eq!(line["label"], json!("orca.whirlpool.token_vault_a"));
+        let out = format_edge_list(OutputFormat::Csv, &list);
+        assert_eq!(
+            out.lines().nth(1).unwrap(),
+            format!(
+                "3,{},{},385,DIRECT|SYMLINK,true,orca.whirlpool.token_vault_a",
+                from, to
+            )
+        );
+        let out = format_edge_list(OutputFormat::Dot, &list);
+        assert!(out.contains(&format!(
+            "\"{}\" -> \"{}\" [label=\"DIRECT|SYMLINK\"];",
+            from, to
+        )));
+        assert!(out.starts_with("digraph catscope {"));
+    }
+}
@@ -0,0 +1,795 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use serde::Deserialize;
+use serde_json::Value;
+use solana_sdk::{pubkey::Pubkey, system_program::ID as system_id};
+
+#[cfg(target_os = "wasi")]
+use crate::primitive::wasmimport::HostImport;
+use crate::{
+    primitive::{
+        common::{anchor_account_discriminator, AccountReader, PUBKEY_LEN, U32_LEN},
+        dispatch::DispatchTable,
+        err::CatscopeWasmError,
+        guest::{edge_or_log, GuestFilter},
+        header::AccountHeader,
+        label::LABEL_IDL_FIELD,
+        tree::{parse_weight, FilterEdge, Weight, WEIGHT_DIRECT},
+    },
+    rule::RuleDirection,
+    DISCRIMINATOR_SIZE,
+};
+
+// defined types can nest (or recurse); stop compiling past this depth.
+const MAX_TYPE_DEPTH: usize = 32;
+
+/// How to turn one pubkey field into an edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldEdge {
+    pub direction: RuleDirection,
+    pub weight: Weight,
+}
+impl Default for FieldEdge {
+    fn default() -> Self {
+        Self {
+            direction: RuleDirection::Out,
+            weight: WEIGHT_DIRECT,
+        }
+    }
+}
+
+/// The Borsh layout of an account, reduced to what is needed to find pubkeys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Layout {
+    /// bytes without pubkeys
+    Fixed(usize),
+    /// `None` when the field is skipped by an override
+    Pubkey(Option<FieldEdge>),
+    Struct(Vec<Layout>),
+    Array(Box<Layout>, usize),
+    /// u32 length prefix; strings and bytes are `Vec(Fixed(1))`
+    Vec(Box<Layout>),
+    /// u8 tag
+    Option(Box<Layout>),
+    /// u32 tag
+    COption(Box<Layout>),
+    /// u8 tag selecting a variant
+    Enum(Vec<Layout>),
+}
+
+impl Layout {
+    fn fixed_size(&self) -> Option<usize> {
+        match self {
+            Layout::Fixed(n) => Some(*n),
+            Layout::Pubkey(_) => Some(PUBKEY_LEN),
+            Layout::Struct(list) => list
+                .iter()
+                .try_fold(0usize, |acc, x| acc.checked_add(x.fixed_size()?)),
+            Layout::Array(x, n) => x.fixed_size()?.checked_mul(*n),
+            _ => None,
+        }
+    }
+    fn has_pubkey(&self) -> bool {
+        match self {
+            Layout::Fixed(_) => false,
+            Layout::Pubkey(_) => true,
+            Layout::Struct(list) | Layout::Enum(list) => list.iter().any(|x| x.has_pubkey()),
+            Layout::Array(x, _) | Layout::Vec(x) | Layout::Option(x) | Layout::COption(x) => {
+                x.has_pubkey()
+            }
+        }
+    }
+    /// Collapse sub layouts without pubkeys into `Fixed` where the size is known.
+    fn simplify(self) -> Self {
+        if !self.has_pubkey() {
+            if let Some(n) = self.fixed_size() {
+                return Layout::Fixed(n);
+            }
+        }
+        match self {
+            Layout::Struct(list) => {
+                let mut out: Vec<Layout> = Vec::with_capacity(list.len());
+                for x in list.into_iter().map(|x| x.simplify()) {
+                    if let (Layout::Fixed(b), Some(Layout::Fixed(a))) = (&x, out.last_mut()) {
+                        *a += *b;
+                        continue;
+                    }
+                    out.push(x);
+                }
+                Layout::Struct(out)
+            }
+            Layout::Array(x, n) => Layout::Array(Box::new(x.simplify()), n),
+            Layout::Vec(x) => Layout::Vec(Box::new(x.simplify())),
+            Layout::Option(x) => Layout::Option(Box::new(x.simplify())),
+            Layout::COption(x) => Layout::COption(Box::new(x.simplify())),
+            Layout::Enum(list) => Layout::Enum(list.into_iter().map(|x| x.simplify()).collect()),
+            x => x,
+        }
+    }
+    /// List the pubkeys of a fixed size layout, relative to `start`.
+    fn flatten(&self, start: usize, out: &mut Vec<(usize, FieldEdge)>) {
+        match self {
+            Layout::Pubkey(Some(e)) => out.push((start, *e)),
+            Layout::Struct(list) => {
+                let mut i = start;
+                for x in list.iter() {
+                    x.flatten(i, out);
+                    i += x.fixed_size().unwrap_or_default();
+                }
+            }
+            Layout::Array(x, n) => {
+                let size = x.fixed_size().unwrap_or_default();
+                for k in 0..*n {
+                    x.flatten(start + k * size, out);
+                }
+            }
+            _ => {}
+        }
+    }
+    /// Walk Borsh data from `*i`. Returns `None` if the data is too short.
+    fn walk(&self, data: &[u8], i: &mut usize, out: &mut Vec<(usize, FieldEdge)>) -> Option<()> {
+        match self {
+            Layout::Fixed(n) => advance(data, i, *n),
+            Layout::Pubkey(o_e) => {
+                let start = *i;
+                advance(data, i, PUBKEY_LEN)?;
+                if let Some(e) = o_e {
+                    out.push((start, *e));
+                }
+                Some(())
+            }
+            Layout::Struct(list) => {
+                for x in list.iter() {
+                    x.walk(data, i, out)?;
+                }
+                Some(())
+            }
+            Layout::Array(x, n) => repeat(x, *n, data, i, out),
+            Layout::Vec(x) => {
+                let n = read_u32(data, i)? as usize;
+                repeat(x, n, data, i, out)
+            }
+            Layout::Option(x) => {
+                let tag = *data.get(*i)?;
+                *i += 1;
+                if tag != 0 {
+                    x.walk(data, i, out)?;
+                }
+                Some(())
+            }
+            Layout::COption(x) => {
+                if read_u32(data, i)? != 0 {
+                    x.walk(data, i, out)?;
+                }
+                Some(())
+            }
+            Layout::Enum(list) => {
+                let tag = *data.get(*i)? as usize;
+                *i += 1;
+                list.get(tag)?.walk(data, i, out)
+            }
+        }
+    }
+}
+
+fn advance(data: &[u8], i: &mut usize, n: usize) -> Option<()> {
+    let end = i.checked_add(n)?;
+    if data.len() < end {
+        return None;
+    }
+    *i = end;
+    Some(())
+}
+
+fn read_u32(data: &[u8], i: &mut usize) -> Option<u32> {
+    let start = *i;
+    advance(data, i, U32_LEN)?;
+    let x: [u8; U32_LEN] = data[start..*i].try_into().ok()?;
+    Some(u32::from_le_bytes(x))
+}
+
+fn repeat(
+    x: &Layout,
+    n: usize,
+    data: &[u8],
+    i: &mut usize,
+    out: &mut Vec<(usize, FieldEdge)>,
+) -> Option<()> {
+    if let Layout::Fixed(size) = x {
+        // do not loop over large byte arrays
+        return advance(data, i, size.checked_mul(n)?);
+    }
+    for _ in 0..n {
+        x.walk(data, i, out)?;
+    }
+    Some(())
+}
+
+/// A compiled IDL account type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AccountLayout {
+    /// The pubkey offsets are known ahead of time; `size` excludes the discriminator.
+    Fixed {
+        size: usize,
+        list: Vec<(usize, FieldEdge)>,
+    },
+    Dynamic(Layout),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IdlAccount {
+    name: String,
+    layout: AccountLayout,
+}
+
+/// An Anchor IDL reduced to the pubkey fields of each account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Idl {
+    /// The program id declared by the IDL, if any.
+    pub address: Option<Pubkey>,
+    m_account: DispatchTable<IdlAccount>,
+}
+
+#[derive(Deserialize)]
+struct IdlJson {
+    #[serde(default)]
+    address: Option<String>,
+    #[serde(default)]
+    metadata: IdlMetadataJson,
+    #[serde(default)]
+    accounts: Vec<IdlAccountJson>,
+    #[serde(default)]
+    types: Vec<IdlTypeDefJson>,
+}
+
+#[derive(Deserialize, Default)]
+struct IdlMetadataJson {
+    #[serde(default)]
+    address: Option<String>,
+    /// Per-field overrides keyed by `Account.field`; nested fields are joined with `.`.
+    #[serde(default)]
+    catscope: BTreeMap<String, FieldOverrideJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FieldOverrideJson {
+    #[serde(default)]
+    direction: Option<String>,
+    #[serde(default)]
+    weight: Option<String>,
+    #[serde(default)]
+    skip: bool,
+}
+
+#[derive(Deserialize)]
+struct IdlAccountJson {
+    name: String,
+    #[serde(default)]
+    discriminator: Option<Vec<u8>>,
+    #[serde(default, rename = "type")]
+    ty: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct IdlTypeDefJson {
+    name: String,
+    #[serde(rename = "type")]
+    ty: Value,
+}
+
+fn invalid(msg: String) -> CatscopeWasmError {
+    CatscopeWasmError::InvalidInput(msg)
+}
+
+struct Compiler<'a> {
+    m_type: BTreeMap<&'a str, &'a Value>,
+    m_override: BTreeMap<String, Option<FieldEdge>>,
+    used: BTreeSet<String>,
+}
+
+impl Compiler<'_> {
+    fn typedef(
+        &mut self,
+        ty: &Value,
+        path: &str,
+        depth: usize,
+    ) -> Result<Layout, CatscopeWasmError> {
+        let kind = ty.get("kind").and_then(|x| x.as_str()).unwrap_or_default();
+        match kind {
+            "struct" => self.fields(ty.get("fields").unwrap_or(&Value::Null), path, depth),
+            "enum" => {
+                let variants = ty
+                    .get("variants")
+                    .and_then(|x| x.as_array())
+                    .ok_or_else(|| invalid(format!("{}: enum without variants", path)))?;
+                let mut list = Vec::with_capacity(variants.len());
+                for v in variants.iter() {
+                    let name = v.get("name").and_then(|x| x.as_str()).unwrap_or_default();
+                    let sub = format!("{}.{}", path, name);
+                    list.push(self.fields(v.get("fields").unwrap_or(&Value::Null), &sub, depth)?);
+                }
+                Ok(Layout::Enum(list))
+            }
+            "type" => self.ty(ty.get("alias").unwrap_or(&Value::Null), path, depth),
+            x => Err(invalid(format!("{}: unsupported type kind `{}`", path, x))),
+        }
+    }
+    fn fields(
+        &mut self,
+        fields: &Value,
+        path: &str,
+        depth: usize,
+    ) -> Result<Layout, CatscopeWasmError> {
+        let mut list = Vec::new();
+        if let Some(array) = fields.as_array() {
+            for (k, f) in array.iter().enumerate() {
+                // named fields are objects with a type; tuple fields are bare types
+                match (f.get("name").and_then(|x| x.as_str()), f.get("type")) {
+                    (Some(name), Some(t)) => {
+                        list.push(self.ty(t, &format!("{}.{}", path, name), depth)?)
+                    }
+                    _ => list.push(self.ty(f, &format!("{}.{}", path, k), depth)?),
+                }
+            }
+        }
+        Ok(Layout::Struct(list))
+    }
+    fn ty(&mut self, ty: &Value, path: &str, depth: usize) -> Result<Layout, CatscopeWasmError> {
+        if MAX_TYPE_DEPTH < depth {
+            return Err(invalid(format!("{}: types are nested too deep", path)));
+        }
+        if let Some(name) = ty.as_str() {
+            let size = match name {
+                "bool" | "u8" | "i8" => 1,
+                "u16" | "i16" => 2,
+                "u32" | "i32" | "f32" => 4,
+                "u64" | "i64" | "f64" => 8,
+                "u128" | "i128" => 16,
+                "u256" | "i256" => 32,
+                "string" | "bytes" => return Ok(Layout::Vec(Box::new(Layout::Fixed(1)))),
+                "publicKey" | "pubkey" => {
+                    let edge = match self.m_override.get(path) {
+                        Some(x) => {
+                            self.used.insert(path.to_string());
+                            *x
+                        }
+                        None => Some(FieldEdge::default()),
+                    };
+                    return Ok(Layout::Pubkey(edge));
+                }
+                x => return Err(invalid(format!("{}: unsupported type `{}`", path, x))),
+            };
+            return Ok(Layout::Fixed(size));
+        }
+        let object = ty
+            .as_object()
+            .ok_or_else(|| invalid(format!("{}: bad type {}", path, ty)))?;
+        if let Some(x) = object.get("vec") {
+            Ok(Layout::Vec(Box::new(self.ty(x, path, depth + 1)?)))
+        } else if let Some(x) = object.get("option") {
+            Ok(Layout::Option(Box::new(self.ty(x, path, depth + 1)?)))
+        } else if let Some(x) = object.get("coption") {
+            Ok(Layout::COption(Box::new(self.ty(x, path, depth + 1)?)))
+        } else if let Some(x) = object.get("array") {
+            let pair = x.as_array().filter(|p| p.len() == 2);
+            let n = pair.and_then(|p| p[1].as_u64());
+            match (pair, n) {
+                (Some(p), Some(n)) => Ok(Layout::Array(
+                    Box::new(self.ty(&p[0], path, depth + 1)?),
+                    n as usize,
+                )),
+                _ => Err(invalid(format!("{}: unsupported array {}", path, x))),
+            }
+        } else if let Some(x) = object.get("defined") {
+            // anchor < 0.30 uses a string; later versions use {"name": ...}
+            let name = x
+                .as_str()
+                .or_else(|| x.get("name").and_then(|n| n.as_str()))
+                .ok_or_else(|| invalid(format!("{}: bad defined type {}", path, x)))?;
+            let def = *self
+                .m_type
+                .get(name)
+                .ok_or_else(|| invalid(format!("{}: missing type `{}`", path, name)))?;
+            self.typedef(def, path, depth + 1)
+        } else {
+            Err(invalid(format!("{}: unsupported type {}", path, ty)))
+        }
+    }
+}
+
+fn parse_override(
+    key: &str,
+    o: &FieldOverrideJson,
+) -> Result<Option<FieldEdge>, CatscopeWasmError> {
+    if o.skip {
+        return Ok(None);
+    }
+    let mut edge = FieldEdge::default();
+    if let Some(d) = &o.direction {
+        edge.direction = match d.as_str() {
+            "out" => RuleDirection::Out,
+            "in" => RuleDirection::In,
+            x => {
+                return Err(invalid(format!(
+                    "{}: bad direction `{}`; expected in or out",
+                    key, x
+                )))
+            }
+        };
+    }
+    if let Some(w) = &o.weight {
+        edge.weight = parse_weight(w)?;
+    }
+    Ok(Some(edge))
+}
+
+impl Idl {
+    /// Parse an Anchor IDL in either the legacy (< 0.30) or the current JSON format.
+    pub fn parse(input: &str) -> Result<Self, CatscopeWasmError> {
+        let json: IdlJson =
+            serde_json::from_str(input).map_err(|e| invalid(format!("bad idl: {}", e)))?;
+        Self::from_json(&json)
+    }
+    /// Parse the first JSON document of `input` and return the number of bytes consumed.
+    pub fn parse_prefix(input: &str) -> Result<(Self, usize), CatscopeWasmError> {
+        let mut stream = serde_json::Deserializer::from_str(input).into_iter::<IdlJson>();
+        let json = match stream.next() {
+            Some(Ok(x)) => x,
+            Some(Err(e)) => return Err(invalid(format!("bad idl: {}", e))),
+            None => return Err(invalid(String::from("missing idl"))),
+        };
+        Ok((Self::from_json(&json)?, stream.byte_offset()))
+    }
+    fn from_json(json: &IdlJson) -> Result<Self, CatscopeWasmError> {
+        let address = match json.address.as_ref().or(json.metadata.address.as_ref()) {
+            Some(x) => Some(
+                Pubkey::try_from(x.as_str())
+                    .map_err(|e| invalid(format!("bad idl address {}: {}", x, e)))?,
+            ),
+            None => None,
+        };
+        let mut m_override = BTreeMap::new();
+        for (key, o) in json.metadata.catscope.iter() {
+            m_override.insert(key.clone(), parse_override(key, o)?);
+        }
+        let mut compiler = Compiler {
+            m_type: json
+                .types
+                .iter()
+                .map(|t| (t.name.as_str(), &t.ty))
+                .collect(),
+            m_override,
+            used: BTreeSet::new(),
+        };
+        let mut m_account = DispatchTable::default();
+        for account in json.accounts.iter() {
+            let discriminator = match &account.discriminator {
+                Some(d) => d.as_slice().try_into().map_err(|_| {
+                    invalid(format!("{}: discriminator must be 8 bytes", account.name))
+                })?,
+                None => anchor_account_discriminator(&account.name),
+            };
+            // anchor >= 0.30 moves the account struct to the type list
+            let ty = match &account.ty {
+                Some(t) => t,
+                None => *compiler
+                    .m_type
+                    .get(account.name.as_str())
+                    .ok_or_else(|| invalid(format!("{}: missing account type", account.name)))?,
+            };
+            let layout = compiler.typedef(ty, &account.name, 0)?.simplify();
+            let layout = match layout.fixed_size() {
+                Some(size) => {
+                    let mut list = Vec::new();
+                    layout.flatten(DISCRIMINATOR_SIZE, &mut list);
+                    AccountLayout::Fixed { size, list }
+                }
+                None => AccountLayout::Dynamic(layout),
+            };
+            let name = account.name.clone();
+            if m_account
+                .insert(discriminator, IdlAccount { name, layout })
+                .is_some()
+            {
+                return Err(invalid(format!(
+                    "{}: duplicate discriminator",
+                    account.name
+                )));
+            }
+        }
+        if let Some(key) = compiler
+            .m_override
+            .keys()
+            .find(|k| !compiler.used.contains(*k))
+        {
+            return Err(invalid(format!("override {} matches no pubkey field", key)));
+        }
+        Ok(Self { address, m_account })
+    }
+}
+
+/// A filter generated from an Anchor IDL. Every pubkey field of a known account becomes an edge.
+pub struct IdlFilter {
+    idl: Idl,
+    pub program_id: Pubkey,
+}
+
+impl GuestFilter for IdlFilter {
+    fn program_id_list(&self) -> Vec<Pubkey> {
+        vec![self.program_id]
+    }
+
+    fn edge(&self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge> {
+        edge_or_log("idl", header, self.try_edge(header, data))
+    }
+}
+
+impl IdlFilter {
+    fn try_edge(
+        &self,
+        header: &AccountHeader,
+        data: &[u8],
+    ) -> Result<VecDeque<FilterEdge>, CatscopeWasmError> {
+        let mut list = VecDeque::new();
+        let id = header.pubkey;
+        let reader = AccountReader::new(data);
+        let account = match self.idl.m_account.get(data) {
+            Some(x) => x,
+            None => return Ok(list),
+        };
+        #[cfg(target_os = "wasi")]
+        HostImport::log(format!(
+            "idl_edge - 1 - pubkey {}; account {}; data len {}",
+            id,
+            account.name,
+            data.len()
+        ));
+        let mut walked = Vec::new();
+        let field_list = match &account.layout {
+            AccountLayout::Fixed { size, list } => {
+                reader.require(DISCRIMINATOR_SIZE + size)?;
+                list
+            }
+            AccountLayout::Dynamic(layout) => {
+                let mut i = DISCRIMINATOR_SIZE;
+                if layout.walk(data, &mut i, &mut walked).is_none() {
+                    return Err(CatscopeWasmError::FailedToParse);
+                }
+                &walked
+            }
+        };
+        for (i, field) in field_list.iter() {
+            let pubkey = reader.pubkey_at(*i)?;
+            if pubkey == system_id {
+                continue;
+            }
+            let (from, to) = match field.direction {
+                RuleDirection::Out => (id, pubkey),
+                RuleDirection::In => (pubkey, id),
+            };
+            list.push_back(FilterEdge {
+                slot: header.slot,
+                weight: field.weight,
+                from,
+                to,
+                label: LABEL_IDL_FIELD,
+            });
+        }
+        Ok(list)
+    }
+
+    pub fn new(program_id: &Pubkey, idl: &Idl) -> Self {
+        Self {
+            idl: idl.clone(),
+            program_id: *program_id,
+        }
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        orca::{whirlpool_discriminator, whirlpoolconfig_discriminator, Orca},
+        primitive::tree::WEIGHT_SYMLINK,
+    };
+
+    use super::*; // import functions from the parent module
+
+    const ORCA: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+
+    // legacy (anchor < 0.30) layout of the whirlpool account
+    const WHIRLPOOL_IDL: &str = r#"{
+        "version": "0.3.0",
+        "name": "whirlpool",
+        "instructions": [],
+        "accounts": [{
+            "name": "Whirlpool",
+            "type": {"kind": "struct", "fields": [
+                {"name": "whirlpoolsConfig", "type": "publicKey"},
+                {"name": "whirlpoolBump", "type": {"array": ["u8", 1]}},
+                {"name": "tickSpacing", "type": "u16"},
+                {"name": "tickSpacingSeed", "type": {"array": ["u8", 2]}},
+                {"name": "feeRate", "type": "u16"},
+                {"name": "protocolFeeRate", "type": "u16"},
+                {"name": "liquidity", "type": "u128"},
+                {"name": "sqrtPrice", "type": "u128"},
+                {"name": "tickCurrentIndex", "type": "i32"},
+                {"name": "protocolFeeOwedA", "type": "u64"},
+                {"name": "protocolFeeOwedB", "type": "u64"},
+                {"name": "tokenMintA", "type": "publicKey"},
+                {"name": "tokenVaultA", "type": "publicKey"},
+                {"name": "feeGrowthGlobalA", "type": "u128"},
+                {"name": "tokenMintB", "type": "publicKey"},
+                {"name": "tokenVaultB", "type": "publicKey"},
+                {"name": "feeGrowthGlobalB", "type": "u128"},
+                {"name": "rewardLastUpdatedTimestamp", "type": "u64"},
+                {"name": "rewardInfos", "type": {"array": [{"defined": "WhirlpoolRewardInfo"}, 3]}}
+            ]}
+        }],
+        "types": [{
+            "name": "WhirlpoolRewardInfo",
+            "type": {"kind": "struct", "fields": [
+                {"name": "mint", "type": "publicKey"},
+                {"name": "vault", "type": "publicKey"},
+                {"name": "authority", "type": "publicKey"},
+                {"name": "emissionsPerSecondX64", "type": "u128"},
+                {"name": "growthGlobalX64", "type": "u128"}
+            ]}
+        }],
+        "metadata": {
+            "address": "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc",
+            "catscope": {
+                "Whirlpool.whirlpoolsConfig": {"direction": "in"},
+                "Whirlpool.tokenMintA": {"skip": true},
+                "Whirlpool.tokenMintB": {"skip": true},
+                "Whirlpool.rewardInfos.mint": {"skip": true},
+                "Whirlpool.rewardInfos.authority": {"weight": "SYMLINK"}
+            }
+        }
+    }"#;
+
+    fn header(pubkey: &str, data: &[u8]) -> AccountHeader {
+        AccountHeader {
+            pubkey: Pubkey::try_from(pubkey).unwrap(),
+            lamports: 488832,
+            data_size: data.len() as u32,
+            node_id: 100432,
+            owner: Pubkey::try_from(ORCA).unwrap(),
+            rent_epoch: 1,
+            slot: 1,
+            executable: false,
+        }
+    }
+
+    fn to_map(list: &VecDeque<FilterEdge>) -> HashMap<(Pubkey, Pubkey), Weight> {
+        list.iter().map(|e| ((e.from, e.to), e.weight)).collect()
+    }
+
+    #[test]
+    fn test_whirlpool_legacy_idl() {
+        let program_id = Pubkey::try_from(ORCA).unwrap();
+        let idl = Idl::parse(WHIRLPOOL_IDL).unwrap();
+        assert_eq!(idl.address, Some(program_id));
+        let account = idl
+            .m_account
+            .get_discriminator(&whirlpool_discriminator())
+            .unwrap();
+        assert!(matches!(
+            account.layout,
+            AccountLayout::Fixed { size: 645, .. }
+        ));
+
+        let data = std::fs::read("tests/data/whirlpool1.bin").unwrap();
+        let header = header("DtYKbQELgMZ3ihFUrCcCs9gy4djcUuhwgR7UpxVpP2Tg", &data);
+        let filter = IdlFilter::new(&program_id, &idl);
+        let m_edge = to_map(&filter.edge(&header, &data));
+        // every Orca edge is reproduced
+        for (k, w) in to_map(&Orca::new(&program_id).edge(&header, &data)) {
+            assert_eq!(m_edge.get(&k), Some(&w), "missing {:?}", k);
+        }
+        // only the whirlpool config is incoming; the rest come from the reward infos
+        for (k, w) in m_edge.iter() {
+            assert!(k.0 == header.pubkey || k.1 == header.pubkey);
+            assert!(*w == WEIGHT_DIRECT || *w == WEIGHT_SYMLINK);
+        }
+        assert_eq!(m_edge.keys().filter(|k| k.1 == header.pubkey).count(), 1);
+        assert!(filter.edge(&header, &data[..600]).is_empty());
+    }
+
+    #[test]
+    fn test_whirlpoolconfig_idl() {
+        // anchor >= 0.30 layout
+        let input = r#"{
+            "address": "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc",
+            "accounts": [{"name": "WhirlpoolsConfig", "discriminator": [157, 20, 49, 224, 217, 87, 193, 254]}],
+            "types": [{"name": "WhirlpoolsConfig", "type": {"kind": "struct", "fields": [
+                {"name": "fee_authority", "type": "pubkey"},
+                {"name": "collect_protocol_fees_authority", "type": "pubkey"},
+                {"name": "reward_emissions_super_authority", "type": "pubkey"},
+                {"name": "default_protocol_fee_rate", "type": "u16"}
+            ]}}]
+        }"#;
+        let program_id = Pubkey::try_from(ORCA).unwrap();
+        let idl = Idl::parse(input).unwrap();
+        assert!(idl
+            .m_account
+            .get_discriminator(&whirlpoolconfig_discriminator())
+            .is_some());
+        let data = std::fs::read("tests/data/whirlpoolconfig1.bin").unwrap();
+        let header = header("2LecshUwdy9xi7meFgHtFJQNSKk4KdTrcpvaB56dP2NQ", &data);
+        let list = IdlFilter::new(&program_id, &idl).edge(&header, &data);
+        let mut expected = Orca::new(&program_id).edge(&header, &data);
+        // the idl has no program edge
+        expected.retain(|e| e.from != program_id);
+        assert_eq!(to_map(&list), to_map(&expected));
+    }
+
+    #[test]
+    fn test_dynamic_layout() {
+        // solpipe refunds; a pubkey followed by a vec of claims
+        let input = r#"{
+            "accounts": [{"name": "Refunds", "type": {"kind": "struct", "fields": [
+                {"name": "pipeline", "type": "publicKey"},
+                {"name": "note", "type": {"option": "string"}},
+                {"name": "refunds", "type": {"vec": {"defined": {"name": "Claim"}}}}
+            ]}}],
+            "types": [{"name": "Claim", "type": {"kind": "struct", "fields": [
+                {"name": "bidder", "type": "publicKey"},
+                {"name": "balance", "type": "u64"}
+            ]}}],
+            "metadata": {"catscope": {"Refunds.pipeline": {"direction": "in"}}}
+        }"#;
+        let program_id = Pubkey::new_unique();
+        let idl = Idl::parse(input).unwrap();
+        let pipeline = Pubkey::new_unique();
+        let bidders = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut data = anchor_account_discriminator("Refunds").to_vec();
+        data.extend_from_slice(pipeline.as_ref());
+        data.push(1);
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(b"abc");
+        data.extend_from_slice(&2u32.to_le_bytes());
+        for b in bidders.iter() {
+            data.extend_from_slice(b.as_ref());
+            data.extend_from_slice(&5u64.to_le_bytes());
+        }
+        let header = AccountHeader {
+            pubkey: Pubkey::new_unique(),
+            owner: program_id,
+            slot: 1,
+            ..Default::default()
+        };
+        let filter = IdlFilter::new(&program_id, &idl);
+        let list = filter.edge(&header, &data);
+        assert_eq!(list.len(), 3);
+        assert_eq!((list[0].from, list[0].to), (pipeline, header.pubkey));
+        assert_eq!((list[1].from, list[1].to), (header.pubkey, bidders[0]));
+        assert_eq!((list[2].from, list[2].to), (header.pubkey, bidders[1]));
+        // a truncated vec yields nothing
+        assert!(filter.edge(&header, &data[..data.len() - 1]).is_empty());
+    }
+
+    #[test]
+    fn test_bad_idl() {
+        let unknown = r#"{"accounts": [{"name": "A", "type": {"kind": "struct", "fields": [
+            {"name": "a", "type": {"defined": "Missing"}}]}}]}"#;
+        assert!(Idl::parse(unknown).is_err());
+        let unused = r#"{"accounts": [{"name": "A", "type": {"kind": "struct", "fields": [
+            {"name": "a", "type": "u8"}]}}], "metadata": {"catscope": {"A.a": {"skip": true}}}}"#;
+        let err = Idl::parse(unused).unwrap_err();
+        assert!(
+            err.to_string().contains("matches no pubkey field"),
+            "{}",
+            err
+        );
+        let recursive = r#"{"accounts": [{"name": "A", "type": {"kind": "struct", "fields": [
+            {"name": "a", "type": {"defined": "B"}}]}}],
+            "types": [{"name": "B", "type": {"kind": "struct", "fields": [
+            {"name": "b", "type": {"defined": "B"}}]}}]}"#;
+        assert!(Idl::parse(recursive).is_err());
+    }
+}
@@ -1,28 +1,99 @@
+// `#[derive(EdgeSource)]` names this crate by path, including from inside it
+extern crate self as catscope_edge_generator;
+
+use self::meteora::Meteora;
 use self::orca::Orca;
-use self::raydium::Raydium;
+use self::raydium_amm::RaydiumAmm;
+use self::raydium_clmm::Raydium;
+use self::raydium_cpmm::RaydiumCpmm;
+#[cfg(target_os = "wasi")]
+use config::parse_filter_config;
+use config::{FilterConfig, FilterKind};
+use idl::IdlFilter;
+use lookup_table::LookupTable;
 #[cfg(target_os = "wasi")]
 use primitive::{
     filter::{ptr_to_filter, CatscopeFilter},
+    label::label_registry,
+    node::NODE_TYPE_UNKNOWN,
+    tree::{ProgramList, EDGE_LAYOUT_VERSION},
     wasmimport::HostImport,
     wasmstore::{AccountOnGuest, FilterEdgeWithNextPointer},
 };
-use primitive::{
-    guest::GuestFilter,
-    soltoken::SolToken,
-    tree::{parse_program_list, ProgramList},
-};
+use primitive::{guest::GuestFilter, soltoken::SolToken};
+use rule::RuleFilter;
 use safejar::Safejar;
 use solpipe::Solpipe;
+use stake::Stake;
 use std::collections::VecDeque;
+use vote::Vote;
 
 //pub mod all;
+pub mod config;
+pub mod dump;
+pub mod idl;
+pub mod lookup_table;
+pub mod meteora;
 pub mod orca;
 pub mod primitive;
-pub mod raydium;
+/// The Raydium CLMM filter under its old path.
+#[deprecated(note = "renamed to `raydium_clmm`")]
+pub mod raydium {
+    pub use super::raydium_clmm::*;
+}
+pub mod raydium_amm;
+pub mod raydium_clmm;
+pub mod raydium_cpmm;
+pub mod rule;
 pub mod safejar;
 pub mod solpipe;
+pub mod stake;
+/// Helpers shared by the unit tests and the benches; not part of the API.
+#[doc(hidden)]
+pub mod test_support {
+    use crate::DISCRIMINATOR_SIZE;
+
+    /// `size` bytes starting with the discriminator `d`, then non zero bytes, so every pubkey
+    /// read produces an edge.
+    pub fn account(d: [u8; DISCRIMINATOR_SIZE], size: usize) -> Vec<u8> {
+        let mut data: Vec<u8> = (0..size).map(|i| (i % 251 + 1) as u8).collect();
+        data[0..DISCRIMINATOR_SIZE].copy_from_slice(&d);
+        data
+    }
+}
+pub mod vote;
 
 pub(crate) const DISCRIMINATOR_SIZE: usize = 8;
+
+/// Build the filters enabled in the config.
+/// The system, token, Token-2022 and both BPF loader programs are always tracked.
+pub fn filter_list(config: &FilterConfig) -> VecDeque<Box<dyn GuestFilter + 'static>> {
+    let mut list: VecDeque<Box<dyn GuestFilter + 'static>> = VecDeque::new();
+    list.push_back(Box::new(SolToken::default()));
+    for (kind, program_id) in config.list.iter() {
+        let filter: Box<dyn GuestFilter + 'static> = match kind {
+            FilterKind::Safejar => Box::new(Safejar::new(program_id)),
+            FilterKind::Solpipe => Box::new(Solpipe::new(program_id)),
+            FilterKind::Orca => Box::new(Orca::new(program_id)),
+            FilterKind::RaydiumCpmm => Box::new(RaydiumCpmm::new(program_id)),
+            FilterKind::RaydiumAmm => Box::new(RaydiumAmm::new(program_id)),
+            FilterKind::RaydiumClmm => Box::new(Raydium::new(program_id)),
+            FilterKind::Meteora => Box::new(Meteora::new(program_id)),
+            FilterKind::Stake => Box::new(Stake::new(program_id)),
+            FilterKind::Vote => Box::new(Vote::new(program_id, config.epoch_schedule())),
+            FilterKind::LookupTable => Box::new(LookupTable::new(program_id)),
+        };
+        list.push_back(filter);
+    }
+    for (program_id, idl) in config.idl_list.iter() {
+        list.push_back(Box::new(IdlFilter::new(program_id, idl)));
+    }
+    if !config.rule_list.is_empty() {
+        list.push_back(Box::new(RuleFilter::new(&config.rule_list)));
+    }
+    list
+}
+
 /// A place holder for starting the web assembly.
 /// # Safety
 #[cfg(target_os = "wasi")]
@@ -39,9 +110,6 @@ pub extern "C" fn _start() -> i32 {
 pub unsafe extern "C" fn init() -> u64 {
     let mut hi = HostImport::default();
 
-    let mut list: VecDeque<Box<dyn GuestFilter + 'static>> = VecDeque::new();
-    list.push_back(Box::new(SolToken::default()));
-
     let args = match hi.init_args() {
         Ok(x) => match x {
             Some(y) => y,
@@ -49,29 +117,16 @@ pub unsafe extern "C" fn init() -> u64 {
         },
         Err(_) => return 0,
     };
-    let program_list = match parse_program_list(args.slice()) {
+    let config = match parse_filter_config(args.slice()) {
         Ok(x) => x,
-        Err(_) => return 0,
-    };
-    for (i, program_id) in program_list.iter().enumerate() {
-        match i {
-            0 => {
-                list.push_back(Box::new(Safejar::new(program_id)));
-            }
-            1 => {
-                list.push_back(Box::new(Solpipe::new(program_id)));
-            }
-            2 => {
-                list.push_back(Box::new(Orca::new(program_id)));
-            }
-            3 => {
-                list.push_back(Box::new(Raydium::new(program_id)));
-            }
-            _ => {}
+        Err(e) => {
+            HostImport::log(format!("init - failed to parse filter config: {}", e));
+            return 0;
         }
-    }
+    };
+    let list = filter_list(&config);
 
-    let filter = Box::new(CatscopeFilter::new(list, hi));
+    let filter = Box::new(CatscopeFilter::new(list, config.edge_cache_capacity(), hi));
     Box::into_raw(filter) as u64
 }
 
@@ -151,7 +206,28 @@ pub unsafe extern "C" fn program_list(cat_ptr: u64) -> u64 {
     out.pointer()
 }
 
+/// List the edge labels so the host can decode `FilterEdge::label`.
+/// The blob holds the length of the registry as a `u32`, followed by one `id,name` line per label.
+/// # Safety
+///
+#[cfg(target_os = "wasi")]
+#[no_mangle]
+pub unsafe extern "C" fn label_list(cat_ptr: u64) -> u64 {
+    let filter: &mut CatscopeFilter = ptr_to_filter(cat_ptr).unwrap();
+    let registry = label_registry();
+    let store = filter.store_mut();
+    let mut out = store.allocate(4 + registry.len()).unwrap();
+    {
+        let p = out.slice_mut();
+        p[0..4].copy_from_slice(&(registry.len() as u32).to_le_bytes());
+        p[4..].copy_from_slice(registry.as_bytes());
+    }
+    out.pointer()
+}
+
 /// Produce edges from reading an account.
+/// Edges that were emitted for an earlier version of the account and are now gone are
+/// returned with `WEIGHT_REMOVE` set; closed accounts return only removals.
 /// # Safety
 ///
 #[cfg(target_os = "wasi")]
@@ -174,7 +250,7 @@ pub unsafe extern "C" fn edge(cat_ptr: u64, ptr: u64, size: u32) -> u64 {
         h.pubkey, h.owner, h.lamports
     ));*/
     let data = a.data();
-    let mut list = filter.edge(h, data);
+    let mut list = filter.update(h, data);
     if list.is_empty() {
         return 0;
     }
@@ -186,8 +262,395 @@ pub unsafe extern "C" fn edge(cat_ptr: u64, ptr: u64, size: u32) -> u64 {
             .unwrap();
         let h = out.payload_mut::<FilterEdgeWithNextPointer>();
         h.edge = edge;
+        h.version = EDGE_LAYOUT_VERSION;
         h.next_pointer = last_ptr;
         last_ptr = out.pointer();
     }
     last_ptr
 }
+
+/// Classify an account; the account blob is passed the same way as to `edge`.
+/// Returns a `NodeType`, or `NODE_TYPE_UNKNOWN` when no filter recognizes the account.
+/// # Safety
+///
+#[cfg(target_os = "wasi")]
+#[no_mangle] // Prevent Rust from changing the function name
+pub unsafe extern "C" fn node_type(cat_ptr: u64, ptr: u64, size: u32) -> u64 {
+    let filter: &mut CatscopeFilter = ptr_to_filter(cat_ptr).unwrap();
+    let blob = filter
+        .store()
+        .recover_blob(ptr as usize, size as usize)
+        .unwrap();
+    let a: AccountOnGuest = match blob.try_into() {
+        Ok(x) => x,
+        Err(_) => return NODE_TYPE_UNKNOWN as u64,
+    };
+    filter.node_type(a.header(), a.data()) as u64
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{
+        bpf_loader_upgradeable, program_option::COption, program_pack::Pack, pubkey::Pubkey,
+        system_program::ID as system_id,
+    };
+    use solana_stake_interface::state::{Meta, StakeStateV2};
+    use solana_vote_interface::state::{VoteInit, VoteState, VoteStateVersions};
+    use spl_token::{
+        state::{Account, AccountState, Mint},
+        ID as token_id,
+    };
+
+    use crate::{
+        config::parse_filter_config,
+        meteora, orca,
+        primitive::{
+            common::anchor_account_discriminator,
+            header::AccountHeader,
+            node::NODE_TYPE_UNKNOWN,
+            token2022::TOKEN_2022_ID,
+            tree::{FilterEdge, Weight},
+        },
+        raydium_amm::AMM_INFO_SIZE,
+        raydium_clmm, safejar, solpipe,
+        test_support::account,
+    };
+
+    use super::*; // import functions from the parent module
+
+    const SIZE: usize = 10_000;
+
+    fn header(owner: &Pubkey, data: &[u8]) -> AccountHeader {
+        AccountHeader {
+            pubkey: Pubkey::new_from_array([7; 32]),
+            owner: *owner,
+            data_size: data.len() as u32,
+            slot: 1,
+            ..Default::default()
+        }
+    }
+
+    fn find<'a>(list: &'a VecDeque<Box<dyn GuestFilter>>, owner: &Pubkey) -> &'a dyn GuestFilter {
+        list.iter()
+            .find(|f| f.program_id_list().contains(owner))
+            .unwrap()
+            .as_ref()
+    }
+
+    fn route(
+        list: &VecDeque<Box<dyn GuestFilter>>,
+        owner: &Pubkey,
+        data: &[u8],
+    ) -> Vec<(Pubkey, Pubkey, Weight)> {
+        let edges: VecDeque<FilterEdge> = find(list, owner).edge(&header(owner, data), data);
+        edges.iter().map(|e| (e.from, e.to, e.weight)).collect()
+    }
+
+    // The discriminators are derived from the account names; these are the bytes the filters
+    // used to hard code, so a typo in a name cannot silently disable an account type.
+    #[test]
+    fn test_discriminator_list() {
+        let list: [([u8; 8], [u8; 8]); 31] = [
+            (
+                meteora::lbpair_discriminator(),
+                [33, 11, 49, 98, 181, 101, 177, 13],
+            ),
+            (
+                meteora::binarray_discriminator(),
+                [92, 142, 92, 220, 5, 148, 70, 181],
+            ),
+            (
+                meteora::binarraybitmapextension_discriminator(),
+                [80, 111, 124, 113, 55, 237, 18, 5],
+            ),
+            (
+                meteora::position_discriminator(),
+                [170, 188, 143, 228, 122, 64, 247, 208],
+            ),
+            (
+                meteora::positionv2_discriminator(),
+                [117, 176, 212, 199, 245, 180, 133, 182],
+            ),
+            (
+                orca::whirlpoolconfig_discriminator(),
+                [157, 20, 49, 224, 217, 87, 193, 254],
+            ),
+            (
+                orca::whirlpool_discriminator(),
+                [63, 149, 209, 12, 225, 128, 99, 9],
+            ),
+            (
+                orca::tickarray_discriminator(),
+                [69, 97, 189, 190, 110, 7, 66, 187],
+            ),
+            (
+                safejar::controller_discriminator(),
+                [184, 79, 171, 0, 183, 43, 113, 110],
+            ),
+            (
+                safejar::delegation_discriminator(),
+                [237, 90, 140, 159, 124, 255, 243, 80],
+            ),
+            (
+                safejar::ruleaccumulator_discriminator(),
+                [127, 132, 189, 170, 68, 38, 206, 135],
+            ),
+            (
+                safejar::spendrequest_discriminator(),
+                [71, 251, 215, 71, 98, 153, 90, 25],
+            ),
+            (
+                solpipe::agent_discriminator(),
+                [47, 166, 112, 147, 155, 197, 86, 7],
+            ),
+            (
+                solpipe::bidlist_discriminator(),
+                [233, 127, 13, 29, 123, 209, 192, 79],
+            ),
+            (
+                solpipe::refunds_discriminator(),
+                [169, 83, 174, 99, 135, 161, 12, 150],
+            ),
+            (
+                solpipe::controller_discriminator(),
+                [184, 79, 171, 0, 183, 43, 113, 110],
+            ),
+            (
+                solpipe::controllerapi_discriminator(),
+                [224, 136, 168, 42, 53, 0, 84, 163],
+            ),
+            (
+                solpipe::payout_discriminator(),
+                [69, 45, 245, 131, 218, 101, 158, 228],
+            ),
+            (
+                solpipe::periodring_discriminator(),
+                [61, 191, 59, 143, 226, 235, 104, 26],
+            ),
+            (
+                solpipe::pipeline_discriminator(),
+                [30, 82, 16, 218, 196, 77, 115, 224],
+            ),
+            (
+                solpipe::protocol_discriminator(),
+                [45, 39, 101, 43, 115, 72, 131, 40],
+            ),
+            (
+                solpipe::bidreceipt_discriminator(),
+                [186, 150, 141, 135, 59, 122, 39, 99],
+            ),
+            (
+                raydium_clmm::discriminator_amm_config(),
+                [218, 244, 33, 104, 203, 203, 43, 111],
+            ),
+            (
+                raydium_clmm::discriminator_observation_state(),
+                [122, 174, 197, 53, 129, 9, 165, 132],
+            ),
+            (
+                raydium_clmm::discriminator_operation_state(),
+                [19, 236, 58, 237, 81, 222, 183, 252],
+            ),
+            (
+                raydium_clmm::discriminator_personal_position_state(),
+                [70, 111, 150, 126, 230, 15, 25, 117],
+            ),
+            (
+                raydium_clmm::discriminator_pool_state(),
+                [247, 237, 227, 245, 215, 195, 222, 70],
+            ),
+            (
+                raydium_clmm::discriminator_protocol_position_state(),
+                [100, 226, 145, 99, 146, 218, 160, 106],
+            ),
+            (
+                raydium_clmm::discriminator_support_mint_associated(),
+                [134, 40, 183, 79, 12, 112, 162, 53],
+            ),
+            (
+                raydium_clmm::discriminator_tick_array_bitmap_extension(),
+                [60, 150, 36, 219, 97, 128, 139, 153],
+            ),
+            (
+                raydium_clmm::discriminator_tick_array_state(),
+                [192, 155, 85, 205, 49, 249, 129, 42],
+            ),
+        ];
+        for (k, (derived, expected)) in list.iter().enumerate() {
+            assert_eq!(derived, expected, "discriminator {}", k);
+        }
+    }
+
+    // Every filter must survive every truncation of the accounts it parses and either
+    // return all of its edges or none of them.
+    #[test]
+    fn test_truncated_accounts() {
+        let id: Vec<Pubkey> = (0..12).map(|_| Pubkey::new_unique()).collect();
+        let idl = r#"{"accounts": [{"name": "Refunds", "type": {"kind": "struct", "fields": [
+            {"name": "pipeline", "type": "publicKey"},
+            {"name": "refunds", "type": {"vec": "publicKey"}}]}}]}"#;
+        let input = format!(
+            "safejar={}\nsolpipe={}\norca={}\nraydium_cpmm={}\nraydium_amm={}\n\
+             raydium_clmm={}\nmeteora={}\nidl={},{}\nrule={},-,0,100,out,DIRECT,true\n\
+             stake={}\nvote={}\nlookup_table={}\n",
+            id[0],
+            id[1],
+            id[2],
+            id[3],
+            id[4],
+            id[5],
+            id[6],
+            id[7],
+            idl,
+            id[8],
+            id[9],
+            id[10],
+            id[11]
+        );
+        let config = parse_filter_config(input.as_bytes()).unwrap();
+        let list = filter_list(&config);
+
+        let mut sample: Vec<(Pubkey, Vec<u8>)> = Vec::new();
+        for d in [
+            safejar::controller_discriminator(),
+            safejar::delegation_discriminator(),
+        ] {
+            sample.push((id[0], account(d, SIZE)));
+        }
+        for d in [
+            solpipe::controller_discriminator(),
+            solpipe::controllerapi_discriminator(),
+            solpipe::pipeline_discriminator(),
+            solpipe::payout_discriminator(),
+            solpipe::periodring_discriminator(),
+            solpipe::agent_discriminator(),
+        ] {
+            sample.push((id[1], account(d, SIZE)));
+        }
+        // keep the vec lengths of the bid list and the refunds small
+        let mut data = account(solpipe::bidlist_discriminator(), SIZE);
+        data[41..45].copy_from_slice(&2u32.to_le_bytes());
+        sample.push((id[1], data));
+        let mut data = account(solpipe::refunds_discriminator(), SIZE);
+        data[40..44].copy_from_slice(&2u32.to_le_bytes());
+        sample.push((id[1], data));
+        for name in ["whirlpool1", "whirlpoolconfig1", "tickarray1"] {
+            let data = std::fs::read(format!("tests/data/{}.bin", name)).unwrap();
+            sample.push((id[2], data));
+        }
+        for d in [
+            raydium_clmm::discriminator_amm_config(),
+            raydium_clmm::discriminator_pool_state(),
+        ] {
+            sample.push((id[3], account(d, SIZE)));
+        }
+        sample.push((id[4], account([1; 8], AMM_INFO_SIZE)));
+        for d in [
+            raydium_clmm::discriminator_amm_config(),
+            raydium_clmm::discriminator_observation_state(),
+            raydium_clmm::discriminator_operation_state(),
+            raydium_clmm::discriminator_personal_position_state(),
+            raydium_clmm::discriminator_pool_state(),
+            raydium_clmm::discriminator_protocol_position_state(),
+            raydium_clmm::discriminator_tick_array_bitmap_extension(),
+            raydium_clmm::discriminator_tick_array_state(),
+        ] {
+            sample.push((id[5], account(d, SIZE)));
+        }
+        for d in [
+            meteora::lbpair_discriminator(),
+            meteora::binarray_discriminator(),
+            meteora::binarraybitmapextension_discriminator(),
+            meteora::position_discriminator(),
+            meteora::positionv2_discriminator(),
+        ] {
+            sample.push((id[6], account(d, SIZE)));
+        }
+        let mut data = account(anchor_account_discriminator("Refunds"), SIZE);
+        data[40..44].copy_from_slice(&3u32.to_le_bytes());
+        sample.push((id[7], data));
+        sample.push((id[8], account([1; 8], SIZE)));
+        let mut meta = Meta::default();
+        meta.lockup.custodian = Pubkey::new_from_array([5; 32]);
+        let mut data = borsh::to_vec(&StakeStateV2::Stake(
+            meta,
+            Default::default(),
+            Default::default(),
+        ))
+        .unwrap();
+        data.resize(StakeStateV2::size_of(), 0);
+        sample.push((id[9], data));
+        let init = VoteInit {
+            node_pubkey: Pubkey::new_from_array([6; 32]),
+            ..Default::default()
+        };
+        let state = VoteState::new(&init, &Default::default());
+        let mut data = vec![0; VoteState::size_of()];
+        VoteState::serialize(&VoteStateVersions::new_current(state), &mut data).unwrap();
+        sample.push((id[10], data));
+        let mut data = vec![0; lookup_table::LOOKUP_TABLE_META_SIZE + 3 * 32];
+        data[0] = 1;
+        data[4..12].fill(0xff);
+        data[21] = 1;
+        sample.push((id[11], data));
+        sample.push((system_id, account([1; 8], 100)));
+        let mut token_account = vec![0; Account::LEN];
+        Account {
+            mint: Pubkey::new_from_array([1; 32]),
+            owner: Pubkey::new_from_array([2; 32]),
+            delegate: COption::Some(Pubkey::new_from_array([3; 32])),
+            state: AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut token_account);
+        let mut mint = vec![0; Mint::LEN];
+        Mint {
+            mint_authority: COption::Some(Pubkey::new_from_array([4; 32])),
+            is_initialized: true,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut mint);
+        sample.push((token_id, token_account.clone()));
+        sample.push((token_id, mint));
+        let mut data = token_account;
+        data.extend_from_slice(&[2, 7, 0, 0, 0]);
+        sample.push((TOKEN_2022_ID, data));
+        let mut data = [&3u32.to_le_bytes()[..], &[9; 8], &[1], &[8; 32]].concat();
+        data.extend_from_slice(&[0x7f; 64]);
+        sample.push((bpf_loader_upgradeable::ID, data));
+
+        for (owner, data) in sample.iter() {
+            let full = route(&list, owner, data);
+            assert!(!full.is_empty(), "no edges for a full account of {}", owner);
+            // only the filters configured at run time cannot tell what an account is
+            if *owner != id[7] && *owner != id[8] {
+                assert_ne!(
+                    find(&list, owner).node_type(&header(owner, data), data),
+                    NODE_TYPE_UNKNOWN,
+                    "no node type for a full account of {}",
+                    owner
+                );
+            }
+            for len in 0..data.len() {
+                let edges = route(&list, owner, &data[..len]);
+                // the token program tells accounts and mints apart by their size
+                if *owner == token_id || *owner == TOKEN_2022_ID {
+                    continue;
+                }
+                // a lookup table holds as many addresses as fit in the account, so a truncated
+                // table may still list a prefix of the addresses
+                if *owner == id[11] && full.starts_with(&edges) {
+                    continue;
+                }
+                assert!(
+                    edges.is_empty() || edges == full,
+                    "partial edges for owner {} at length {} of {}",
+                    owner,
+                    len,
+                    data.len()
+                );
+            }
+        }
+    }
+}
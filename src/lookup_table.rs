@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+
+use solana_sdk::pubkey::Pubkey;
+
+#[cfg(target_os = "wasi")]
+use crate::primitive::wasmimport::HostImport;
+use crate::primitive::{
+    common::{AccountReader, PUBKEY_LEN, U32_LEN, U64_LEN},
+    err::CatscopeWasmError,
+    guest::{edge_or_log, GuestFilter},
+    header::AccountHeader,
+    label::{LABEL_LOOKUP_TABLE_ADDRESS, LABEL_LOOKUP_TABLE_AUTHORITY},
+    node::{NodeType, NODE_TYPE_LOOKUP_TABLE, NODE_TYPE_UNKNOWN},
+    tree::{FilterEdge, WEIGHT_AUTHORITY, WEIGHT_SYMLINK},
+};
+
+// `ProgramState` is bincode encoded: a u32 variant tag followed by `LookupTableMeta`.
+const DEACTIVATION_SLOT_OFFSET: usize = U32_LEN;
+// deactivation_slot, last_extended_slot, last_extended_slot_start_index
+const AUTHORITY_OFFSET: usize = U32_LEN + 2 * U64_LEN + 1;
+/// The addresses start after the metadata and its padding.
+pub const LOOKUP_TABLE_META_SIZE: usize = 56;
+
+/// Links address lookup tables to their authority and to every address they hold.
+pub struct LookupTable {
+    pub program_id: Pubkey,
+}
+
+impl GuestFilter for LookupTable {
+    fn program_id_list(&self) -> Vec<Pubkey> {
+        vec![self.program_id]
+    }
+
+    fn edge(&self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge> {
+        edge_or_log("lookup_table", header, self.try_edge(header, data))
+    }
+
+    fn node_type(&self, _header: &AccountHeader, data: &[u8]) -> NodeType {
+        match AccountReader::new(data).u32_at(0) {
+            Ok(1) => NODE_TYPE_LOOKUP_TABLE,
+            _ => NODE_TYPE_UNKNOWN,
+        }
+    }
+}
+
+impl LookupTable {
+    fn try_edge(
+        &self,
+        header: &AccountHeader,
+        data: &[u8],
+    ) -> Result<VecDeque<FilterEdge>, CatscopeWasmError> {
+        let id = header.pubkey;
+        let reader = AccountReader::new(data);
+        #[cfg(target_os = "wasi")]
+        HostImport::log(format!(
+            "lookup_table_edge - 1 - pubkey {}; data len {}",
+            id,
+            data.len()
+        ));
+        match reader.u32_at(0)? {
+            // Uninitialized
+            0 => return Ok(VecDeque::new()),
+            // LookupTable
+            1 => {}
+            _ => return Err(CatscopeWasmError::FailedToParse),
+        }
+        reader.require(LOOKUP_TABLE_META_SIZE)?;
+        let address_size = data.len() - LOOKUP_TABLE_META_SIZE;
+        let count = address_size / PUBKEY_LEN;
+        if count * PUBKEY_LEN != address_size {
+            return Err(CatscopeWasmError::FailedToParse);
+        }
+        // a table holds up to 256 addresses; size the list once
+        let mut list = VecDeque::with_capacity(count + 1);
+        // frozen tables have no authority
+        let authority = match reader.u8_at(AUTHORITY_OFFSET)? {
+            0 => None,
+            1 => Some(reader.pubkey_at(AUTHORITY_OFFSET + 1)?),
+            _ => return Err(CatscopeWasmError::FailedToParse),
+        };
+        if let Some(authority) = authority {
+            // table to authority
+            list.push_back(FilterEdge {
+                slot: header.slot,
+                from: id,
+                to: authority,
+                weight: WEIGHT_AUTHORITY,
+                label: LABEL_LOOKUP_TABLE_AUTHORITY,
+            });
+        }
+        // Deactivated tables cannot be used by new transactions, so their addresses are dropped.
+        // The authority keeps its edge until the table is closed.
+        if reader.u64_at(DEACTIVATION_SLOT_OFFSET)? != u64::MAX {
+            return Ok(list);
+        }
+        for k in 0..count {
+            // table to address
+            list.push_back(FilterEdge {
+                slot: header.slot,
+                from: id,
+                to: reader.pubkey_at(LOOKUP_TABLE_META_SIZE + k * PUBKEY_LEN)?,
+                weight: WEIGHT_SYMLINK,
+                label: LABEL_LOOKUP_TABLE_ADDRESS,
+            });
+        }
+        Ok(list)
+    }
+
+    pub fn new(program_id: &Pubkey) -> Self {
+        Self {
+            program_id: *program_id,
+        }
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*; // import functions from the parent module
+
+    const LOOKUP_TABLE: &str = "AddressLookupTab1e1111111111111111111111111";
+
+    // a table as written by the program
+    fn table(deactivation_slot: u64, authority: Option<&Pubkey>, address: &[Pubkey]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&deactivation_slot.to_le_bytes());
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.push(0);
+        match authority {
+            Some(x) => {
+                data.push(1);
+                data.extend_from_slice(x.as_ref());
+            }
+            None => data.extend_from_slice(&[0; 33]),
+        }
+        data.extend_from_slice(&[0; 2]);
+        assert_eq!(data.len(), LOOKUP_TABLE_META_SIZE);
+        for x in address.iter() {
+            data.extend_from_slice(x.as_ref());
+        }
+        data
+    }
+
+    #[test]
+    fn test_lookup_table() {
+        let filter = LookupTable::new(&Pubkey::try_from(LOOKUP_TABLE).unwrap());
+        let header = AccountHeader {
+            pubkey: Pubkey::new_unique(),
+            owner: filter.program_id,
+            slot: 8,
+            ..Default::default()
+        };
+        let id = header.pubkey;
+        let authority = Pubkey::new_unique();
+        let address: Vec<Pubkey> = (0..256).map(|_| Pubkey::new_unique()).collect();
+
+        let data = table(u64::MAX, Some(&authority), &address);
+        let list = filter.edge(&header, &data);
+        assert_eq!(list.len(), 257);
+        assert_eq!(
+            (list[0].from, list[0].to, list[0].weight),
+            (id, authority, WEIGHT_AUTHORITY)
+        );
+        for (edge, x) in list.iter().skip(1).zip(address.iter()) {
+            assert_eq!((edge.from, edge.to, edge.weight), (id, *x, WEIGHT_SYMLINK));
+        }
+        // frozen tables keep their addresses
+        let data = table(u64::MAX, None, &address[..3]);
+        let list = filter.edge(&header, &data);
+        assert_eq!(list.len(), 3);
+        assert!(list.iter().all(|e| e.weight == WEIGHT_SYMLINK));
+        // deactivated tables only keep the authority
+        let data = table(100, Some(&authority), &address[..3]);
+        let list = filter.edge(&header, &data);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].to, authority);
+        // empty, uninitialized and malformed tables
+        assert!(filter.edge(&header, &table(u64::MAX, None, &[])).is_empty());
+        assert!(filter.edge(&header, &[0; 56]).is_empty());
+        let data = table(u64::MAX, Some(&authority), &address[..2]);
+        assert!(filter.edge(&header, &data[..data.len() - 1]).is_empty());
+        assert!(filter.edge(&header, &data[..40]).is_empty());
+    }
+}
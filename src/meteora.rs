@@ -5,18 +5,48 @@ use solana_sdk::{pubkey::Pubkey, system_program::ID as system_id};
 #[cfg(target_os = "wasi")]
 use crate::primitive::wasmimport::HostImport;
 use crate::primitive::{
-    common::match_discriminator,
-    guest::GuestFilter,
+    common::{anchor_account_discriminator, AccountReader},
+    dispatch::DispatchTable,
+    err::CatscopeWasmError,
+    guest::{edge_or_log, GuestFilter},
     header::AccountHeader,
+    label::{
+        LABEL_METEORA_BIN_ARRAY_BITMAP_EXTENSION_LB_PAIR, LABEL_METEORA_BIN_ARRAY_LB_PAIR,
+        LABEL_METEORA_LB_PAIR_BASE_KEY, LABEL_METEORA_LB_PAIR_CREATOR,
+        LABEL_METEORA_LB_PAIR_ORACLE, LABEL_METEORA_LB_PAIR_PRE_ACTIVATION_SWAP_ADDRESS,
+        LABEL_METEORA_LB_PAIR_PROGRAM, LABEL_METEORA_LB_PAIR_RESERVE_X,
+        LABEL_METEORA_LB_PAIR_RESERVE_Y, LABEL_METEORA_LB_PAIR_TOKEN_X_MINT,
+        LABEL_METEORA_LB_PAIR_TOKEN_Y_MINT, LABEL_METEORA_POSITION_LB_PAIR,
+        LABEL_METEORA_POSITION_OWNER, LABEL_METEORA_POSITION_V2_FEE_OWNER,
+        LABEL_METEORA_POSITION_V2_OPERATOR,
+    },
+    node::{
+        NodeType, NODE_TYPE_METEORA_BIN_ARRAY, NODE_TYPE_METEORA_BIN_ARRAY_BITMAP_EXTENSION,
+        NODE_TYPE_METEORA_LB_PAIR, NODE_TYPE_METEORA_POSITION, NODE_TYPE_METEORA_POSITION_V2,
+        NODE_TYPE_UNKNOWN,
+    },
     tree::{FilterEdge, WEIGHT_DIRECT},
 };
 
+// minimum account sizes; every offset read below must fit inside of these.
+pub const LB_PAIR_SIZE: usize = 904;
+pub const BIN_ARRAY_MIN_SIZE: usize = 56;
+pub const BIN_ARRAY_EXT_MIN_SIZE: usize = 40;
+pub const POSITION_MIN_SIZE: usize = 72;
+pub const POSITION_V2_SIZE: usize = 8120;
+
+/// The accounts this filter parses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AccountKind {
+    LbPair,
+    BinArray,
+    BinArrayBitmapExtension,
+    Position,
+    PositionV2,
+}
+
 pub struct Meteora {
-    d_lb_pair: [u8; 8],
-    d_bin_array: [u8; 8],
-    d_bin_array_ext: [u8; 8],
-    d_position: [u8; 8],
-    d_position_v2: [u8; 8],
+    dispatch: DispatchTable<AccountKind>,
     pub program_id: Pubkey,
 }
 
@@ -26,10 +56,34 @@ impl GuestFilter for Meteora {
     }
 
     fn edge(&self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge> {
+        edge_or_log("meteora", header, self.try_edge(header, data))
+    }
+
+    fn node_type(&self, _header: &AccountHeader, data: &[u8]) -> NodeType {
+        match self.dispatch.get(data) {
+            Some(AccountKind::LbPair) => NODE_TYPE_METEORA_LB_PAIR,
+            Some(AccountKind::BinArray) => NODE_TYPE_METEORA_BIN_ARRAY,
+            Some(AccountKind::BinArrayBitmapExtension) => {
+                NODE_TYPE_METEORA_BIN_ARRAY_BITMAP_EXTENSION
+            }
+            Some(AccountKind::Position) => NODE_TYPE_METEORA_POSITION,
+            Some(AccountKind::PositionV2) => NODE_TYPE_METEORA_POSITION_V2,
+            None => NODE_TYPE_UNKNOWN,
+        }
+    }
+}
+
+impl Meteora {
+    fn try_edge(
+        &self,
+        header: &AccountHeader,
+        data: &[u8],
+    ) -> Result<VecDeque<FilterEdge>, CatscopeWasmError> {
         let mut list = VecDeque::new();
         let id = header.pubkey;
         let pubkey_len = std::mem::size_of::<Pubkey>();
         let mut i;
+        let reader = AccountReader::new(data);
 
         #[cfg(target_os = "wasi")]
         HostImport::log(format!(
@@ -37,251 +91,467 @@ impl GuestFilter for Meteora {
             id,
             data.len()
         ));
-        if match_discriminator(&self.d_lb_pair, data) {
-            #[cfg(target_os = "wasi")]
-            HostImport::log(format!("meteora_edge - 2 - lb_pair - pubkey {};", id));
-            
-            // program → lb_pair
-            list.push_back(FilterEdge {
-                slot: header.slot,
-                weight: WEIGHT_DIRECT,
-                from: self.program_id,
-                to: id,
-            });
+        match self.dispatch.get(data) {
+            Some(AccountKind::LbPair) => {
+                reader.require(LB_PAIR_SIZE)?;
+                #[cfg(target_os = "wasi")]
+                HostImport::log(format!("meteora_edge - 2 - lb_pair - pubkey {};", id));
 
-            // token_0_mint
-            {
-                i = 88;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey 
-                    });
-                }
-            }
+                // program → lb_pair
+                list.push_back(FilterEdge {
+                    slot: header.slot,
+                    weight: WEIGHT_DIRECT,
+                    from: self.program_id,
+                    to: id,
+                    label: LABEL_METEORA_LB_PAIR_PROGRAM,
+                });
 
-            // token_1_mint
-            {
-                i = 120;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey 
-                    });
-                }
-            }
-            // reserve_x
-            {
-                i = 152;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey 
-                    });
-                }
-            }
-            // reserve_y
-            {
-                i = 184;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey 
-                    });
+                // token_x_mint, token_y_mint, reserve_x, reserve_y
+                for (k, label) in [
+                    LABEL_METEORA_LB_PAIR_TOKEN_X_MINT,
+                    LABEL_METEORA_LB_PAIR_TOKEN_Y_MINT,
+                    LABEL_METEORA_LB_PAIR_RESERVE_X,
+                    LABEL_METEORA_LB_PAIR_RESERVE_Y,
+                ]
+                .into_iter()
+                .enumerate()
+                {
+                    i = 88 + k * pubkey_len;
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_id {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_DIRECT,
+                            from: id,
+                            to: pubkey,
+                            label,
+                        });
+                    }
                 }
-            }
-            // oracle
-            {
-                i = 488;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey 
-                    });
+                // oracle; after protocol_fee (16), padding1 (32) and reward_infos (2 x 144)
+                {
+                    i = 552;
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_id {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_DIRECT,
+                            from: id,
+                            to: pubkey,
+                            label: LABEL_METEORA_LB_PAIR_ORACLE,
+                        });
+                    }
                 }
-            }
-            // pre_activation_swap_address
-            {
-                i = 688;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey 
-                    });
+                // pre_activation_swap_address
+                {
+                    i = 752;
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_id {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_DIRECT,
+                            from: id,
+                            to: pubkey,
+                            label: LABEL_METEORA_LB_PAIR_PRE_ACTIVATION_SWAP_ADDRESS,
+                        });
+                    }
                 }
-            }
-            // base_key
-            {
-                i = 720;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey 
-                    });
-                }
-            }
-            // creator
-            {
-                i = 784;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey 
-                    });
+                // base_key
+                {
+                    i = 784;
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_id {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_DIRECT,
+                            from: id,
+                            to: pubkey,
+                            label: LABEL_METEORA_LB_PAIR_BASE_KEY,
+                        });
+                    }
                 }
-            } else if match_discriminator(&self.d_bin_array, data) {
-            {
-                i = 24;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey });
-                }
-            } else if match_discriminator(&self.d_bin_array_ext, data) {
-            {
-                i = 8;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey 
-                    });
+                // creator
+                {
+                    i = 848;
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_id {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_DIRECT,
+                            from: id,
+                            to: pubkey,
+                            label: LABEL_METEORA_LB_PAIR_CREATOR,
+                        });
+                    }
                 }
             }
-            } else if match_discriminator(&self.d_position, data) {
-            // lb_pair
-            {
-                i = 8;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey 
-                    });
+            Some(AccountKind::BinArray) => {
+                reader.require(BIN_ARRAY_MIN_SIZE)?;
+                // lb_pair
+                {
+                    i = 24;
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_id {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_DIRECT,
+                            from: pubkey,
+                            to: id,
+                            label: LABEL_METEORA_BIN_ARRAY_LB_PAIR,
+                        });
+                    }
                 }
             }
-            // owner
-            {
-                i = 40;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey 
-                    });
+            Some(AccountKind::BinArrayBitmapExtension) => {
+                reader.require(BIN_ARRAY_EXT_MIN_SIZE)?;
+                // lb_pair
+                {
+                    i = 8;
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_id {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_DIRECT,
+                            from: pubkey,
+                            to: id,
+                            label: LABEL_METEORA_BIN_ARRAY_BITMAP_EXTENSION_LB_PAIR,
+                        });
+                    }
                 }
             }
-        } else if match_discriminator(&self.d_position_v2, data) {
-            // lb_pair
-            {
-                i = 8;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey 
-                    });
-                }
+            Some(AccountKind::Position) => {
+                reader.require(POSITION_MIN_SIZE)?;
+                self.position_edge(header, &reader, &mut list)?;
             }
-            // owner
-            {
-                i = 40;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey 
-                    });
+            Some(AccountKind::PositionV2) => {
+                reader.require(POSITION_V2_SIZE)?;
+                self.position_edge(header, &reader, &mut list)?;
+                // operator
+                {
+                    i = 7960;
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_id {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_DIRECT,
+                            from: id,
+                            to: pubkey,
+                            label: LABEL_METEORA_POSITION_V2_OPERATOR,
+                        });
+                    }
                 }
-            }
-            // operator
-            {
-                i = 248;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey 
-                    });
-                }
-            }
-            // fee_owner
-            {
-                i = 281;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge { 
-                        slot: header.slot, 
-                        weight: WEIGHT_DIRECT, 
-                        from: id, 
-                        to: pubkey 
-                    });
+                // fee_owner
+                {
+                    i = 8001;
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_id {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_DIRECT,
+                            from: id,
+                            to: pubkey,
+                            label: LABEL_METEORA_POSITION_V2_FEE_OWNER,
+                        });
+                    }
                 }
             }
+            None => {}
         }
 
         #[cfg(target_os = "wasi")]
         HostImport::log(format!("meteora_edge - 4 - pubkey {};", id));
 
-        list
+        Ok(list)
     }
-}
 
-impl Meteora {
     pub fn new(program_id: &Pubkey) -> Self {
-        let d_lb_pair = [33, 11, 49, 98, 181, 101, 177, 13];
-        let d_bin_array = [92, 142, 92, 220, 5, 148, 70, 181];
-        let d_bin_array_ext = [80, 111, 124, 113, 55, 237, 18, 5];
-        let d_position = [170, 188, 143, 228, 122, 64, 247, 208];
-        let d_position_v2 = [117, 176, 212, 199, 245, 180, 133, 182];
-
         Self {
             program_id: *program_id,
-            d_lb_pair,
-            d_bin_array,
-            d_bin_array_ext,
-            d_position,
-            d_position_v2,
+            dispatch: [
+                (lbpair_discriminator(), AccountKind::LbPair),
+                (binarray_discriminator(), AccountKind::BinArray),
+                (
+                    binarraybitmapextension_discriminator(),
+                    AccountKind::BinArrayBitmapExtension,
+                ),
+                (position_discriminator(), AccountKind::Position),
+                (positionv2_discriminator(), AccountKind::PositionV2),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+    // Position and PositionV2 share the lb_pair and owner prefix.
+    fn position_edge(
+        &self,
+        header: &AccountHeader,
+        reader: &AccountReader,
+        list: &mut VecDeque<FilterEdge>,
+    ) -> Result<(), CatscopeWasmError> {
+        let id = header.pubkey;
+        // lb_pair
+        {
+            let i = 8;
+            let pubkey = reader.pubkey_at(i)?;
+            if pubkey != system_id {
+                list.push_back(FilterEdge {
+                    slot: header.slot,
+                    weight: WEIGHT_DIRECT,
+                    from: pubkey,
+                    to: id,
+                    label: LABEL_METEORA_POSITION_LB_PAIR,
+                });
+            }
         }
+        // owner
+        {
+            let i = 40;
+            let pubkey = reader.pubkey_at(i)?;
+            if pubkey != system_id {
+                list.push_back(FilterEdge {
+                    slot: header.slot,
+                    weight: WEIGHT_DIRECT,
+                    from: id,
+                    to: pubkey,
+                    label: LABEL_METEORA_POSITION_OWNER,
+                });
+            }
+        }
+        Ok(())
     }
 }
 
+pub fn lbpair_discriminator() -> [u8; 8] {
+    anchor_account_discriminator("LbPair")
+}
+pub fn binarray_discriminator() -> [u8; 8] {
+    anchor_account_discriminator("BinArray")
+}
+pub fn binarraybitmapextension_discriminator() -> [u8; 8] {
+    anchor_account_discriminator("BinArrayBitmapExtension")
+}
+pub fn position_discriminator() -> [u8; 8] {
+    anchor_account_discriminator("Position")
+}
+pub fn positionv2_discriminator() -> [u8; 8] {
+    anchor_account_discriminator("PositionV2")
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::primitive::{label::EdgeLabel, tree::Weight};
+
+    use super::*; // import functions from the parent module
+
+    // The fixtures in tests/data/meteora-synthetic-*.bin are synthetic: they were laid out field
+    // by field from the lb_clmm IDL, the same reading of the layout as the code under test, with
+    // non zero filler in every field that is not a pubkey so a wrong offset reads the wrong key.
+    // The mints of the lb pair are wrapped SOL and USDC; the other keys are made up. They are
+    // not a substitute for dumps of live accounts, which can be taken with:
+    //   solana account --output-file=./tests/data/meteora-lbpair1.bin <lb pair>
+
+    fn program_id() -> Pubkey {
+        Pubkey::try_from("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo").unwrap()
+    }
+
+    fn key(s: &str) -> Pubkey {
+        Pubkey::try_from(s).unwrap()
+    }
+
+    fn fixture(name: &str, pubkey: &str) -> (AccountHeader, Vec<u8>) {
+        let data = std::fs::read(format!("tests/data/{}.bin", name)).unwrap();
+        let header = AccountHeader {
+            pubkey: key(pubkey),
+            lamports: 488832,
+            data_size: data.len() as u32,
+            node_id: 100432,
+            owner: program_id(),
+            rent_epoch: 1,
+            slot: 1,
+            executable: false,
+        };
+        (header, data)
+    }
+
+    // the other end of each edge, whether the edge leaves the account, its weight and label
+    fn edge_map(header: &AccountHeader, data: &[u8]) -> HashMap<Pubkey, (bool, Weight, EdgeLabel)> {
+        let filter = Meteora::new(&program_id());
+        let mut list = filter.edge(header, data);
+        let mut m_edge = HashMap::default();
+        while let Some(edge) = list.pop_front() {
+            if edge.from == header.pubkey {
+                // from
+                m_edge.insert(edge.to, (true, edge.weight, edge.label));
+            } else {
+                // to
+                m_edge.insert(edge.from, (false, edge.weight, edge.label));
+            }
+        }
+        m_edge
+    }
+
+    const LB_PAIR: &str = "7vP2WB5rXA4oRK5KEoWnR2VPRFyMqsrCo6Fv21Jjmq8U";
+
+    #[test]
+    fn test_synthetic_lb_pair() {
+        let (header, data) = fixture("meteora-synthetic-lbpair", LB_PAIR);
+        assert_eq!(data.len(), LB_PAIR_SIZE);
+        let m_edge = edge_map(&header, &data);
+        assert_eq!(m_edge.len(), 9, "lb pair must have 9 edges");
+        assert_eq!(
+            *m_edge.get(&program_id()).unwrap(),
+            (false, WEIGHT_DIRECT, LABEL_METEORA_LB_PAIR_PROGRAM)
+        );
+        for (pubkey, label) in [
+            (
+                "So11111111111111111111111111111111111111112",
+                LABEL_METEORA_LB_PAIR_TOKEN_X_MINT,
+            ),
+            (
+                "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+                LABEL_METEORA_LB_PAIR_TOKEN_Y_MINT,
+            ),
+            (
+                "5jLDmbkbjrtPTkSLVk4CXQcWZw38Y56xVxiZinJat5xk",
+                LABEL_METEORA_LB_PAIR_RESERVE_X,
+            ),
+            (
+                "3bU8hYs8LMqupRZh96f7zBiS7k2Mvy43RYn4Qp8kfkxa",
+                LABEL_METEORA_LB_PAIR_RESERVE_Y,
+            ),
+            (
+                "FLerL2AVU4Et79x23pA9CnQY2anGE4JurjWWCnSjkNEo",
+                LABEL_METEORA_LB_PAIR_ORACLE,
+            ),
+            (
+                "39A2SyqDtXq4sSj5UcohBJYr8tbKvZHiEQo6gm1X5UXL",
+                LABEL_METEORA_LB_PAIR_PRE_ACTIVATION_SWAP_ADDRESS,
+            ),
+            (
+                "giYobkMVp3DmSRSVSWMpsMXvHEy5ZpRLT9yR3bK9SHc",
+                LABEL_METEORA_LB_PAIR_BASE_KEY,
+            ),
+            (
+                "7Ne54qgpN7fFY3kNLdMj2RrdgFEtvMbTbyag2bwnhhmY",
+                LABEL_METEORA_LB_PAIR_CREATOR,
+            ),
+        ] {
+            assert_eq!(
+                *m_edge.get(&key(pubkey)).unwrap(),
+                (true, WEIGHT_DIRECT, label),
+                "lb pair edge to {}",
+                pubkey
+            );
+        }
+    }
+    #[test]
+    fn test_synthetic_bin_array() {
+        let (header, data) = fixture(
+            "meteora-synthetic-binarray",
+            "xbDVUmoXbwDhLe1RD43WNK6QwkgUqGC7WgeAwYD26gw",
+        );
+        let m_edge = edge_map(&header, &data);
+        assert_eq!(m_edge.len(), 1, "bin array must have 1 edge");
+        assert_eq!(
+            *m_edge.get(&key(LB_PAIR)).unwrap(),
+            (false, WEIGHT_DIRECT, LABEL_METEORA_BIN_ARRAY_LB_PAIR)
+        );
+    }
+    #[test]
+    fn test_synthetic_bin_array_bitmap_extension() {
+        let (header, data) = fixture(
+            "meteora-synthetic-binarraybitmapextension",
+            "5tBaxnNQXqJWa2RSELbutLt2B3Ao44YauQ9mD317nK4T",
+        );
+        let m_edge = edge_map(&header, &data);
+        assert_eq!(m_edge.len(), 1, "bitmap extension must have 1 edge");
+        assert_eq!(
+            *m_edge.get(&key(LB_PAIR)).unwrap(),
+            (
+                false,
+                WEIGHT_DIRECT,
+                LABEL_METEORA_BIN_ARRAY_BITMAP_EXTENSION_LB_PAIR
+            )
+        );
+    }
+    #[test]
+    fn test_synthetic_position() {
+        let (header, data) = fixture(
+            "meteora-synthetic-position",
+            "4JFaagdMHNxjZMUgGgCexs6cDsXY9GcyT5nBwvoDgvff",
+        );
+        let m_edge = edge_map(&header, &data);
+        assert_eq!(m_edge.len(), 2, "position must have 2 edges");
+        assert_eq!(
+            *m_edge.get(&key(LB_PAIR)).unwrap(),
+            (false, WEIGHT_DIRECT, LABEL_METEORA_POSITION_LB_PAIR)
+        );
+        assert_eq!(
+            *m_edge
+                .get(&key("3oamFMUCN2PHCDh2851P7DZ4dv2PTBPyhSEE3NRRLD57"))
+                .unwrap(),
+            (true, WEIGHT_DIRECT, LABEL_METEORA_POSITION_OWNER)
+        );
+    }
+    #[test]
+    fn test_synthetic_position_v2() {
+        let (header, data) = fixture(
+            "meteora-synthetic-positionv2",
+            "9YZYeEGxUC1GTja6CfQSymhvn3XhVwooP14yHWbCh1yM",
+        );
+        assert_eq!(data.len(), POSITION_V2_SIZE);
+        let m_edge = edge_map(&header, &data);
+        assert_eq!(m_edge.len(), 4, "position v2 must have 4 edges");
+        assert_eq!(
+            *m_edge.get(&key(LB_PAIR)).unwrap(),
+            (false, WEIGHT_DIRECT, LABEL_METEORA_POSITION_LB_PAIR)
+        );
+        for (pubkey, label) in [
+            (
+                "BK2A6eKF8VDtwEfFxoLANLYaww7d4UzPZoWnb14EL8c5",
+                LABEL_METEORA_POSITION_OWNER,
+            ),
+            (
+                "DiTpGfCtsC4SidLJ9afeC9TjZ2N7HfxHxTjG5q5nR4kr",
+                LABEL_METEORA_POSITION_V2_OPERATOR,
+            ),
+            (
+                "FUdvVgHKPDPnFxXP7XhFhUrJLgKvmvBzT5mvgAKvUimV",
+                LABEL_METEORA_POSITION_V2_FEE_OWNER,
+            ),
+        ] {
+            assert_eq!(
+                *m_edge.get(&key(pubkey)).unwrap(),
+                (true, WEIGHT_DIRECT, label),
+                "position v2 edge to {}",
+                pubkey
+            );
+        }
+    }
+    #[test]
+    fn test_synthetic_short_buffer() {
+        for (name, size) in [
+            ("meteora-synthetic-lbpair", LB_PAIR_SIZE),
+            ("meteora-synthetic-binarray", BIN_ARRAY_MIN_SIZE),
+            (
+                "meteora-synthetic-binarraybitmapextension",
+                BIN_ARRAY_EXT_MIN_SIZE,
+            ),
+            ("meteora-synthetic-position", POSITION_MIN_SIZE),
+            ("meteora-synthetic-positionv2", POSITION_V2_SIZE),
+        ] {
+            let (header, data) = fixture(name, LB_PAIR);
+            for len in [8, size / 2, size - 1] {
+                assert!(
+                    edge_map(&header, &data[..len]).is_empty(),
+                    "{} cut to {} bytes must have no edges",
+                    name,
+                    len
+                );
+            }
+        }
+    }
+}
@@ -1,19 +1,98 @@
 use std::collections::VecDeque;
 
-use solana_sdk::{pubkey::Pubkey, system_program::ID as system_id};
+use solana_sdk::pubkey::Pubkey;
 
 #[cfg(target_os = "wasi")]
 use crate::primitive::wasmimport::HostImport;
 use crate::primitive::{
-    common::match_discriminator,
-    guest::GuestFilter,
+    common::anchor_account_discriminator,
+    dispatch::DispatchTable,
+    err::CatscopeWasmError,
+    guest::{edge_or_log, GuestFilter},
     header::AccountHeader,
-    tree::{FilterEdge, WEIGHT_DIRECT},
+    node::{
+        NodeType, NODE_TYPE_ORCA_TICK_ARRAY, NODE_TYPE_ORCA_WHIRLPOOL,
+        NODE_TYPE_ORCA_WHIRLPOOLS_CONFIG, NODE_TYPE_UNKNOWN,
+    },
+    source::{EdgeSource, Skip},
+    tree::FilterEdge,
 };
+
+/// The accounts this filter parses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AccountKind {
+    WhirlpoolsConfig,
+    Whirlpool,
+    TickArray,
+}
+
+#[derive(EdgeSource)]
+#[edge(program, weight = DIRECT, label = ORCA_WHIRLPOOLS_CONFIG_PROGRAM)]
+pub struct WhirlpoolsConfig {
+    #[edge(
+        out,
+        weight = DIRECT,
+        label = ORCA_WHIRLPOOLS_CONFIG_FEE_AUTHORITY,
+        skip_default
+    )]
+    pub fee_authority: Pubkey,
+    #[edge(
+        out,
+        weight = DIRECT,
+        label = ORCA_WHIRLPOOLS_CONFIG_COLLECT_PROTOCOL_FEES_AUTHORITY,
+        skip_default
+    )]
+    pub collect_protocol_fees_authority: Pubkey,
+    #[edge(
+        out,
+        weight = DIRECT,
+        label = ORCA_WHIRLPOOLS_CONFIG_REWARD_EMISSIONS_SUPER_AUTHORITY,
+        skip_default
+    )]
+    pub reward_emissions_super_authority: Pubkey,
+}
+
+/// A whirlpool up to token vault B; the rewards are not parsed yet.
+#[derive(EdgeSource)]
+pub struct Whirlpool {
+    #[edge(
+        in,
+        weight = DIRECT,
+        label = ORCA_WHIRLPOOL_WHIRLPOOLS_CONFIG,
+        skip_default
+    )]
+    pub whirlpools_config: Pubkey,
+    pub whirlpool_bump: [u8; 1],
+    pub tick_spacing: u16,
+    pub tick_spacing_seed: [u8; 2],
+    pub fee_rate: u16,
+    pub protocol_fee_rate: u16,
+    pub liquidity: u128,
+    pub sqrt_price: u128,
+    pub tick_current_index: i32,
+    pub protocol_fee_owed_a: u64,
+    pub protocol_fee_owed_b: u64,
+    // no edges to the mints; the vault accounts already carry the mint
+    pub token_mint_a: Pubkey,
+    #[edge(out, weight = DIRECT, label = ORCA_WHIRLPOOL_TOKEN_VAULT_A, skip_default)]
+    pub token_vault_a: Pubkey,
+    pub fee_growth_global_a: u128,
+    pub token_mint_b: Pubkey,
+    #[edge(out, weight = DIRECT, label = ORCA_WHIRLPOOL_TOKEN_VAULT_B, skip_default)]
+    pub token_vault_b: Pubkey,
+}
+
+#[derive(EdgeSource)]
+pub struct TickArray {
+    pub start_tick_index: i32,
+    /// 88 ticks of 113 bytes
+    pub ticks: Skip<{ 88 * 113 }>,
+    #[edge(in, weight = DIRECT, label = ORCA_TICK_ARRAY_WHIRLPOOL, skip_default)]
+    pub whirlpool: Pubkey,
+}
+
 pub struct Orca {
-    d_whirlpool: [u8; 8],
-    d_whirlpoolconfig: [u8; 8],
-    d_tickarray: [u8; 8],
+    dispatch: DispatchTable<AccountKind>,
     pub program_id: Pubkey,
 }
 impl GuestFilter for Orca {
@@ -22,158 +101,70 @@ impl GuestFilter for Orca {
     }
 
     fn edge(&self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge> {
-        let mut list = VecDeque::new();
-        let id = header.pubkey;
-        // all discriminators are the same length
-        let prefix = self.d_whirlpool.len();
-        let mut i;
-        let pubkey_len = std::mem::size_of::<Pubkey>();
+        edge_or_log("orca", header, self.try_edge(header, data))
+    }
+
+    fn node_type(&self, _header: &AccountHeader, data: &[u8]) -> NodeType {
+        match self.dispatch.get(data) {
+            Some(AccountKind::WhirlpoolsConfig) => NODE_TYPE_ORCA_WHIRLPOOLS_CONFIG,
+            Some(AccountKind::Whirlpool) => NODE_TYPE_ORCA_WHIRLPOOL,
+            Some(AccountKind::TickArray) => NODE_TYPE_ORCA_TICK_ARRAY,
+            None => NODE_TYPE_UNKNOWN,
+        }
+    }
+}
+
+impl Orca {
+    fn try_edge(
+        &self,
+        header: &AccountHeader,
+        data: &[u8],
+    ) -> Result<VecDeque<FilterEdge>, CatscopeWasmError> {
         #[cfg(target_os = "wasi")]
         HostImport::log(format!(
             "orca_edge - 1 - pubkey {}; data len {}",
-            id,
+            header.pubkey,
             data.len()
         ));
-        if match_discriminator(&self.d_whirlpoolconfig, data) {
-            #[cfg(target_os = "wasi")]
-            HostImport::log(format!("orca_edge - 2 - whirlpoolconfig - pubkey {};", id));
-            // program
-            list.push_back(FilterEdge {
-                slot: header.slot,
-                weight: WEIGHT_DIRECT,
-                from: self.program_id,
-                to: id,
-            });
-            // fee authority
-            {
-                i = 8;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge {
-                        slot: header.slot,
-                        weight: WEIGHT_DIRECT,
-                        from: id,
-                        to: pubkey,
-                    });
-                }
-            }
-            // Collect Protocol Fees Authority
-            {
-                i = 40;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge {
-                        slot: header.slot,
-                        weight: WEIGHT_DIRECT,
-                        from: id,
-                        to: pubkey,
-                    });
-                }
-            }
-            // Reward Emissions Super Authority
-            {
-                i = 72;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge {
-                        slot: header.slot,
-                        weight: WEIGHT_DIRECT,
-                        from: id,
-                        to: pubkey,
-                    });
-                }
-            }
-        } else if match_discriminator(&self.d_whirlpool, data) {
-            #[cfg(target_os = "wasi")]
-            HostImport::log(format!("orca_edge - 2 - whirlpool - pubkey {};", id));
-            // WhirlpoolsConfig
-            {
-                i = 8;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge {
-                        slot: header.slot,
-                        weight: WEIGHT_DIRECT,
-                        from: pubkey,
-                        to: id,
-                    });
-                }
-            }
-            // skip mint edges; they are useless as the mint information is available in the vault
-            // accounts
-            // token vault A
-            {
-                i = 133;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge {
-                        slot: header.slot,
-                        weight: WEIGHT_DIRECT,
-                        from: id,
-                        to: pubkey,
-                    });
-                }
-            }
-            // token vault B
-            {
-                i = 213;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge {
-                        slot: header.slot,
-                        weight: WEIGHT_DIRECT,
-                        from: id,
-                        to: pubkey,
-                    });
-                }
+        let list = match self.dispatch.get(data) {
+            Some(AccountKind::WhirlpoolsConfig) => {
+                WhirlpoolsConfig::parse(data)?.edge(header, &self.program_id)
             }
-            // rewards -> TBD
-        } else if match_discriminator(&self.d_tickarray, data) {
-            // whirlpool
-            {
-                i = data.len() - pubkey_len;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_id {
-                    list.push_back(FilterEdge {
-                        slot: header.slot,
-                        weight: WEIGHT_DIRECT,
-                        from: pubkey,
-                        to: id,
-                    });
-                }
-            }
-        }
+            Some(AccountKind::Whirlpool) => Whirlpool::parse(data)?.edge(header, &self.program_id),
+            Some(AccountKind::TickArray) => TickArray::parse(data)?.edge(header, &self.program_id),
+            None => VecDeque::new(),
+        };
         #[cfg(target_os = "wasi")]
-        HostImport::log(format!("orca_edge - 4 - pubkey {};", id));
-        list
+        HostImport::log(format!("orca_edge - 4 - pubkey {};", header.pubkey));
+        Ok(list)
     }
-}
 
-impl Orca {
     pub fn new(program_id: &Pubkey) -> Self {
-        let d_whirlpool = whirlpool_discriminator();
-        let d_whirlpoolconfig = whirlpoolconfig_discriminator();
-        let d_tickarray = tickarray_discriminator();
         Self {
             program_id: *program_id,
-            d_whirlpool,
-            d_tickarray,
-            d_whirlpoolconfig,
+            dispatch: [
+                (
+                    whirlpoolconfig_discriminator(),
+                    AccountKind::WhirlpoolsConfig,
+                ),
+                (whirlpool_discriminator(), AccountKind::Whirlpool),
+                (tickarray_discriminator(), AccountKind::TickArray),
+            ]
+            .into_iter()
+            .collect(),
         }
     }
 }
-//  9d 14 31 e0 d9 57 c1 fe
+
 pub fn whirlpoolconfig_discriminator() -> [u8; 8] {
-    [157, 20, 49, 224, 217, 87, 193, 254]
+    anchor_account_discriminator("WhirlpoolsConfig")
 }
 
-// 3f 95 d1 0c e1 80 63 09
 pub fn whirlpool_discriminator() -> [u8; 8] {
-    [63, 149, 209, 12, 225, 128, 99, 9]
+    anchor_account_discriminator("Whirlpool")
 }
-// 45 61 bd be 6e 07 42 bb
 pub fn tickarray_discriminator() -> [u8; 8] {
-    [69, 97, 189, 190, 110, 7, 66, 187]
+    anchor_account_discriminator("TickArray")
 }
 
 // unit tests
@@ -181,7 +172,7 @@ pub fn tickarray_discriminator() -> [u8; 8] {
 mod tests {
     use std::collections::HashMap;
 
-    use crate::primitive::tree::Weight;
+    use crate::primitive::tree::{Weight, WEIGHT_DIRECT};
 
     use super::*; // import functions from the parent module
     use std::sync::Once;
@@ -190,11 +181,11 @@ mod tests {
 
     fn init_logger() {
         INIT_LOGGER.call_once(|| {
-            env_logger::init();
+            let _ = env_logger::try_init();
         });
     }
 
-    use log::{info, warn};
+    use log::info;
     // dump with:  solana account --output-file=./whirlpool1.bin DtYKbQELgMZ3ihFUrCcCs9gy4djcUuhwgR7UpxVpP2Tg; xxd -p -c 99999999 ./whirlpool1.bin
     #[test]
     fn test_whirlpool() {
@@ -278,6 +269,14 @@ mod tests {
         }
     }
     #[test]
+    fn test_account_size() {
+        // the last pubkey read from each account, as laid out by the Orca program
+        assert_eq!(8 + WhirlpoolsConfig::SIZE, 72 + 32);
+        assert_eq!(8 + Whirlpool::SIZE, 213 + 32);
+        // a tick array is read to its end
+        assert_eq!(8 + TickArray::SIZE, 9988);
+    }
+    #[test]
     fn test_whirlpoolconfig() {
         init_logger();
         let program_id = Pubkey::try_from("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc").unwrap();
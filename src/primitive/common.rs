@@ -1,5 +1,7 @@
 use core::slice;
 
+use solana_sdk::pubkey::Pubkey;
+
 use super::err::CatscopeWasmError;
 
 pub const DATA_LIST: [usize; 20] = [
@@ -40,7 +42,7 @@ pub fn find_k(arr: &[usize], target: usize) -> Option<usize> {
         if mid % 2 == 0 {
             mid = low + mid / 2
         } else {
-            mid = low + (mid + 1) / 2
+            mid = low + mid.div_ceil(2)
         }
         // warn!(
         //    "1 - {} {} {}",
@@ -162,6 +164,14 @@ pub fn cast_bytes_manual<T>(bytes: &[u8]) -> &[T] {
     unsafe { slice::from_raw_parts(bytes.as_ptr() as *const T, bytes.len() / size_of::<T>()) }
 }
 
+/// Derive the Anchor account discriminator, `sha256("account:<name>")[..8]`.
+pub fn anchor_account_discriminator(name: &str) -> [u8; 8] {
+    let hash = solana_sdk::hash::hashv(&[b"account:", name.as_bytes()]);
+    let mut d = [0u8; 8];
+    d.copy_from_slice(&hash.to_bytes()[..8]);
+    d
+}
+
 pub fn match_discriminator(a: &[u8], b: &[u8]) -> bool {
     if a.len() < 8 {
         return false;
@@ -176,3 +186,138 @@ pub fn match_discriminator(a: &[u8], b: &[u8]) -> bool {
     }
     true
 }
+
+/// A bounds checked reader over account data.
+///
+/// Reads past the end of the buffer return [`CatscopeWasmError::ShortBuffer`]
+/// instead of panicking, so truncated or malicious accounts cannot trap the
+/// wasm instance. Offsets passed to the `*_at` methods are absolute; the
+/// `read_*` methods advance the cursor.
+pub struct AccountReader<'a> {
+    data: &'a [u8],
+    i: usize,
+}
+
+impl<'a> AccountReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, i: 0 }
+    }
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    pub fn position(&self) -> usize {
+        self.i
+    }
+    /// Fail unless the buffer holds at least `len` bytes.
+    pub fn require(&self, len: usize) -> Result<(), CatscopeWasmError> {
+        if self.data.len() < len {
+            return Err(CatscopeWasmError::ShortBuffer(len, self.data.len()));
+        }
+        Ok(())
+    }
+    /// Move the cursor to an absolute offset.
+    pub fn seek(&mut self, i: usize) -> Result<(), CatscopeWasmError> {
+        self.require(i)?;
+        self.i = i;
+        Ok(())
+    }
+    /// Move the cursor forward by `n` bytes.
+    pub fn skip(&mut self, n: usize) -> Result<(), CatscopeWasmError> {
+        let i = self.end(self.i, n)?;
+        self.i = i;
+        Ok(())
+    }
+    fn end(&self, i: usize, n: usize) -> Result<usize, CatscopeWasmError> {
+        let end = i
+            .checked_add(n)
+            .ok_or(CatscopeWasmError::ShortBuffer(usize::MAX, self.data.len()))?;
+        self.require(end)?;
+        Ok(end)
+    }
+    pub fn bytes_at(&self, i: usize, n: usize) -> Result<&'a [u8], CatscopeWasmError> {
+        let end = self.end(i, n)?;
+        Ok(&self.data[i..end])
+    }
+    pub fn array_at<const N: usize>(&self, i: usize) -> Result<[u8; N], CatscopeWasmError> {
+        let mut x = [0u8; N];
+        x.copy_from_slice(self.bytes_at(i, N)?);
+        Ok(x)
+    }
+    pub fn pubkey_at(&self, i: usize) -> Result<Pubkey, CatscopeWasmError> {
+        Ok(Pubkey::new_from_array(self.array_at(i)?))
+    }
+    pub fn u8_at(&self, i: usize) -> Result<u8, CatscopeWasmError> {
+        Ok(self.bytes_at(i, 1)?[0])
+    }
+    pub fn u32_at(&self, i: usize) -> Result<u32, CatscopeWasmError> {
+        Ok(u32::from_le_bytes(self.array_at(i)?))
+    }
+    pub fn u64_at(&self, i: usize) -> Result<u64, CatscopeWasmError> {
+        Ok(u64::from_le_bytes(self.array_at(i)?))
+    }
+    pub fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], CatscopeWasmError> {
+        let x = self.bytes_at(self.i, n)?;
+        self.i += n;
+        Ok(x)
+    }
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], CatscopeWasmError> {
+        let x = self.array_at(self.i)?;
+        self.i += N;
+        Ok(x)
+    }
+    pub fn read_pubkey(&mut self) -> Result<Pubkey, CatscopeWasmError> {
+        let x = self.pubkey_at(self.i)?;
+        self.i += PUBKEY_LEN;
+        Ok(x)
+    }
+    pub fn read_u8(&mut self) -> Result<u8, CatscopeWasmError> {
+        let x = self.u8_at(self.i)?;
+        self.i += 1;
+        Ok(x)
+    }
+    pub fn read_u32(&mut self) -> Result<u32, CatscopeWasmError> {
+        let x = self.u32_at(self.i)?;
+        self.i += U32_LEN;
+        Ok(x)
+    }
+    pub fn read_u64(&mut self) -> Result<u64, CatscopeWasmError> {
+        let x = self.u64_at(self.i)?;
+        self.i += U64_LEN;
+        Ok(x)
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*; // import functions from the parent module
+
+    #[test]
+    fn test_account_reader() {
+        let pubkey = Pubkey::new_unique();
+        let mut data = vec![3u8];
+        data.extend_from_slice(pubkey.as_ref());
+        data.extend_from_slice(&7u32.to_le_bytes());
+        data.extend_from_slice(&9u64.to_le_bytes());
+        let mut reader = AccountReader::new(&data);
+        assert_eq!(reader.read_u8().unwrap(), 3);
+        assert_eq!(reader.read_pubkey().unwrap(), pubkey);
+        assert_eq!(reader.read_u32().unwrap(), 7);
+        assert_eq!(reader.read_u64().unwrap(), 9);
+        assert_eq!(reader.position(), data.len());
+        assert!(reader.read_u8().is_err());
+        assert_eq!(reader.pubkey_at(1).unwrap(), pubkey);
+        assert!(reader.pubkey_at(14).is_err());
+        assert!(reader.pubkey_at(usize::MAX).is_err());
+        assert!(reader.bytes_at(usize::MAX, 2).is_err());
+        assert!(reader.require(data.len()).is_ok());
+        assert!(reader.require(data.len() + 1).is_err());
+        assert!(reader.seek(data.len() + 1).is_err());
+        assert!(reader.seek(1).is_ok());
+        assert!(reader.skip(usize::MAX).is_err());
+        assert_eq!(reader.read_pubkey().unwrap(), pubkey);
+    }
+}
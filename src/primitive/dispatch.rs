@@ -0,0 +1,129 @@
+use std::{
+    collections::HashMap,
+    hash::{BuildHasherDefault, Hasher},
+};
+
+use crate::DISCRIMINATOR_SIZE;
+
+/// An account discriminator read as a little endian `u64`, so that it is hashed as one word.
+pub type DiscriminatorKey = u64;
+
+pub fn discriminator_key(d: &[u8; DISCRIMINATOR_SIZE]) -> DiscriminatorKey {
+    u64::from_le_bytes(*d)
+}
+
+// Anchor discriminators are the start of a sha256 hash, so the key is its own hash; SipHash
+// would cost more than the old byte by byte compare.
+#[derive(Debug, Default, Clone, Copy)]
+struct KeyHasher(u64);
+
+impl Hasher for KeyHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes.iter() {
+            self.0 = self.0.rotate_left(8) ^ u64::from(*b);
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.0 = n;
+    }
+}
+
+/// Map account discriminators to whatever a filter does with the account, usually an enum of
+/// the account kinds it parses.
+///
+/// A filter reads the discriminator of an account once and looks it up in one hash map instead
+/// of comparing it byte by byte against each of its discriminators, so the cost does not grow
+/// with the number of account kinds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DispatchTable<T> {
+    m_value: HashMap<DiscriminatorKey, T, BuildHasherDefault<KeyHasher>>,
+}
+
+impl<T> Default for DispatchTable<T> {
+    fn default() -> Self {
+        Self {
+            m_value: HashMap::default(),
+        }
+    }
+}
+
+impl<T> DispatchTable<T> {
+    /// Add an entry; returns the value that was replaced, if any.
+    pub fn insert(&mut self, d: [u8; DISCRIMINATOR_SIZE], value: T) -> Option<T> {
+        self.m_value.insert(discriminator_key(&d), value)
+    }
+
+    /// The entry for a discriminator, added with its default value when missing.
+    pub fn get_or_default(&mut self, d: [u8; DISCRIMINATOR_SIZE]) -> &mut T
+    where
+        T: Default,
+    {
+        self.m_value.entry(discriminator_key(&d)).or_default()
+    }
+
+    /// Look up the entry for the discriminator at the start of the account data.
+    /// Accounts shorter than a discriminator match nothing.
+    pub fn get(&self, data: &[u8]) -> Option<&T> {
+        let d = data.get(..DISCRIMINATOR_SIZE)?;
+        self.get_discriminator(d.try_into().ok()?)
+    }
+
+    pub fn get_discriminator(&self, d: &[u8; DISCRIMINATOR_SIZE]) -> Option<&T> {
+        self.m_value.get(&discriminator_key(d))
+    }
+
+    pub fn len(&self) -> usize {
+        self.m_value.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.m_value.is_empty()
+    }
+}
+
+impl<T> FromIterator<([u8; DISCRIMINATOR_SIZE], T)> for DispatchTable<T> {
+    fn from_iter<I: IntoIterator<Item = ([u8; DISCRIMINATOR_SIZE], T)>>(iter: I) -> Self {
+        let mut table = Self::default();
+        for (d, value) in iter {
+            table.insert(d, value);
+        }
+        table
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*; // import functions from the parent module
+
+    #[test]
+    fn test_dispatch_table() {
+        let list: Vec<([u8; 8], usize)> = (0..20u8)
+            .map(|k| ([k.wrapping_mul(37); 8], k as usize))
+            .collect();
+        let mut table: DispatchTable<usize> = list.iter().copied().collect();
+        assert_eq!(table.len(), 20);
+        for (d, k) in list.iter() {
+            let mut data = d.to_vec();
+            data.extend_from_slice(&[1, 2, 3]);
+            assert_eq!(table.get(&data), Some(k));
+            assert_eq!(table.get_discriminator(d), Some(k));
+            // too short to hold a discriminator
+            assert_eq!(table.get(&data[..7]), None);
+        }
+        assert_eq!(table.get(&[0xff; 8]), None);
+        assert_eq!(table.insert(list[3].0, 100), Some(3));
+        assert_eq!(table.get(&list[3].0), Some(&100));
+        assert_eq!(table.len(), 20);
+        assert!(DispatchTable::<usize>::default().is_empty());
+        let mut table: DispatchTable<Vec<usize>> = DispatchTable::default();
+        table.get_or_default(list[0].0).push(1);
+        table.get_or_default(list[0].0).push(2);
+        assert_eq!(table.get(&list[0].0), Some(&vec![1, 2]));
+        assert_eq!(table.len(), 1);
+    }
+}
@@ -18,6 +18,7 @@ pub enum CatscopeWasmError {
     TransactionError(Box<dyn std::error::Error + Send + Sync>),
     DoubleWriting, // cannot write to a Blob once it has been set
     PayloadTooBig(usize),
+    ShortBuffer(usize, usize), // (bytes needed, bytes available)
     SliceWrongSize,
     EmptyPayload,
     NotImplemented,
@@ -50,6 +51,9 @@ impl std::fmt::Display for CatscopeWasmError {
             CatscopeWasmError::GenericError(e) => write!(f, "generic: {}", e),
             CatscopeWasmError::TransactionError(e) => write!(f, "transaction: {}", e),
             CatscopeWasmError::PayloadTooBig(s) => write!(f, "payload {} is too big", s),
+            CatscopeWasmError::ShortBuffer(need, len) => {
+                write!(f, "need {} bytes but the buffer has {}", need, len)
+            }
             CatscopeWasmError::Unknown(e) => write!(f, "unknown: {}", e),
             CatscopeWasmError::TimeOut => write!(f, "timed out"),
             CatscopeWasmError::DoubleWriting => {
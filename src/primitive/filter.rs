@@ -1,60 +1,47 @@
 use super::{
     guest::GuestFilter,
     header::AccountHeader,
-    tree::{FilterEdge, WEIGHT_IS_OUTGOING},
+    label::EdgeLabel,
+    node::{NodeType, NODE_TYPE_UNKNOWN},
+    tree::{FilterEdge, Weight, WEIGHT_IS_OUTGOING, WEIGHT_REMOVE},
 };
 use solana_sdk::pubkey::Pubkey;
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 
+#[cfg(target_os = "wasi")]
 use super::{wasmimport::HostImport, wasmstore::Store};
 
-#[repr(C, align(8))]
-pub struct CatscopeFilter {
-    host_import: HostImport,
+/// Route accounts to the filter that tracks their owner.
+/// This is the filter set built by `init`; it is usable outside of the guest for offline tools.
+pub struct FilterSet {
     l_program_id: Vec<Pubkey>,
     l_filter: Vec<Box<dyn GuestFilter + 'static>>,
     m_filter_by_pubkey: BTreeMap<Pubkey, usize>, // program id pubkey to filter index
-    m_filter_by_listindex: BTreeMap<usize, usize>, // program id pubkey to filter index
 }
-impl CatscopeFilter {
-    pub fn new(
-        mut list: VecDeque<Box<dyn GuestFilter + 'static>>,
-        host_import: HostImport,
-    ) -> Self {
+impl FilterSet {
+    pub fn new(mut list: VecDeque<Box<dyn GuestFilter + 'static>>) -> Self {
         let mut m_filter_by_pubkey = BTreeMap::new();
         let mut l_filter = Vec::new();
         let mut l_program_id = Vec::new();
-        let mut m_filter_by_listindex = BTreeMap::new();
         let mut k = 0;
-        let mut j = 0;
         while let Some(filter) = list.pop_front() {
             let list = filter.program_id_list();
             for pubkey in list.iter() {
                 m_filter_by_pubkey.insert(*pubkey, k);
-                m_filter_by_listindex.insert(j, k);
                 l_program_id.push(*pubkey);
-                j += 1;
             }
             l_filter.push(filter);
             k += 1;
         }
         Self {
-            host_import,
             l_program_id,
             l_filter,
-            m_filter_by_listindex,
             m_filter_by_pubkey,
         }
     }
-    pub fn store(&self) -> &Store {
-        self.host_import.store()
-    }
-    pub fn store_mut(&mut self) -> &mut Store {
-        self.host_import.store_mut()
-    }
 }
 
-impl GuestFilter for CatscopeFilter {
+impl GuestFilter for FilterSet {
     fn program_id_list(&self) -> Vec<Pubkey> {
         self.l_program_id.clone()
     }
@@ -77,6 +64,203 @@ impl GuestFilter for CatscopeFilter {
         });
         list
     }
+
+    fn node_type(&self, header: &AccountHeader, data: &[u8]) -> NodeType {
+        match self.m_filter_by_pubkey.get(&header.owner) {
+            Some(k) => self.l_filter[*k].node_type(header, data),
+            None => NODE_TYPE_UNKNOWN,
+        }
+    }
+}
+
+/// The number of accounts `EdgeCache::default` remembers, unless the config sets
+/// `edge_cache=accounts`. An account costs about 100 bytes plus 72 bytes per edge, so a full
+/// cache of accounts with 4 edges holds about 12 MB of the guest memory.
+pub const EDGE_CACHE_CAPACITY: usize = 32 * 1024;
+
+/// The edges last emitted for one account. Closed accounts keep an entry with no edges,
+/// so that updates from before the close are still recognized as stale.
+struct CachedEdges {
+    slot: u64,
+    tick: u64, // when the entry was last used
+    list: Vec<(Pubkey, Pubkey, Weight, EdgeLabel)>,
+}
+
+/// Remember the edges emitted per account, so that edges that disappear on an update
+/// can be sent back to the host flagged with [`WEIGHT_REMOVE`].
+///
+/// The cache holds at most `capacity` accounts; the least recently updated account is evicted
+/// first. An evicted account is treated as new on its next update, so edges it dropped in
+/// between are not removed and its slot guard is lost. Every account with edges counts,
+/// including the system, token and loader accounts that are always tracked, so the capacity
+/// must cover the accounts updated between two updates of any account that needs its removals.
+pub struct EdgeCache {
+    capacity: usize,
+    tick: u64,
+    m_account: HashMap<Pubkey, CachedEdges>,
+    m_tick: BTreeMap<u64, Pubkey>, // last use to account, oldest first
+}
+impl Default for EdgeCache {
+    fn default() -> Self {
+        Self::new(EDGE_CACHE_CAPACITY)
+    }
+}
+impl EdgeCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            tick: 0,
+            m_account: HashMap::new(),
+            m_tick: BTreeMap::new(),
+        }
+    }
+
+    /// Accounts with no lamports have been closed.
+    pub fn is_closed(header: &AccountHeader) -> bool {
+        header.lamports == 0
+    }
+
+    /// Diff the edges of an update against the cache.
+    /// Returns removal edges for those that are gone, followed by the current edges.
+    /// A closed account only gets removals, and updates older than the cached slot are ignored.
+    ///
+    /// An edge is gone unless the update has an edge with the same `from`, `to`, weight and
+    /// label; a removal carries the old weight and label with [`WEIGHT_REMOVE`] set. An edge
+    /// that only changes its weight or label therefore comes out as a removal of the old edge
+    /// and then the new edge. Removals come first, so a host that keys edges by their
+    /// endpoints alone ends up with the new edge as well.
+    pub fn update(
+        &mut self,
+        header: &AccountHeader,
+        mut list: VecDeque<FilterEdge>,
+    ) -> VecDeque<FilterEdge> {
+        if let Some(cached) = self.m_account.get(&header.pubkey) {
+            if header.slot < cached.slot {
+                return VecDeque::new();
+            }
+        }
+        let closed = Self::is_closed(header);
+        if closed {
+            list.clear();
+        }
+        let current: Vec<(Pubkey, Pubkey, Weight, EdgeLabel)> = list
+            .iter()
+            .map(|e| (e.from, e.to, e.weight, e.label))
+            .collect();
+        let old = if current.is_empty() && !closed && !self.m_account.contains_key(&header.pubkey) {
+            // nothing to remember for accounts without edges
+            None
+        } else {
+            self.insert(header.pubkey, header.slot, current)
+        };
+        if let Some(old) = old {
+            let set: HashSet<(Pubkey, Pubkey, Weight, EdgeLabel)> = list
+                .iter()
+                .map(|e| (e.from, e.to, e.weight, e.label))
+                .collect();
+            for (from, to, weight, label) in old.into_iter().rev() {
+                if !set.contains(&(from, to, weight, label)) {
+                    list.push_front(FilterEdge {
+                        slot: header.slot,
+                        from,
+                        to,
+                        weight: weight | WEIGHT_REMOVE,
+                        label,
+                    });
+                }
+            }
+        }
+        list
+    }
+
+    // Store the edges of an account as its most recent entry; returns the edges it replaced.
+    fn insert(
+        &mut self,
+        pubkey: Pubkey,
+        slot: u64,
+        list: Vec<(Pubkey, Pubkey, Weight, EdgeLabel)>,
+    ) -> Option<Vec<(Pubkey, Pubkey, Weight, EdgeLabel)>> {
+        self.tick += 1;
+        let tick = self.tick;
+        let old = self
+            .m_account
+            .insert(pubkey, CachedEdges { slot, tick, list });
+        match old {
+            Some(old) => {
+                self.m_tick.remove(&old.tick);
+                self.m_tick.insert(tick, pubkey);
+                Some(old.list)
+            }
+            None => {
+                self.m_tick.insert(tick, pubkey);
+                while self.m_account.len() > self.capacity {
+                    let (_, evict) = self.m_tick.pop_first().unwrap();
+                    self.m_account.remove(&evict);
+                }
+                None
+            }
+        }
+    }
+
+    /// The number of accounts in the cache, closed accounts included.
+    pub fn len(&self) -> usize {
+        self.m_account.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.m_account.is_empty()
+    }
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+#[cfg(target_os = "wasi")]
+#[repr(C, align(8))]
+pub struct CatscopeFilter {
+    host_import: HostImport,
+    filter_set: FilterSet,
+    cache: EdgeCache,
+}
+#[cfg(target_os = "wasi")]
+impl CatscopeFilter {
+    pub fn new(
+        list: VecDeque<Box<dyn GuestFilter + 'static>>,
+        cache_capacity: usize,
+        host_import: HostImport,
+    ) -> Self {
+        Self {
+            host_import,
+            filter_set: FilterSet::new(list),
+            cache: EdgeCache::new(cache_capacity),
+        }
+    }
+    /// Produce the edges of an account update, including removals of edges that went away
+    /// since the previous update of the same account.
+    pub fn update(&mut self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge> {
+        let list = self.filter_set.edge(header, data);
+        self.cache.update(header, list)
+    }
+    pub fn store(&self) -> &Store {
+        self.host_import.store()
+    }
+    pub fn store_mut(&mut self) -> &mut Store {
+        self.host_import.store_mut()
+    }
+}
+
+#[cfg(target_os = "wasi")]
+impl GuestFilter for CatscopeFilter {
+    fn program_id_list(&self) -> Vec<Pubkey> {
+        self.filter_set.program_id_list()
+    }
+
+    fn edge(&self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge> {
+        self.filter_set.edge(header, data)
+    }
+
+    fn node_type(&self, header: &AccountHeader, data: &[u8]) -> NodeType {
+        self.filter_set.node_type(header, data)
+    }
 }
 
 pub fn weight_set_outgoing(edge: &mut FilterEdge) {
@@ -92,3 +276,265 @@ pub fn ptr_to_filter<'a, T>(buffer_ptr: u64) -> Option<&'a mut T> {
     let s = unsafe { cat_ptr.as_mut().unwrap() };
     Some(s)
 }
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::primitive::{
+        label::LABEL_NONE,
+        tree::{edge_is_removal, WEIGHT_DIRECT, WEIGHT_SYMLINK},
+    };
+
+    use super::*; // import functions from the parent module
+
+    fn edge(from: Pubkey, to: Pubkey, weight: Weight) -> FilterEdge {
+        FilterEdge {
+            slot: 0,
+            from,
+            to,
+            weight,
+            label: LABEL_NONE,
+        }
+    }
+
+    #[test]
+    fn test_edge_cache() {
+        let mut cache = EdgeCache::default();
+        let mut header = AccountHeader {
+            pubkey: Pubkey::new_unique(),
+            lamports: 10,
+            slot: 1,
+            ..Default::default()
+        };
+        let config = Pubkey::new_unique();
+        let vault = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let list: VecDeque<FilterEdge> = vec![
+            edge(config, header.pubkey, WEIGHT_DIRECT),
+            edge(header.pubkey, vault[0], WEIGHT_DIRECT),
+        ]
+        .into();
+        let out = cache.update(&header, list.clone());
+        assert_eq!(out.len(), 2);
+        assert!(out.iter().all(|e| !edge_is_removal(&e.weight)));
+
+        // the same edges again remove nothing
+        header.slot = 2;
+        assert_eq!(cache.update(&header, list.clone()).len(), 2);
+
+        // the vault changes and the config edge changes its weight
+        header.slot = 3;
+        let out = cache.update(
+            &header,
+            vec![
+                edge(config, header.pubkey, WEIGHT_SYMLINK),
+                edge(header.pubkey, vault[1], WEIGHT_DIRECT),
+            ]
+            .into(),
+        );
+        let removed: Vec<(Pubkey, Pubkey, Weight)> = out
+            .iter()
+            .filter(|e| edge_is_removal(&e.weight))
+            .map(|e| (e.from, e.to, e.weight & !WEIGHT_REMOVE))
+            .collect();
+        assert_eq!(
+            removed,
+            vec![
+                (config, header.pubkey, WEIGHT_DIRECT),
+                (header.pubkey, vault[0], WEIGHT_DIRECT)
+            ]
+        );
+        assert!(out
+            .iter()
+            .all(|e| e.slot == 3 || !edge_is_removal(&e.weight)));
+
+        // stale updates are ignored
+        header.slot = 2;
+        assert!(cache.update(&header, list.clone()).is_empty());
+
+        // closing the account tears every edge down
+        header.slot = 4;
+        header.lamports = 0;
+        let out = cache.update(&header, list.clone());
+        assert_eq!(out.len(), 2);
+        assert!(out.iter().all(|e| edge_is_removal(&e.weight)));
+        assert_eq!(cache.len(), 1);
+
+        // updates from before the close stay stale
+        header.slot = 3;
+        header.lamports = 10;
+        assert!(cache.update(&header, list.clone()).is_empty());
+        // a later update opens the account again, with nothing to remove
+        header.slot = 5;
+        let out = cache.update(&header, list.clone());
+        assert_eq!(out.len(), 2);
+        assert!(out.iter().all(|e| !edge_is_removal(&e.weight)));
+    }
+
+    #[test]
+    fn test_edge_cache_order() {
+        let mut cache = EdgeCache::default();
+        let mut header = AccountHeader {
+            pubkey: Pubkey::new_unique(),
+            lamports: 10,
+            slot: 1,
+            ..Default::default()
+        };
+        let target = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        // a host that keys edges by their endpoints
+        let mut graph: HashMap<(Pubkey, Pubkey), Weight> = HashMap::new();
+        let mut apply = |list: VecDeque<FilterEdge>| {
+            for e in list.iter() {
+                if edge_is_removal(&e.weight) {
+                    graph.remove(&(e.from, e.to));
+                } else {
+                    graph.insert((e.from, e.to), e.weight);
+                }
+            }
+            list
+        };
+        apply(
+            cache.update(
+                &header,
+                vec![
+                    edge(header.pubkey, target, WEIGHT_DIRECT),
+                    edge(header.pubkey, other, WEIGHT_DIRECT),
+                ]
+                .into(),
+            ),
+        );
+        // only the weight of the first edge changes, and the second edge goes away
+        header.slot = 2;
+        let out = apply(cache.update(
+            &header,
+            vec![edge(header.pubkey, target, WEIGHT_SYMLINK)].into(),
+        ));
+        let got: Vec<(Pubkey, Weight)> = out.iter().map(|e| (e.to, e.weight)).collect();
+        assert_eq!(
+            got,
+            vec![
+                (target, WEIGHT_DIRECT | WEIGHT_REMOVE),
+                (other, WEIGHT_DIRECT | WEIGHT_REMOVE),
+                (target, WEIGHT_SYMLINK),
+            ]
+        );
+        assert_eq!(
+            graph.into_iter().collect::<Vec<_>>(),
+            vec![((header.pubkey, target), WEIGHT_SYMLINK)]
+        );
+    }
+
+    #[test]
+    fn test_edge_cache_capacity() {
+        let mut cache = EdgeCache::new(2);
+        let target = Pubkey::new_unique();
+        let header: Vec<AccountHeader> = (0..3)
+            .map(|_| AccountHeader {
+                pubkey: Pubkey::new_unique(),
+                lamports: 10,
+                slot: 1,
+                ..Default::default()
+            })
+            .collect();
+        let list = |h: &AccountHeader| -> VecDeque<FilterEdge> {
+            vec![edge(h.pubkey, target, WEIGHT_DIRECT)].into()
+        };
+        // accounts without edges take no room
+        assert!(cache.update(&header[2], VecDeque::new()).is_empty());
+        assert!(cache.is_empty());
+
+        cache.update(&header[0], list(&header[0]));
+        cache.update(&header[1], list(&header[1]));
+        // touch the first account, so the second one is the oldest
+        cache.update(&header[0], list(&header[0]));
+        cache.update(&header[2], list(&header[2]));
+        assert_eq!(cache.len(), 2);
+
+        // the first account is still known: dropping its edge removes it
+        let out = cache.update(&header[0], VecDeque::new());
+        assert_eq!(out.len(), 1);
+        assert!(edge_is_removal(&out[0].weight));
+        // the second account was evicted, so there is nothing to remove
+        assert!(cache.update(&header[1], VecDeque::new()).is_empty());
+        assert!(cache.len() <= cache.capacity());
+
+        // closed accounts count against the capacity too
+        let mut closed = header[1].clone();
+        closed.lamports = 0;
+        closed.slot = 7;
+        cache.update(&closed, VecDeque::new());
+        assert_eq!(cache.len(), 2);
+        closed.lamports = 10;
+        closed.slot = 6;
+        assert!(cache.update(&closed, list(&closed)).is_empty());
+    }
+
+    #[test]
+    fn test_edge_cache_churn() {
+        let capacity = 64;
+        let mut cache = EdgeCache::new(capacity);
+        let target = Pubkey::new_unique();
+        let mut header = AccountHeader {
+            pubkey: Pubkey::new_unique(),
+            lamports: 10,
+            ..Default::default()
+        };
+        let vault: Vec<Pubkey> = (0..12).map(|_| Pubkey::new_unique()).collect();
+        // accounts that are updated once, like the token accounts of a busy mint
+        let churn = |cache: &mut EdgeCache, count: usize, slot: u64| {
+            for _ in 0..count {
+                let h = AccountHeader {
+                    pubkey: Pubkey::new_unique(),
+                    lamports: 10,
+                    slot,
+                    ..Default::default()
+                };
+                cache.update(&h, vec![edge(h.pubkey, target, WEIGHT_DIRECT)].into());
+            }
+        };
+        // a pool that switches vault after every `capacity - 1` other updates keeps its removals
+        for k in 0..10 {
+            header.slot = k as u64;
+            churn(&mut cache, capacity - 1, header.slot);
+            let out = cache.update(
+                &header,
+                vec![edge(header.pubkey, vault[k], WEIGHT_DIRECT)].into(),
+            );
+            let removed: Vec<Pubkey> = out
+                .iter()
+                .filter(|e| edge_is_removal(&e.weight))
+                .map(|e| e.to)
+                .collect();
+            if k == 0 {
+                assert!(removed.is_empty());
+            } else {
+                assert_eq!(removed, vec![vault[k - 1]]);
+            }
+            assert_eq!(cache.len(), capacity);
+        }
+        // one more account in between and the pool is evicted: its old vault edge is not removed
+        header.slot = 10;
+        churn(&mut cache, capacity, header.slot);
+        let out = cache.update(
+            &header,
+            vec![edge(header.pubkey, vault[10], WEIGHT_DIRECT)].into(),
+        );
+        assert_eq!(out.len(), 1);
+        assert!(!edge_is_removal(&out[0].weight));
+        // a larger cache rides out the same churn
+        let mut cache = EdgeCache::new(2 * capacity);
+        header.slot = 11;
+        cache.update(
+            &header,
+            vec![edge(header.pubkey, vault[10], WEIGHT_DIRECT)].into(),
+        );
+        churn(&mut cache, capacity, header.slot);
+        header.slot = 12;
+        let out = cache.update(
+            &header,
+            vec![edge(header.pubkey, vault[11], WEIGHT_DIRECT)].into(),
+        );
+        assert_eq!(out.len(), 2);
+        assert!(edge_is_removal(&out[0].weight));
+    }
+}
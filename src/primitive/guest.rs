@@ -2,8 +2,14 @@ use std::collections::VecDeque;
 
 use solana_sdk::pubkey::Pubkey;
 
-use super::header::AccountHeader;
-use super::tree::FilterEdge;
+#[cfg(target_os = "wasi")]
+use super::wasmimport::HostImport;
+use super::{
+    err::CatscopeWasmError,
+    header::AccountHeader,
+    node::{NodeType, NODE_TYPE_UNKNOWN},
+    tree::FilterEdge,
+};
 
 // This trait has to be implemented by a guest wasm.
 pub trait GuestFilter {
@@ -14,4 +20,27 @@ pub trait GuestFilter {
     /// self is marked mutable to allow the writing of account data to a shared buffer.
     /// return -1 for failure, 0 for no parent, 1 for parent.
     fn edge(&self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge>;
+    /// Classify an account, eg as a whirlpool or a token mint.
+    /// Filters that cannot tell what an account is return `NODE_TYPE_UNKNOWN`.
+    fn node_type(&self, _header: &AccountHeader, _data: &[u8]) -> NodeType {
+        NODE_TYPE_UNKNOWN
+    }
+}
+
+/// Unwrap the edges parsed by a filter.
+/// A parse error is logged and yields no edges, so a bad account never produces a partial list.
+#[cfg_attr(not(target_os = "wasi"), allow(unused_variables))]
+pub fn edge_or_log(
+    name: &str,
+    header: &AccountHeader,
+    result: Result<VecDeque<FilterEdge>, CatscopeWasmError>,
+) -> VecDeque<FilterEdge> {
+    #[cfg(target_os = "wasi")]
+    if let Err(e) = &result {
+        HostImport::log(format!(
+            "{}_edge - failed to parse pubkey {}; data len {}: {}",
+            name, header.pubkey, header.data_size, e
+        ));
+    }
+    result.unwrap_or_default()
 }
@@ -31,6 +31,7 @@ impl AccountHeader {
         self.rent_epoch = 0;
         self.node_id = 0;
     }
+    #[allow(clippy::should_implement_trait)]
     pub fn cmp(&self, b: &Self) -> Ordering {
         if self.slot < b.slot {
             Ordering::Less
@@ -0,0 +1,462 @@
+use std::fmt::Write;
+
+/// Says which account field produced an edge, eg `orca.whirlpool.token_vault_a`.
+/// Weights group edges by how the host walks them; labels tell edges with the same weight apart.
+pub type EdgeLabel = u16;
+
+/// Set by filters that do not label their edges and by guests that predate labels.
+pub const LABEL_NONE: EdgeLabel = 0;
+
+// Every filter owns a block of 256 ids, so adding a label never moves another one.
+// Ids are part of the ABI: never reuse or renumber them.
+
+// system and token programs
+pub const LABEL_SYSTEM_ACCOUNT: EdgeLabel = 0x0101;
+pub const LABEL_SYSTEM_NONCE: EdgeLabel = 0x0102;
+pub const LABEL_SYSTEM_NONCE_AUTHORITY: EdgeLabel = 0x0103;
+pub const LABEL_TOKEN_ACCOUNT_MINT: EdgeLabel = 0x0110;
+pub const LABEL_TOKEN_ACCOUNT_OWNER: EdgeLabel = 0x0111;
+pub const LABEL_TOKEN_ACCOUNT_DELEGATE: EdgeLabel = 0x0112;
+pub const LABEL_TOKEN_ACCOUNT_CLOSE_AUTHORITY: EdgeLabel = 0x0113;
+pub const LABEL_TOKEN_MINT_PROGRAM: EdgeLabel = 0x0114;
+pub const LABEL_TOKEN_MINT_MINT_AUTHORITY: EdgeLabel = 0x0115;
+pub const LABEL_TOKEN_MINT_FREEZE_AUTHORITY: EdgeLabel = 0x0116;
+pub const LABEL_TOKEN_MULTISIG_SIGNER: EdgeLabel = 0x0117;
+pub const LABEL_TOKEN_2022_MINT_TRANSFER_FEE_CONFIG_AUTHORITY: EdgeLabel = 0x0120;
+pub const LABEL_TOKEN_2022_MINT_WITHDRAW_WITHHELD_AUTHORITY: EdgeLabel = 0x0121;
+pub const LABEL_TOKEN_2022_MINT_CLOSE_AUTHORITY: EdgeLabel = 0x0122;
+pub const LABEL_TOKEN_2022_MINT_CONFIDENTIAL_TRANSFER_AUTHORITY: EdgeLabel = 0x0123;
+pub const LABEL_TOKEN_2022_MINT_PERMANENT_DELEGATE: EdgeLabel = 0x0124;
+pub const LABEL_TOKEN_2022_MINT_TRANSFER_HOOK_PROGRAM: EdgeLabel = 0x0125;
+pub const LABEL_TOKEN_2022_MINT_METADATA_ADDRESS: EdgeLabel = 0x0126;
+pub const LABEL_TOKEN_2022_MINT_GROUP_ADDRESS: EdgeLabel = 0x0127;
+
+// upgradeable BPF loader
+pub const LABEL_LOADER_BUFFER_AUTHORITY: EdgeLabel = 0x0201;
+pub const LABEL_LOADER_PROGRAM_PROGRAMDATA: EdgeLabel = 0x0202;
+pub const LABEL_LOADER_PROGRAMDATA_UPGRADE_AUTHORITY: EdgeLabel = 0x0203;
+pub const LABEL_LOADER_PROGRAMDATA_IMMUTABLE: EdgeLabel = 0x0204;
+
+// stake
+pub const LABEL_STAKE_ACCOUNT_STAKER: EdgeLabel = 0x0301;
+pub const LABEL_STAKE_ACCOUNT_WITHDRAWER: EdgeLabel = 0x0302;
+pub const LABEL_STAKE_ACCOUNT_CUSTODIAN: EdgeLabel = 0x0303;
+pub const LABEL_STAKE_ACCOUNT_VOTER: EdgeLabel = 0x0304;
+
+// vote
+pub const LABEL_VOTE_ACCOUNT_NODE: EdgeLabel = 0x0401;
+pub const LABEL_VOTE_ACCOUNT_WITHDRAWER: EdgeLabel = 0x0402;
+pub const LABEL_VOTE_ACCOUNT_VOTER: EdgeLabel = 0x0403;
+
+// address lookup table
+pub const LABEL_LOOKUP_TABLE_AUTHORITY: EdgeLabel = 0x0501;
+pub const LABEL_LOOKUP_TABLE_ADDRESS: EdgeLabel = 0x0502;
+
+// safejar
+pub const LABEL_SAFEJAR_CONTROLLER_PROGRAM: EdgeLabel = 0x1001;
+pub const LABEL_SAFEJAR_CONTROLLER_OWNER: EdgeLabel = 0x1002;
+pub const LABEL_SAFEJAR_DELEGATION_CONTROLLER: EdgeLabel = 0x1003;
+
+// solpipe
+pub const LABEL_SOLPIPE_CONTROLLER_PROGRAM: EdgeLabel = 0x1101;
+pub const LABEL_SOLPIPE_CONTROLLER_ADMIN: EdgeLabel = 0x1102;
+pub const LABEL_SOLPIPE_CONTROLLER_API_CONTROLLER: EdgeLabel = 0x1103;
+pub const LABEL_SOLPIPE_PIPELINE_CONTROLLER: EdgeLabel = 0x1104;
+pub const LABEL_SOLPIPE_PIPELINE_ADMIN: EdgeLabel = 0x1105;
+pub const LABEL_SOLPIPE_PAYOUT_PIPELINE: EdgeLabel = 0x1106;
+pub const LABEL_SOLPIPE_PERIOD_RING_PIPELINE: EdgeLabel = 0x1107;
+pub const LABEL_SOLPIPE_REFUNDS_PIPELINE: EdgeLabel = 0x1108;
+pub const LABEL_SOLPIPE_REFUNDS_BIDDER: EdgeLabel = 0x1109;
+pub const LABEL_SOLPIPE_BID_LIST_PAYOUT: EdgeLabel = 0x110a;
+pub const LABEL_SOLPIPE_BID_LIST_BIDDER: EdgeLabel = 0x110b;
+pub const LABEL_SOLPIPE_AGENT_CONTROLLER: EdgeLabel = 0x110c;
+pub const LABEL_SOLPIPE_AGENT_AUTHORIZER: EdgeLabel = 0x110d;
+pub const LABEL_SOLPIPE_AGENT_VAULT: EdgeLabel = 0x110e;
+
+// orca
+pub const LABEL_ORCA_WHIRLPOOLS_CONFIG_PROGRAM: EdgeLabel = 0x1201;
+pub const LABEL_ORCA_WHIRLPOOLS_CONFIG_FEE_AUTHORITY: EdgeLabel = 0x1202;
+pub const LABEL_ORCA_WHIRLPOOLS_CONFIG_COLLECT_PROTOCOL_FEES_AUTHORITY: EdgeLabel = 0x1203;
+pub const LABEL_ORCA_WHIRLPOOLS_CONFIG_REWARD_EMISSIONS_SUPER_AUTHORITY: EdgeLabel = 0x1204;
+pub const LABEL_ORCA_WHIRLPOOL_WHIRLPOOLS_CONFIG: EdgeLabel = 0x1205;
+pub const LABEL_ORCA_WHIRLPOOL_TOKEN_VAULT_A: EdgeLabel = 0x1206;
+pub const LABEL_ORCA_WHIRLPOOL_TOKEN_VAULT_B: EdgeLabel = 0x1207;
+pub const LABEL_ORCA_TICK_ARRAY_WHIRLPOOL: EdgeLabel = 0x1208;
+
+// raydium cpmm
+pub const LABEL_RAYDIUM_CPMM_AMM_CONFIG_PROTOCOL_OWNER: EdgeLabel = 0x1301;
+pub const LABEL_RAYDIUM_CPMM_AMM_CONFIG_FUND_OWNER: EdgeLabel = 0x1302;
+pub const LABEL_RAYDIUM_CPMM_POOL_STATE_AMM_CONFIG: EdgeLabel = 0x1303;
+pub const LABEL_RAYDIUM_CPMM_POOL_STATE_TOKEN_0_VAULT: EdgeLabel = 0x1304;
+pub const LABEL_RAYDIUM_CPMM_POOL_STATE_TOKEN_1_VAULT: EdgeLabel = 0x1305;
+pub const LABEL_RAYDIUM_CPMM_POOL_STATE_LP_MINT: EdgeLabel = 0x1306;
+pub const LABEL_RAYDIUM_CPMM_POOL_STATE_OBSERVATION: EdgeLabel = 0x1307;
+
+// raydium amm v4
+pub const LABEL_RAYDIUM_AMM_AMM_INFO_COIN_VAULT: EdgeLabel = 0x1401;
+pub const LABEL_RAYDIUM_AMM_AMM_INFO_PC_VAULT: EdgeLabel = 0x1402;
+pub const LABEL_RAYDIUM_AMM_AMM_INFO_LP_MINT: EdgeLabel = 0x1403;
+pub const LABEL_RAYDIUM_AMM_AMM_INFO_OPEN_ORDERS: EdgeLabel = 0x1404;
+pub const LABEL_RAYDIUM_AMM_AMM_INFO_MARKET: EdgeLabel = 0x1405;
+
+// raydium clmm
+pub const LABEL_RAYDIUM_CLMM_AMM_CONFIG_PROTOCOL_OWNER: EdgeLabel = 0x1501;
+pub const LABEL_RAYDIUM_CLMM_AMM_CONFIG_FUND_OWNER: EdgeLabel = 0x1502;
+pub const LABEL_RAYDIUM_CLMM_PROTOCOL_POSITION_POOL: EdgeLabel = 0x1503;
+pub const LABEL_RAYDIUM_CLMM_TICK_ARRAY_POOL: EdgeLabel = 0x1504;
+pub const LABEL_RAYDIUM_CLMM_PERSONAL_POSITION_NFT_MINT: EdgeLabel = 0x1505;
+pub const LABEL_RAYDIUM_CLMM_PERSONAL_POSITION_POOL: EdgeLabel = 0x1506;
+pub const LABEL_RAYDIUM_CLMM_OBSERVATION_POOL: EdgeLabel = 0x1507;
+pub const LABEL_RAYDIUM_CLMM_TICK_ARRAY_BITMAP_EXTENSION_POOL: EdgeLabel = 0x1508;
+pub const LABEL_RAYDIUM_CLMM_POOL_STATE_AMM_CONFIG: EdgeLabel = 0x1509;
+pub const LABEL_RAYDIUM_CLMM_POOL_STATE_OWNER: EdgeLabel = 0x150a;
+pub const LABEL_RAYDIUM_CLMM_POOL_STATE_TOKEN_VAULT_0: EdgeLabel = 0x150b;
+pub const LABEL_RAYDIUM_CLMM_POOL_STATE_TOKEN_VAULT_1: EdgeLabel = 0x150c;
+pub const LABEL_RAYDIUM_CLMM_POOL_STATE_OBSERVATION: EdgeLabel = 0x150d;
+pub const LABEL_RAYDIUM_CLMM_OPERATION_STATE_OPERATION_OWNER: EdgeLabel = 0x150e;
+pub const LABEL_RAYDIUM_CLMM_OPERATION_STATE_WHITELIST_MINT: EdgeLabel = 0x150f;
+
+// meteora dlmm
+pub const LABEL_METEORA_LB_PAIR_PROGRAM: EdgeLabel = 0x1601;
+pub const LABEL_METEORA_LB_PAIR_TOKEN_X_MINT: EdgeLabel = 0x1602;
+pub const LABEL_METEORA_LB_PAIR_TOKEN_Y_MINT: EdgeLabel = 0x1603;
+pub const LABEL_METEORA_LB_PAIR_RESERVE_X: EdgeLabel = 0x1604;
+pub const LABEL_METEORA_LB_PAIR_RESERVE_Y: EdgeLabel = 0x1605;
+pub const LABEL_METEORA_LB_PAIR_ORACLE: EdgeLabel = 0x1606;
+pub const LABEL_METEORA_LB_PAIR_PRE_ACTIVATION_SWAP_ADDRESS: EdgeLabel = 0x1607;
+pub const LABEL_METEORA_LB_PAIR_BASE_KEY: EdgeLabel = 0x1608;
+pub const LABEL_METEORA_LB_PAIR_CREATOR: EdgeLabel = 0x1609;
+pub const LABEL_METEORA_BIN_ARRAY_LB_PAIR: EdgeLabel = 0x160a;
+pub const LABEL_METEORA_BIN_ARRAY_BITMAP_EXTENSION_LB_PAIR: EdgeLabel = 0x160b;
+pub const LABEL_METEORA_POSITION_LB_PAIR: EdgeLabel = 0x160c;
+pub const LABEL_METEORA_POSITION_OWNER: EdgeLabel = 0x160d;
+pub const LABEL_METEORA_POSITION_V2_OPERATOR: EdgeLabel = 0x160e;
+pub const LABEL_METEORA_POSITION_V2_FEE_OWNER: EdgeLabel = 0x160f;
+
+// filters configured at run time; their fields are not known at compile time
+pub const LABEL_IDL_FIELD: EdgeLabel = 0x2001;
+pub const LABEL_RULE_OFFSET: EdgeLabel = 0x2101;
+
+/// Names of the edge labels, as returned to the host by the `label_list` export.
+pub const EDGE_LABEL_LIST: [(EdgeLabel, &str); 101] = [
+    (LABEL_SYSTEM_ACCOUNT, "system.account"),
+    (LABEL_SYSTEM_NONCE, "system.nonce"),
+    (LABEL_SYSTEM_NONCE_AUTHORITY, "system.nonce.authority"),
+    (LABEL_TOKEN_ACCOUNT_MINT, "token.account.mint"),
+    (LABEL_TOKEN_ACCOUNT_OWNER, "token.account.owner"),
+    (LABEL_TOKEN_ACCOUNT_DELEGATE, "token.account.delegate"),
+    (
+        LABEL_TOKEN_ACCOUNT_CLOSE_AUTHORITY,
+        "token.account.close_authority",
+    ),
+    (LABEL_TOKEN_MINT_PROGRAM, "token.mint.program"),
+    (LABEL_TOKEN_MINT_MINT_AUTHORITY, "token.mint.mint_authority"),
+    (
+        LABEL_TOKEN_MINT_FREEZE_AUTHORITY,
+        "token.mint.freeze_authority",
+    ),
+    (LABEL_TOKEN_MULTISIG_SIGNER, "token.multisig.signer"),
+    (
+        LABEL_TOKEN_2022_MINT_TRANSFER_FEE_CONFIG_AUTHORITY,
+        "token_2022.mint.transfer_fee_config_authority",
+    ),
+    (
+        LABEL_TOKEN_2022_MINT_WITHDRAW_WITHHELD_AUTHORITY,
+        "token_2022.mint.withdraw_withheld_authority",
+    ),
+    (
+        LABEL_TOKEN_2022_MINT_CLOSE_AUTHORITY,
+        "token_2022.mint.close_authority",
+    ),
+    (
+        LABEL_TOKEN_2022_MINT_CONFIDENTIAL_TRANSFER_AUTHORITY,
+        "token_2022.mint.confidential_transfer_authority",
+    ),
+    (
+        LABEL_TOKEN_2022_MINT_PERMANENT_DELEGATE,
+        "token_2022.mint.permanent_delegate",
+    ),
+    (
+        LABEL_TOKEN_2022_MINT_TRANSFER_HOOK_PROGRAM,
+        "token_2022.mint.transfer_hook_program",
+    ),
+    (
+        LABEL_TOKEN_2022_MINT_METADATA_ADDRESS,
+        "token_2022.mint.metadata_address",
+    ),
+    (
+        LABEL_TOKEN_2022_MINT_GROUP_ADDRESS,
+        "token_2022.mint.group_address",
+    ),
+    (LABEL_LOADER_BUFFER_AUTHORITY, "loader.buffer.authority"),
+    (
+        LABEL_LOADER_PROGRAM_PROGRAMDATA,
+        "loader.program.programdata",
+    ),
+    (
+        LABEL_LOADER_PROGRAMDATA_UPGRADE_AUTHORITY,
+        "loader.programdata.upgrade_authority",
+    ),
+    (
+        LABEL_LOADER_PROGRAMDATA_IMMUTABLE,
+        "loader.programdata.immutable",
+    ),
+    (LABEL_STAKE_ACCOUNT_STAKER, "stake.account.staker"),
+    (LABEL_STAKE_ACCOUNT_WITHDRAWER, "stake.account.withdrawer"),
+    (LABEL_STAKE_ACCOUNT_CUSTODIAN, "stake.account.custodian"),
+    (LABEL_STAKE_ACCOUNT_VOTER, "stake.account.voter"),
+    (LABEL_VOTE_ACCOUNT_NODE, "vote.account.node"),
+    (LABEL_VOTE_ACCOUNT_WITHDRAWER, "vote.account.withdrawer"),
+    (LABEL_VOTE_ACCOUNT_VOTER, "vote.account.voter"),
+    (LABEL_LOOKUP_TABLE_AUTHORITY, "lookup_table.table.authority"),
+    (LABEL_LOOKUP_TABLE_ADDRESS, "lookup_table.table.address"),
+    (
+        LABEL_SAFEJAR_CONTROLLER_PROGRAM,
+        "safejar.controller.program",
+    ),
+    (LABEL_SAFEJAR_CONTROLLER_OWNER, "safejar.controller.owner"),
+    (
+        LABEL_SAFEJAR_DELEGATION_CONTROLLER,
+        "safejar.delegation.controller",
+    ),
+    (
+        LABEL_SOLPIPE_CONTROLLER_PROGRAM,
+        "solpipe.controller.program",
+    ),
+    (LABEL_SOLPIPE_CONTROLLER_ADMIN, "solpipe.controller.admin"),
+    (
+        LABEL_SOLPIPE_CONTROLLER_API_CONTROLLER,
+        "solpipe.controller_api.controller",
+    ),
+    (
+        LABEL_SOLPIPE_PIPELINE_CONTROLLER,
+        "solpipe.pipeline.controller",
+    ),
+    (LABEL_SOLPIPE_PIPELINE_ADMIN, "solpipe.pipeline.admin"),
+    (LABEL_SOLPIPE_PAYOUT_PIPELINE, "solpipe.payout.pipeline"),
+    (
+        LABEL_SOLPIPE_PERIOD_RING_PIPELINE,
+        "solpipe.period_ring.pipeline",
+    ),
+    (LABEL_SOLPIPE_REFUNDS_PIPELINE, "solpipe.refunds.pipeline"),
+    (LABEL_SOLPIPE_REFUNDS_BIDDER, "solpipe.refunds.bidder"),
+    (LABEL_SOLPIPE_BID_LIST_PAYOUT, "solpipe.bid_list.payout"),
+    (LABEL_SOLPIPE_BID_LIST_BIDDER, "solpipe.bid_list.bidder"),
+    (LABEL_SOLPIPE_AGENT_CONTROLLER, "solpipe.agent.controller"),
+    (LABEL_SOLPIPE_AGENT_AUTHORIZER, "solpipe.agent.authorizer"),
+    (LABEL_SOLPIPE_AGENT_VAULT, "solpipe.agent.vault"),
+    (
+        LABEL_ORCA_WHIRLPOOLS_CONFIG_PROGRAM,
+        "orca.whirlpools_config.program",
+    ),
+    (
+        LABEL_ORCA_WHIRLPOOLS_CONFIG_FEE_AUTHORITY,
+        "orca.whirlpools_config.fee_authority",
+    ),
+    (
+        LABEL_ORCA_WHIRLPOOLS_CONFIG_COLLECT_PROTOCOL_FEES_AUTHORITY,
+        "orca.whirlpools_config.collect_protocol_fees_authority",
+    ),
+    (
+        LABEL_ORCA_WHIRLPOOLS_CONFIG_REWARD_EMISSIONS_SUPER_AUTHORITY,
+        "orca.whirlpools_config.reward_emissions_super_authority",
+    ),
+    (
+        LABEL_ORCA_WHIRLPOOL_WHIRLPOOLS_CONFIG,
+        "orca.whirlpool.whirlpools_config",
+    ),
+    (
+        LABEL_ORCA_WHIRLPOOL_TOKEN_VAULT_A,
+        "orca.whirlpool.token_vault_a",
+    ),
+    (
+        LABEL_ORCA_WHIRLPOOL_TOKEN_VAULT_B,
+        "orca.whirlpool.token_vault_b",
+    ),
+    (LABEL_ORCA_TICK_ARRAY_WHIRLPOOL, "orca.tick_array.whirlpool"),
+    (
+        LABEL_RAYDIUM_CPMM_AMM_CONFIG_PROTOCOL_OWNER,
+        "raydium_cpmm.amm_config.protocol_owner",
+    ),
+    (
+        LABEL_RAYDIUM_CPMM_AMM_CONFIG_FUND_OWNER,
+        "raydium_cpmm.amm_config.fund_owner",
+    ),
+    (
+        LABEL_RAYDIUM_CPMM_POOL_STATE_AMM_CONFIG,
+        "raydium_cpmm.pool_state.amm_config",
+    ),
+    (
+        LABEL_RAYDIUM_CPMM_POOL_STATE_TOKEN_0_VAULT,
+        "raydium_cpmm.pool_state.token_0_vault",
+    ),
+    (
+        LABEL_RAYDIUM_CPMM_POOL_STATE_TOKEN_1_VAULT,
+        "raydium_cpmm.pool_state.token_1_vault",
+    ),
+    (
+        LABEL_RAYDIUM_CPMM_POOL_STATE_LP_MINT,
+        "raydium_cpmm.pool_state.lp_mint",
+    ),
+    (
+        LABEL_RAYDIUM_CPMM_POOL_STATE_OBSERVATION,
+        "raydium_cpmm.pool_state.observation",
+    ),
+    (
+        LABEL_RAYDIUM_AMM_AMM_INFO_COIN_VAULT,
+        "raydium_amm.amm_info.coin_vault",
+    ),
+    (
+        LABEL_RAYDIUM_AMM_AMM_INFO_PC_VAULT,
+        "raydium_amm.amm_info.pc_vault",
+    ),
+    (
+        LABEL_RAYDIUM_AMM_AMM_INFO_LP_MINT,
+        "raydium_amm.amm_info.lp_mint",
+    ),
+    (
+        LABEL_RAYDIUM_AMM_AMM_INFO_OPEN_ORDERS,
+        "raydium_amm.amm_info.open_orders",
+    ),
+    (
+        LABEL_RAYDIUM_AMM_AMM_INFO_MARKET,
+        "raydium_amm.amm_info.market",
+    ),
+    (
+        LABEL_RAYDIUM_CLMM_AMM_CONFIG_PROTOCOL_OWNER,
+        "raydium_clmm.amm_config.protocol_owner",
+    ),
+    (
+        LABEL_RAYDIUM_CLMM_AMM_CONFIG_FUND_OWNER,
+        "raydium_clmm.amm_config.fund_owner",
+    ),
+    (
+        LABEL_RAYDIUM_CLMM_PROTOCOL_POSITION_POOL,
+        "raydium_clmm.protocol_position.pool",
+    ),
+    (
+        LABEL_RAYDIUM_CLMM_TICK_ARRAY_POOL,
+        "raydium_clmm.tick_array.pool",
+    ),
+    (
+        LABEL_RAYDIUM_CLMM_PERSONAL_POSITION_NFT_MINT,
+        "raydium_clmm.personal_position.nft_mint",
+    ),
+    (
+        LABEL_RAYDIUM_CLMM_PERSONAL_POSITION_POOL,
+        "raydium_clmm.personal_position.pool",
+    ),
+    (
+        LABEL_RAYDIUM_CLMM_OBSERVATION_POOL,
+        "raydium_clmm.observation.pool",
+    ),
+    (
+        LABEL_RAYDIUM_CLMM_TICK_ARRAY_BITMAP_EXTENSION_POOL,
+        "raydium_clmm.tick_array_bitmap_extension.pool",
+    ),
+    (
+        LABEL_RAYDIUM_CLMM_POOL_STATE_AMM_CONFIG,
+        "raydium_clmm.pool_state.amm_config",
+    ),
+    (
+        LABEL_RAYDIUM_CLMM_POOL_STATE_OWNER,
+        "raydium_clmm.pool_state.owner",
+    ),
+    (
+        LABEL_RAYDIUM_CLMM_POOL_STATE_TOKEN_VAULT_0,
+        "raydium_clmm.pool_state.token_vault_0",
+    ),
+    (
+        LABEL_RAYDIUM_CLMM_POOL_STATE_TOKEN_VAULT_1,
+        "raydium_clmm.pool_state.token_vault_1",
+    ),
+    (
+        LABEL_RAYDIUM_CLMM_POOL_STATE_OBSERVATION,
+        "raydium_clmm.pool_state.observation",
+    ),
+    (
+        LABEL_RAYDIUM_CLMM_OPERATION_STATE_OPERATION_OWNER,
+        "raydium_clmm.operation_state.operation_owner",
+    ),
+    (
+        LABEL_RAYDIUM_CLMM_OPERATION_STATE_WHITELIST_MINT,
+        "raydium_clmm.operation_state.whitelist_mint",
+    ),
+    (LABEL_METEORA_LB_PAIR_PROGRAM, "meteora.lb_pair.program"),
+    (
+        LABEL_METEORA_LB_PAIR_TOKEN_X_MINT,
+        "meteora.lb_pair.token_x_mint",
+    ),
+    (
+        LABEL_METEORA_LB_PAIR_TOKEN_Y_MINT,
+        "meteora.lb_pair.token_y_mint",
+    ),
+    (LABEL_METEORA_LB_PAIR_RESERVE_X, "meteora.lb_pair.reserve_x"),
+    (LABEL_METEORA_LB_PAIR_RESERVE_Y, "meteora.lb_pair.reserve_y"),
+    (LABEL_METEORA_LB_PAIR_ORACLE, "meteora.lb_pair.oracle"),
+    (
+        LABEL_METEORA_LB_PAIR_PRE_ACTIVATION_SWAP_ADDRESS,
+        "meteora.lb_pair.pre_activation_swap_address",
+    ),
+    (LABEL_METEORA_LB_PAIR_BASE_KEY, "meteora.lb_pair.base_key"),
+    (LABEL_METEORA_LB_PAIR_CREATOR, "meteora.lb_pair.creator"),
+    (LABEL_METEORA_BIN_ARRAY_LB_PAIR, "meteora.bin_array.lb_pair"),
+    (
+        LABEL_METEORA_BIN_ARRAY_BITMAP_EXTENSION_LB_PAIR,
+        "meteora.bin_array_bitmap_extension.lb_pair",
+    ),
+    (LABEL_METEORA_POSITION_LB_PAIR, "meteora.position.lb_pair"),
+    (LABEL_METEORA_POSITION_OWNER, "meteora.position.owner"),
+    (
+        LABEL_METEORA_POSITION_V2_OPERATOR,
+        "meteora.position_v2.operator",
+    ),
+    (
+        LABEL_METEORA_POSITION_V2_FEE_OWNER,
+        "meteora.position_v2.fee_owner",
+    ),
+    (LABEL_IDL_FIELD, "idl.field"),
+    (LABEL_RULE_OFFSET, "rule.offset"),
+];
+
+/// The name of a label, or `None` for `LABEL_NONE` and ids this guest does not know.
+pub fn label_name(label: EdgeLabel) -> Option<&'static str> {
+    EDGE_LABEL_LIST
+        .iter()
+        .find(|(l, _)| *l == label)
+        .map(|(_, n)| *n)
+}
+
+/// The registry sent to the host: one `id,name` line per label, with the id in decimal.
+pub fn label_registry() -> String {
+    let mut out = String::new();
+    for (label, name) in EDGE_LABEL_LIST.iter() {
+        writeln!(out, "{},{}", label, name).unwrap();
+    }
+    out
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*; // import functions from the parent module
+
+    #[test]
+    fn test_label_registry() {
+        let id: HashSet<EdgeLabel> = EDGE_LABEL_LIST.iter().map(|(l, _)| *l).collect();
+        let name: HashSet<&str> = EDGE_LABEL_LIST.iter().map(|(_, n)| *n).collect();
+        assert_eq!(id.len(), EDGE_LABEL_LIST.len());
+        assert_eq!(name.len(), EDGE_LABEL_LIST.len());
+        assert!(!id.contains(&LABEL_NONE));
+        assert_eq!(
+            label_name(LABEL_ORCA_WHIRLPOOL_TOKEN_VAULT_A),
+            Some("orca.whirlpool.token_vault_a")
+        );
+        assert_eq!(label_name(LABEL_NONE), None);
+        let registry = label_registry();
+        assert_eq!(registry.lines().count(), EDGE_LABEL_LIST.len());
+        assert!(registry.contains("\n4614,orca.whirlpool.token_vault_a\n"));
+    }
+}
@@ -1,10 +1,14 @@
 pub mod common;
+pub mod dispatch;
 pub mod err;
-#[cfg(target_os = "wasi")]
 pub mod filter;
 pub mod guest;
 pub mod header;
+pub mod label;
+pub mod node;
 pub mod soltoken;
+pub mod source;
+pub mod token2022;
 pub mod tree;
 #[cfg(target_os = "wasi")]
 pub mod wasmimport;
@@ -0,0 +1,171 @@
+/// What an account is, eg `orca.whirlpool` or `token.mint`, as told by the filter of its owner.
+pub type NodeType = u16;
+
+/// Accounts of programs that are not tracked, accounts that no filter recognizes and accounts
+/// of filters configured at run time.
+pub const NODE_TYPE_UNKNOWN: NodeType = 0;
+
+// Ids follow the blocks of the edge labels: every filter owns 256 ids.
+// Ids are part of the ABI: never reuse or renumber them.
+
+// system and token programs
+pub const NODE_TYPE_SYSTEM_ACCOUNT: NodeType = 0x0101;
+pub const NODE_TYPE_SYSTEM_NONCE: NodeType = 0x0102;
+pub const NODE_TYPE_TOKEN_ACCOUNT: NodeType = 0x0110;
+pub const NODE_TYPE_TOKEN_MINT: NodeType = 0x0111;
+pub const NODE_TYPE_TOKEN_MULTISIG: NodeType = 0x0112;
+pub const NODE_TYPE_TOKEN_2022_ACCOUNT: NodeType = 0x0120;
+pub const NODE_TYPE_TOKEN_2022_MINT: NodeType = 0x0121;
+pub const NODE_TYPE_TOKEN_2022_MULTISIG: NodeType = 0x0122;
+
+// upgradeable BPF loader
+pub const NODE_TYPE_LOADER_BUFFER: NodeType = 0x0201;
+pub const NODE_TYPE_LOADER_PROGRAM: NodeType = 0x0202;
+pub const NODE_TYPE_LOADER_PROGRAMDATA: NodeType = 0x0203;
+
+pub const NODE_TYPE_STAKE_ACCOUNT: NodeType = 0x0301;
+pub const NODE_TYPE_VOTE_ACCOUNT: NodeType = 0x0401;
+pub const NODE_TYPE_LOOKUP_TABLE: NodeType = 0x0501;
+
+// safejar
+pub const NODE_TYPE_SAFEJAR_CONTROLLER: NodeType = 0x1001;
+pub const NODE_TYPE_SAFEJAR_DELEGATION: NodeType = 0x1002;
+
+// solpipe
+pub const NODE_TYPE_SOLPIPE_CONTROLLER: NodeType = 0x1101;
+pub const NODE_TYPE_SOLPIPE_CONTROLLER_API: NodeType = 0x1102;
+pub const NODE_TYPE_SOLPIPE_PIPELINE: NodeType = 0x1103;
+pub const NODE_TYPE_SOLPIPE_PAYOUT: NodeType = 0x1104;
+pub const NODE_TYPE_SOLPIPE_PERIOD_RING: NodeType = 0x1105;
+pub const NODE_TYPE_SOLPIPE_REFUNDS: NodeType = 0x1106;
+pub const NODE_TYPE_SOLPIPE_BID_LIST: NodeType = 0x1107;
+pub const NODE_TYPE_SOLPIPE_AGENT: NodeType = 0x1108;
+
+// orca
+pub const NODE_TYPE_ORCA_WHIRLPOOLS_CONFIG: NodeType = 0x1201;
+pub const NODE_TYPE_ORCA_WHIRLPOOL: NodeType = 0x1202;
+pub const NODE_TYPE_ORCA_TICK_ARRAY: NodeType = 0x1203;
+
+// raydium cpmm
+pub const NODE_TYPE_RAYDIUM_CPMM_AMM_CONFIG: NodeType = 0x1301;
+pub const NODE_TYPE_RAYDIUM_CPMM_POOL_STATE: NodeType = 0x1302;
+
+// raydium amm v4
+pub const NODE_TYPE_RAYDIUM_AMM_AMM_INFO: NodeType = 0x1401;
+
+// raydium clmm
+pub const NODE_TYPE_RAYDIUM_CLMM_AMM_CONFIG: NodeType = 0x1501;
+pub const NODE_TYPE_RAYDIUM_CLMM_PROTOCOL_POSITION: NodeType = 0x1502;
+pub const NODE_TYPE_RAYDIUM_CLMM_SUPPORT_MINT_ASSOCIATED: NodeType = 0x1503;
+pub const NODE_TYPE_RAYDIUM_CLMM_TICK_ARRAY: NodeType = 0x1504;
+pub const NODE_TYPE_RAYDIUM_CLMM_PERSONAL_POSITION: NodeType = 0x1505;
+pub const NODE_TYPE_RAYDIUM_CLMM_OBSERVATION: NodeType = 0x1506;
+pub const NODE_TYPE_RAYDIUM_CLMM_TICK_ARRAY_BITMAP_EXTENSION: NodeType = 0x1507;
+pub const NODE_TYPE_RAYDIUM_CLMM_POOL_STATE: NodeType = 0x1508;
+pub const NODE_TYPE_RAYDIUM_CLMM_OPERATION_STATE: NodeType = 0x1509;
+
+// meteora dlmm
+pub const NODE_TYPE_METEORA_LB_PAIR: NodeType = 0x1601;
+pub const NODE_TYPE_METEORA_BIN_ARRAY: NodeType = 0x1602;
+pub const NODE_TYPE_METEORA_BIN_ARRAY_BITMAP_EXTENSION: NodeType = 0x1603;
+pub const NODE_TYPE_METEORA_POSITION: NodeType = 0x1604;
+pub const NODE_TYPE_METEORA_POSITION_V2: NodeType = 0x1605;
+
+/// Names of the node types.
+pub const NODE_TYPE_LIST: [(NodeType, &str); 44] = [
+    (NODE_TYPE_SYSTEM_ACCOUNT, "system.account"),
+    (NODE_TYPE_SYSTEM_NONCE, "system.nonce"),
+    (NODE_TYPE_TOKEN_ACCOUNT, "token.account"),
+    (NODE_TYPE_TOKEN_MINT, "token.mint"),
+    (NODE_TYPE_TOKEN_MULTISIG, "token.multisig"),
+    (NODE_TYPE_TOKEN_2022_ACCOUNT, "token_2022.account"),
+    (NODE_TYPE_TOKEN_2022_MINT, "token_2022.mint"),
+    (NODE_TYPE_TOKEN_2022_MULTISIG, "token_2022.multisig"),
+    (NODE_TYPE_LOADER_BUFFER, "loader.buffer"),
+    (NODE_TYPE_LOADER_PROGRAM, "loader.program"),
+    (NODE_TYPE_LOADER_PROGRAMDATA, "loader.programdata"),
+    (NODE_TYPE_STAKE_ACCOUNT, "stake.account"),
+    (NODE_TYPE_VOTE_ACCOUNT, "vote.account"),
+    (NODE_TYPE_LOOKUP_TABLE, "lookup_table.table"),
+    (NODE_TYPE_SAFEJAR_CONTROLLER, "safejar.controller"),
+    (NODE_TYPE_SAFEJAR_DELEGATION, "safejar.delegation"),
+    (NODE_TYPE_SOLPIPE_CONTROLLER, "solpipe.controller"),
+    (NODE_TYPE_SOLPIPE_CONTROLLER_API, "solpipe.controller_api"),
+    (NODE_TYPE_SOLPIPE_PIPELINE, "solpipe.pipeline"),
+    (NODE_TYPE_SOLPIPE_PAYOUT, "solpipe.payout"),
+    (NODE_TYPE_SOLPIPE_PERIOD_RING, "solpipe.period_ring"),
+    (NODE_TYPE_SOLPIPE_REFUNDS, "solpipe.refunds"),
+    (NODE_TYPE_SOLPIPE_BID_LIST, "solpipe.bid_list"),
+    (NODE_TYPE_SOLPIPE_AGENT, "solpipe.agent"),
+    (NODE_TYPE_ORCA_WHIRLPOOLS_CONFIG, "orca.whirlpools_config"),
+    (NODE_TYPE_ORCA_WHIRLPOOL, "orca.whirlpool"),
+    (NODE_TYPE_ORCA_TICK_ARRAY, "orca.tick_array"),
+    (NODE_TYPE_RAYDIUM_CPMM_AMM_CONFIG, "raydium_cpmm.amm_config"),
+    (NODE_TYPE_RAYDIUM_CPMM_POOL_STATE, "raydium_cpmm.pool_state"),
+    (NODE_TYPE_RAYDIUM_AMM_AMM_INFO, "raydium_amm.amm_info"),
+    (NODE_TYPE_RAYDIUM_CLMM_AMM_CONFIG, "raydium_clmm.amm_config"),
+    (
+        NODE_TYPE_RAYDIUM_CLMM_PROTOCOL_POSITION,
+        "raydium_clmm.protocol_position",
+    ),
+    (
+        NODE_TYPE_RAYDIUM_CLMM_SUPPORT_MINT_ASSOCIATED,
+        "raydium_clmm.support_mint_associated",
+    ),
+    (NODE_TYPE_RAYDIUM_CLMM_TICK_ARRAY, "raydium_clmm.tick_array"),
+    (
+        NODE_TYPE_RAYDIUM_CLMM_PERSONAL_POSITION,
+        "raydium_clmm.personal_position",
+    ),
+    (
+        NODE_TYPE_RAYDIUM_CLMM_OBSERVATION,
+        "raydium_clmm.observation",
+    ),
+    (
+        NODE_TYPE_RAYDIUM_CLMM_TICK_ARRAY_BITMAP_EXTENSION,
+        "raydium_clmm.tick_array_bitmap_extension",
+    ),
+    (NODE_TYPE_RAYDIUM_CLMM_POOL_STATE, "raydium_clmm.pool_state"),
+    (
+        NODE_TYPE_RAYDIUM_CLMM_OPERATION_STATE,
+        "raydium_clmm.operation_state",
+    ),
+    (NODE_TYPE_METEORA_LB_PAIR, "meteora.lb_pair"),
+    (NODE_TYPE_METEORA_BIN_ARRAY, "meteora.bin_array"),
+    (
+        NODE_TYPE_METEORA_BIN_ARRAY_BITMAP_EXTENSION,
+        "meteora.bin_array_bitmap_extension",
+    ),
+    (NODE_TYPE_METEORA_POSITION, "meteora.position"),
+    (NODE_TYPE_METEORA_POSITION_V2, "meteora.position_v2"),
+];
+
+/// The name of a node type, or `None` for `NODE_TYPE_UNKNOWN` and ids this guest does not know.
+pub fn node_type_name(node_type: NodeType) -> Option<&'static str> {
+    NODE_TYPE_LIST
+        .iter()
+        .find(|(t, _)| *t == node_type)
+        .map(|(_, n)| *n)
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*; // import functions from the parent module
+
+    #[test]
+    fn test_node_type_list() {
+        let id: HashSet<NodeType> = NODE_TYPE_LIST.iter().map(|(t, _)| *t).collect();
+        let name: HashSet<&str> = NODE_TYPE_LIST.iter().map(|(_, n)| *n).collect();
+        assert_eq!(id.len(), NODE_TYPE_LIST.len());
+        assert_eq!(name.len(), NODE_TYPE_LIST.len());
+        assert!(!id.contains(&NODE_TYPE_UNKNOWN));
+        assert_eq!(
+            node_type_name(NODE_TYPE_ORCA_WHIRLPOOL),
+            Some("orca.whirlpool")
+        );
+        assert_eq!(node_type_name(NODE_TYPE_UNKNOWN), None);
+    }
+}
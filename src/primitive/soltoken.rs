@@ -1,26 +1,133 @@
-use std::collections::VecDeque;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+};
 
 use solana_sdk::{
-    bpf_loader::ID as bpf_loader_id, pubkey::Pubkey, system_program::ID as system_id,
+    bpf_loader::ID as bpf_loader_id,
+    bpf_loader_upgradeable::ID as bpf_loader_upgradeable_id,
+    program_option::COption,
+    program_pack::{IsInitialized, Pack},
+    pubkey,
+    pubkey::Pubkey,
+    system_program::ID as system_id,
+};
+use spl_token::{
+    state::{Account, Mint, Multisig},
+    ID as token_id,
 };
-use spl_token::{state::Mint, ID as token_id};
 
 #[cfg(target_os = "wasi")]
 use super::wasmimport::HostImport;
 
 use super::{
-    guest::GuestFilter,
+    common::{AccountReader, U32_LEN, U64_LEN},
+    err::CatscopeWasmError,
+    guest::{edge_or_log, GuestFilter},
     header::AccountHeader,
-    tree::{FilterEdge, WEIGHT_PROGRAM, WEIGHT_SPLTOKEN_MINT, WEIGHT_SPLTOKEN_OWNER},
+    label::{
+        LABEL_LOADER_BUFFER_AUTHORITY, LABEL_LOADER_PROGRAMDATA_IMMUTABLE,
+        LABEL_LOADER_PROGRAMDATA_UPGRADE_AUTHORITY, LABEL_LOADER_PROGRAM_PROGRAMDATA,
+        LABEL_SYSTEM_ACCOUNT, LABEL_SYSTEM_NONCE, LABEL_SYSTEM_NONCE_AUTHORITY,
+        LABEL_TOKEN_ACCOUNT_CLOSE_AUTHORITY, LABEL_TOKEN_ACCOUNT_DELEGATE,
+        LABEL_TOKEN_ACCOUNT_MINT, LABEL_TOKEN_ACCOUNT_OWNER, LABEL_TOKEN_MINT_FREEZE_AUTHORITY,
+        LABEL_TOKEN_MINT_MINT_AUTHORITY, LABEL_TOKEN_MINT_PROGRAM, LABEL_TOKEN_MULTISIG_SIGNER,
+    },
+    node::{
+        NodeType, NODE_TYPE_LOADER_BUFFER, NODE_TYPE_LOADER_PROGRAM, NODE_TYPE_LOADER_PROGRAMDATA,
+        NODE_TYPE_SYSTEM_ACCOUNT, NODE_TYPE_SYSTEM_NONCE, NODE_TYPE_TOKEN_2022_ACCOUNT,
+        NODE_TYPE_TOKEN_2022_MINT, NODE_TYPE_TOKEN_2022_MULTISIG, NODE_TYPE_TOKEN_ACCOUNT,
+        NODE_TYPE_TOKEN_MINT, NODE_TYPE_TOKEN_MULTISIG, NODE_TYPE_UNKNOWN,
+    },
+    token2022::{self, AccountType, Extension, TOKEN_2022_ID},
+    tree::{
+        FilterEdge, WEIGHT_ATA, WEIGHT_AUTHORITY, WEIGHT_CLOSE_AUTHORITY, WEIGHT_DELEGATE,
+        WEIGHT_FREEZE_AUTHORITY, WEIGHT_IMMUTABLE, WEIGHT_MINT_AUTHORITY, WEIGHT_MULTISIG_SIGNER,
+        WEIGHT_NONCE, WEIGHT_PROGRAM, WEIGHT_PROGRAM_DATA, WEIGHT_SPLTOKEN_MINT,
+        WEIGHT_SPLTOKEN_OWNER,
+    },
 };
+/// The associated token account program.
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// The number of token accounts `AtaMemo::default` remembers.
+pub const ATA_MEMO_CAPACITY: usize = 32 * 1024;
+
+/// Whether a token account is the associated token account of its owner and mint.
+///
+/// Deriving the address takes up to 255 sha256 rounds, and token accounts are by far the most
+/// frequent updates. The answer is kept per token account and derived again only when the
+/// owner or the mint of the account changes. The oldest accounts are forgotten first.
+pub struct AtaMemo {
+    capacity: usize,
+    m_account: HashMap<Pubkey, (Pubkey, Pubkey, bool)>, // account to owner, mint and answer
+    l_account: VecDeque<Pubkey>,                        // oldest first
+}
+impl Default for AtaMemo {
+    fn default() -> Self {
+        Self::new(ATA_MEMO_CAPACITY)
+    }
+}
+impl AtaMemo {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            m_account: HashMap::new(),
+            l_account: VecDeque::new(),
+        }
+    }
+    pub fn is_ata(
+        &mut self,
+        account: &Pubkey,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        program_id: &Pubkey,
+    ) -> bool {
+        if let Some((o, m, answer)) = self.m_account.get(account) {
+            if o == owner && m == mint {
+                return *answer;
+            }
+        }
+        let answer = associated_token_address(owner, mint, program_id) == *account;
+        if self
+            .m_account
+            .insert(*account, (*owner, *mint, answer))
+            .is_none()
+        {
+            self.l_account.push_back(*account);
+            while self.l_account.len() > self.capacity {
+                if let Some(old) = self.l_account.pop_front() {
+                    self.m_account.remove(&old);
+                }
+            }
+        }
+        answer
+    }
+    pub fn len(&self) -> usize {
+        self.m_account.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.m_account.is_empty()
+    }
+}
+
 #[repr(C, align(8))]
 pub struct SolToken {
-    program_id: [Pubkey; 3],
+    program_id: [Pubkey; 5],
+    ata: RefCell<AtaMemo>,
 }
 impl Default for SolToken {
     fn default() -> Self {
         Self {
-            program_id: [system_id, token_id, bpf_loader_id],
+            program_id: [
+                system_id,
+                token_id,
+                bpf_loader_id,
+                TOKEN_2022_ID,
+                bpf_loader_upgradeable_id,
+            ],
+            ata: RefCell::new(AtaMemo::default()),
         }
     }
 }
@@ -30,18 +137,79 @@ impl GuestFilter for SolToken {
     }
 
     fn edge(&self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge> {
+        edge_or_log("soltoken", header, self.try_edge(header, data))
+    }
+
+    fn node_type(&self, header: &AccountHeader, data: &[u8]) -> NodeType {
+        let reader = AccountReader::new(data);
+        if header.owner.eq(&system_id) {
+            match nonce_authority(&reader) {
+                Ok(Some(_)) => NODE_TYPE_SYSTEM_NONCE,
+                _ => NODE_TYPE_SYSTEM_ACCOUNT,
+            }
+        } else if header.owner.eq(&token_id) {
+            match data.len() {
+                Account::LEN => NODE_TYPE_TOKEN_ACCOUNT,
+                Mint::LEN => NODE_TYPE_TOKEN_MINT,
+                Multisig::LEN => NODE_TYPE_TOKEN_MULTISIG,
+                _ => NODE_TYPE_UNKNOWN,
+            }
+        } else if header.owner.eq(&TOKEN_2022_ID) {
+            match token2022::account_type(&reader) {
+                Ok(Some(AccountType::Account)) => NODE_TYPE_TOKEN_2022_ACCOUNT,
+                Ok(Some(AccountType::Mint)) => NODE_TYPE_TOKEN_2022_MINT,
+                Ok(Some(AccountType::Multisig)) => NODE_TYPE_TOKEN_2022_MULTISIG,
+                _ => NODE_TYPE_UNKNOWN,
+            }
+        } else if header.owner.eq(&bpf_loader_upgradeable_id) {
+            match reader.u32_at(0) {
+                Ok(1) => NODE_TYPE_LOADER_BUFFER,
+                Ok(2) => NODE_TYPE_LOADER_PROGRAM,
+                Ok(3) => NODE_TYPE_LOADER_PROGRAMDATA,
+                _ => NODE_TYPE_UNKNOWN,
+            }
+        } else {
+            NODE_TYPE_UNKNOWN
+        }
+    }
+}
+impl SolToken {
+    fn try_edge(
+        &self,
+        header: &AccountHeader,
+        data: &[u8],
+    ) -> Result<VecDeque<FilterEdge>, CatscopeWasmError> {
         let mut list = VecDeque::new();
-        let pubkey_len = std::mem::size_of::<Pubkey>();
+        let reader = AccountReader::new(data);
         if header.owner.eq(&system_id) {
-            // there is nothing to do;
             #[cfg(target_os = "wasi")]
             HostImport::log(format!("system account - 1 -  {}", header.pubkey));
-            list.push_back(FilterEdge {
-                slot: header.slot,
-                from: system_id,
-                to: header.pubkey,
-                weight: WEIGHT_PROGRAM,
-            });
+            match nonce_authority(&reader)? {
+                Some(authority) => {
+                    list.push_back(FilterEdge {
+                        slot: header.slot,
+                        from: system_id,
+                        to: header.pubkey,
+                        weight: WEIGHT_PROGRAM | WEIGHT_NONCE,
+                        label: LABEL_SYSTEM_NONCE,
+                    });
+                    // nonce->authority;
+                    list.push_back(FilterEdge {
+                        slot: header.slot,
+                        from: header.pubkey,
+                        to: authority,
+                        weight: WEIGHT_AUTHORITY,
+                        label: LABEL_SYSTEM_NONCE_AUTHORITY,
+                    });
+                }
+                None => list.push_back(FilterEdge {
+                    slot: header.slot,
+                    from: system_id,
+                    to: header.pubkey,
+                    weight: WEIGHT_PROGRAM,
+                    label: LABEL_SYSTEM_ACCOUNT,
+                }),
+            }
         } else if header.owner.eq(&token_id) {
             #[cfg(target_os = "wasi")]
             HostImport::log(format!(
@@ -50,40 +218,704 @@ impl GuestFilter for SolToken {
                 header.owner,
                 data.len()
             ));
-            if 165 <= data.len() && data.len() <= 176 {
-                // both edges are incoming, not outgoing.
-                let mint = Pubkey::try_from(&data[0..pubkey_len]).unwrap();
-                let owner = Pubkey::try_from(&data[pubkey_len..2 * pubkey_len]).unwrap();
-                #[cfg(target_os = "wasi")]
-                HostImport::log(format!(
-                    "token edge - 1 -  id {}; owner {}; mint {};",
-                    header.pubkey, owner, mint
-                ));
-                // mint edge; mint->token;
+            // token program accounts are always exactly one of the three sizes
+            let account_type = match data.len() {
+                Account::LEN => AccountType::Account,
+                Mint::LEN => AccountType::Mint,
+                Multisig::LEN => AccountType::Multisig,
+                _ => return Ok(list),
+            };
+            self.token_edge(header, &token_id, account_type, &reader, &[], &mut list)?;
+        } else if header.owner.eq(&TOKEN_2022_ID) {
+            #[cfg(target_os = "wasi")]
+            HostImport::log(format!(
+                "token2022 - 1 -  id {}; account data {}",
+                header.pubkey,
+                data.len()
+            ));
+            // Token-2022 shares the base mint, account and multisig layouts with the token
+            // program; extended accounts add an account type byte and the TLV extension area.
+            let account_type = match token2022::account_type(&reader)? {
+                Some(x) => x,
+                None => return Ok(list),
+            };
+            // reject accounts with a truncated extension area before emitting anything
+            let extension_list = token2022::extension_list(&reader)?;
+            self.token_edge(
+                header,
+                &TOKEN_2022_ID,
+                account_type,
+                &reader,
+                &extension_list,
+                &mut list,
+            )?;
+        } else if header.owner.eq(&bpf_loader_upgradeable_id) {
+            #[cfg(target_os = "wasi")]
+            HostImport::log(format!(
+                "upgradeable loader - 1 -  id {}; account data {}",
+                header.pubkey,
+                data.len()
+            ));
+            self.loader_edge(header, &reader, &mut list)?;
+        }
+        Ok(list)
+    }
+
+    // Edges of an account owned by either token program; uninitialized accounts have none.
+    fn token_edge(
+        &self,
+        header: &AccountHeader,
+        program_id: &Pubkey,
+        account_type: AccountType,
+        reader: &AccountReader,
+        extension_list: &[Extension],
+        list: &mut VecDeque<FilterEdge>,
+    ) -> Result<(), CatscopeWasmError> {
+        match account_type {
+            AccountType::Account => {
+                if let Some(account) = unpack::<Account>(reader)? {
+                    #[cfg(target_os = "wasi")]
+                    HostImport::log(format!(
+                        "token edge - 1 -  id {}; owner {}; mint {};",
+                        header.pubkey, account.owner, account.mint
+                    ));
+                    self.account_edge(header, program_id, &account, list);
+                }
+            }
+            AccountType::Mint => {
+                if let Some(mint) = unpack::<Mint>(reader)? {
+                    #[cfg(target_os = "wasi")]
+                    HostImport::log(format!("mint edge - 1 - mint {}", header.pubkey));
+                    self.mint_edge(header, program_id, &mint, list);
+                    self.extension_edge(header, reader, extension_list, list)?;
+                }
+            }
+            AccountType::Multisig => {
+                if let Some(multisig) = unpack::<Multisig>(reader)? {
+                    self.multisig_edge(header, &multisig, list)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn account_edge(
+        &self,
+        header: &AccountHeader,
+        program_id: &Pubkey,
+        account: &Account,
+        list: &mut VecDeque<FilterEdge>,
+    ) {
+        // both edges are incoming, not outgoing.
+        // mint edge; mint->token;
+        list.push_back(FilterEdge {
+            slot: header.slot,
+            from: account.mint,
+            to: header.pubkey,
+            weight: WEIGHT_SPLTOKEN_MINT,
+            label: LABEL_TOKEN_ACCOUNT_MINT,
+        });
+        // owner edge; owner->token;
+        let mut weight = WEIGHT_SPLTOKEN_OWNER;
+        if self
+            .ata
+            .borrow_mut()
+            .is_ata(&header.pubkey, &account.owner, &account.mint, program_id)
+        {
+            weight |= WEIGHT_ATA;
+        }
+        list.push_back(FilterEdge {
+            slot: header.slot,
+            from: account.owner,
+            to: header.pubkey,
+            weight,
+            label: LABEL_TOKEN_ACCOUNT_OWNER,
+        });
+        // authority edges; delegate/close authority->token;
+        for (authority, weight, label) in [
+            (
+                account.delegate,
+                WEIGHT_DELEGATE,
+                LABEL_TOKEN_ACCOUNT_DELEGATE,
+            ),
+            (
+                account.close_authority,
+                WEIGHT_CLOSE_AUTHORITY,
+                LABEL_TOKEN_ACCOUNT_CLOSE_AUTHORITY,
+            ),
+        ] {
+            if let COption::Some(from) = authority {
                 list.push_back(FilterEdge {
                     slot: header.slot,
-                    from: mint,
+                    from,
                     to: header.pubkey,
-                    weight: WEIGHT_SPLTOKEN_MINT,
+                    weight,
+                    label,
                 });
-                // owner edge; owner->token;
+            }
+        }
+    }
+
+    fn mint_edge(
+        &self,
+        header: &AccountHeader,
+        program_id: &Pubkey,
+        mint: &Mint,
+        list: &mut VecDeque<FilterEdge>,
+    ) {
+        list.push_back(FilterEdge {
+            slot: header.slot,
+            from: *program_id,
+            to: header.pubkey,
+            weight: WEIGHT_PROGRAM,
+            label: LABEL_TOKEN_MINT_PROGRAM,
+        });
+        // mint/freeze authority->mint;
+        for (authority, weight, label) in [
+            (
+                mint.mint_authority,
+                WEIGHT_MINT_AUTHORITY,
+                LABEL_TOKEN_MINT_MINT_AUTHORITY,
+            ),
+            (
+                mint.freeze_authority,
+                WEIGHT_FREEZE_AUTHORITY,
+                LABEL_TOKEN_MINT_FREEZE_AUTHORITY,
+            ),
+        ] {
+            if let COption::Some(from) = authority {
                 list.push_back(FilterEdge {
                     slot: header.slot,
-                    from: owner,
+                    from,
                     to: header.pubkey,
-                    weight: WEIGHT_SPLTOKEN_OWNER,
+                    weight,
+                    label,
                 });
-            } else if 82 <= data.len() || data.len() <= std::mem::size_of::<Mint>() {
-                #[cfg(target_os = "wasi")]
-                HostImport::log(format!("mint edge - 1 - mint {}", header.pubkey));
+            }
+        }
+    }
+
+    // `UpgradeableLoaderState` is bincode encoded: a u32 variant tag, then the fields.
+    fn loader_edge(
+        &self,
+        header: &AccountHeader,
+        reader: &AccountReader,
+        list: &mut VecDeque<FilterEdge>,
+    ) -> Result<(), CatscopeWasmError> {
+        match reader.u32_at(0)? {
+            // Buffer { authority_address }
+            1 => {
+                if let Some(authority) = option_pubkey_at(reader, U32_LEN)? {
+                    // buffer->authority;
+                    list.push_back(FilterEdge {
+                        slot: header.slot,
+                        from: header.pubkey,
+                        to: authority,
+                        weight: WEIGHT_AUTHORITY,
+                        label: LABEL_LOADER_BUFFER_AUTHORITY,
+                    });
+                }
+            }
+            // Program { programdata_address }
+            2 => {
+                // program->programdata;
                 list.push_back(FilterEdge {
                     slot: header.slot,
-                    from: token_id,
-                    to: header.pubkey,
-                    weight: WEIGHT_PROGRAM,
+                    from: header.pubkey,
+                    to: reader.pubkey_at(U32_LEN)?,
+                    weight: WEIGHT_PROGRAM_DATA,
+                    label: LABEL_LOADER_PROGRAM_PROGRAMDATA,
                 });
             }
+            // ProgramData { slot, upgrade_authority_address }
+            3 => match option_pubkey_at(reader, U32_LEN + U64_LEN)? {
+                // programdata->upgrade authority;
+                Some(authority) => list.push_back(FilterEdge {
+                    slot: header.slot,
+                    from: header.pubkey,
+                    to: authority,
+                    weight: WEIGHT_AUTHORITY,
+                    label: LABEL_LOADER_PROGRAMDATA_UPGRADE_AUTHORITY,
+                }),
+                // immutable programs are marked by an edge from the loader
+                None => list.push_back(FilterEdge {
+                    slot: header.slot,
+                    from: bpf_loader_upgradeable_id,
+                    to: header.pubkey,
+                    weight: WEIGHT_IMMUTABLE,
+                    label: LABEL_LOADER_PROGRAMDATA_IMMUTABLE,
+                }),
+            },
+            // Uninitialized
+            0 => {}
+            _ => return Err(CatscopeWasmError::FailedToParse),
+        }
+        Ok(())
+    }
+
+    // extension edges; hook program/authority/pointed account->mint;
+    fn extension_edge(
+        &self,
+        header: &AccountHeader,
+        reader: &AccountReader,
+        extension_list: &[Extension],
+        list: &mut VecDeque<FilterEdge>,
+    ) -> Result<(), CatscopeWasmError> {
+        for (pubkey, weight, label) in token2022::extension_pubkey_list(reader, extension_list)? {
+            // metadata and group pointers usually point at the mint itself
+            if pubkey == header.pubkey {
+                continue;
+            }
+            list.push_back(FilterEdge {
+                slot: header.slot,
+                from: pubkey,
+                to: header.pubkey,
+                weight,
+                label,
+            });
+        }
+        Ok(())
+    }
+
+    // signer->multisig; only the first n signers are set.
+    fn multisig_edge(
+        &self,
+        header: &AccountHeader,
+        multisig: &Multisig,
+        list: &mut VecDeque<FilterEdge>,
+    ) -> Result<(), CatscopeWasmError> {
+        let signer_list = multisig
+            .signers
+            .get(..multisig.n as usize)
+            .ok_or(CatscopeWasmError::FailedToParse)?;
+        for signer in signer_list.iter() {
+            list.push_back(FilterEdge {
+                slot: header.slot,
+                from: *signer,
+                to: header.pubkey,
+                weight: WEIGHT_MULTISIG_SIGNER,
+                label: LABEL_TOKEN_MULTISIG_SIGNER,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Size of a durable nonce account.
+pub const NONCE_SIZE: usize = 80;
+
+// Durable nonce accounts hold a bincode `nonce::state::Versions`: a u32 version (legacy or
+// current), a u32 state and, once initialized, the authority, the nonce and the fee.
+// Every other system account is a plain wallet.
+fn nonce_authority(reader: &AccountReader) -> Result<Option<Pubkey>, CatscopeWasmError> {
+    if reader.len() != NONCE_SIZE {
+        return Ok(None);
+    }
+    let version = reader.u32_at(0)?;
+    let state = reader.u32_at(U32_LEN)?;
+    if version <= 1 && state == 1 {
+        Ok(Some(reader.pubkey_at(2 * U32_LEN)?))
+    } else {
+        Ok(None)
+    }
+}
+
+// A bincode `Option<Pubkey>`: a one byte tag followed by the pubkey when set.
+fn option_pubkey_at(reader: &AccountReader, i: usize) -> Result<Option<Pubkey>, CatscopeWasmError> {
+    match reader.u8_at(i)? {
+        0 => Ok(None),
+        1 => Ok(Some(reader.pubkey_at(i + 1)?)),
+        _ => Err(CatscopeWasmError::FailedToParse),
+    }
+}
+
+/// The address of the associated token account of `owner` for `mint` under either token program.
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), program_id.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+// Decode the base layout of a token program account; both token programs share it.
+// Returns `None` for accounts that are not initialized.
+fn unpack<T: Pack + IsInitialized>(reader: &AccountReader) -> Result<Option<T>, CatscopeWasmError> {
+    let x = T::unpack_from_slice(reader.bytes_at(0, T::LEN)?)
+        .map_err(|_| CatscopeWasmError::FailedToParse)?;
+    Ok(x.is_initialized().then_some(x))
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use spl_token::state::AccountState;
+
+    use crate::primitive::{
+        token2022::tests::extended,
+        tree::{weight_list, WEIGHT_DIRECT, WEIGHT_TRANSFER_HOOK},
+    };
+
+    use super::*; // import functions from the parent module
+
+    fn header(owner: Pubkey, data: &[u8]) -> AccountHeader {
+        AccountHeader {
+            pubkey: Pubkey::new_unique(),
+            lamports: 1,
+            data_size: data.len() as u32,
+            owner,
+            slot: 5,
+            ..Default::default()
+        }
+    }
+
+    fn edge_list(list: &VecDeque<FilterEdge>) -> Vec<(Pubkey, Pubkey, u32)> {
+        list.iter().map(|e| (e.from, e.to, e.weight)).collect()
+    }
+
+    #[test]
+    fn test_authority() {
+        let filter = SolToken::default();
+        let [mint, owner, delegate, close, freeze] = [0; 5].map(|_| Pubkey::new_unique());
+        let mut data = vec![0; Account::LEN];
+        Account {
+            mint,
+            owner,
+            delegate: COption::Some(delegate),
+            state: AccountState::Initialized,
+            close_authority: COption::Some(close),
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        let h = header(token_id, &data);
+        assert_eq!(
+            edge_list(&filter.edge(&h, &data)),
+            vec![
+                (mint, h.pubkey, WEIGHT_SPLTOKEN_MINT),
+                (owner, h.pubkey, WEIGHT_SPLTOKEN_OWNER),
+                (delegate, h.pubkey, WEIGHT_DELEGATE),
+                (close, h.pubkey, WEIGHT_CLOSE_AUTHORITY),
+            ]
+        );
+        // the same layout under Token-2022
+        let h = header(TOKEN_2022_ID, &data);
+        assert_eq!(filter.edge(&h, &data).len(), 4);
+
+        // a mint without a mint authority
+        let mut data = vec![0; Mint::LEN];
+        Mint {
+            freeze_authority: COption::Some(freeze),
+            is_initialized: true,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        let h = header(token_id, &data);
+        assert_eq!(
+            edge_list(&filter.edge(&h, &data)),
+            vec![
+                (token_id, h.pubkey, WEIGHT_PROGRAM),
+                (freeze, h.pubkey, WEIGHT_FREEZE_AUTHORITY),
+            ]
+        );
+        // a bad COption tag
+        data[0] = 2;
+        assert!(filter.edge(&h, &data).is_empty());
+
+        // each authority has its own indexed bit, apart from the owner and mint edges
+        let list = [
+            WEIGHT_MINT_AUTHORITY,
+            WEIGHT_FREEZE_AUTHORITY,
+            WEIGHT_DELEGATE,
+            WEIGHT_CLOSE_AUTHORITY,
+        ];
+        for (i, weight) in list.iter().enumerate() {
+            assert!(list[i + 1..].iter().all(|x| x & weight == 0));
+            for other in [WEIGHT_SPLTOKEN_OWNER, WEIGHT_SPLTOKEN_MINT, WEIGHT_DIRECT] {
+                assert_eq!(other & weight, 0);
+                assert!(!weight_list(&other).contains(weight));
+            }
+            assert_eq!(weight_list(weight), &[*weight, 0]);
+        }
+    }
+
+    #[test]
+    fn test_account_kind() {
+        let filter = SolToken::default();
+        let [mint, owner, a, b, c] = [0; 5].map(|_| Pubkey::new_unique());
+        let mut account = vec![0; Account::LEN];
+        Account {
+            mint,
+            owner,
+            state: AccountState::Frozen,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut account);
+        let mut multisig = vec![0; Multisig::LEN];
+        let mut signers = [Pubkey::default(); 11];
+        signers[..3].copy_from_slice(&[a, b, c]);
+        Multisig {
+            m: 2,
+            n: 3,
+            is_initialized: true,
+            signers,
+        }
+        .pack_into_slice(&mut multisig);
+        for program_id in [token_id, TOKEN_2022_ID] {
+            // frozen accounts keep their edges
+            let h = header(program_id, &account);
+            assert_eq!(
+                edge_list(&filter.edge(&h, &account)),
+                vec![
+                    (mint, h.pubkey, WEIGHT_SPLTOKEN_MINT),
+                    (owner, h.pubkey, WEIGHT_SPLTOKEN_OWNER),
+                ]
+            );
+            // a multisig is linked to its n signers
+            let h = header(program_id, &multisig);
+            assert_eq!(
+                edge_list(&filter.edge(&h, &multisig)),
+                vec![
+                    (a, h.pubkey, WEIGHT_MULTISIG_SIGNER),
+                    (b, h.pubkey, WEIGHT_MULTISIG_SIGNER),
+                    (c, h.pubkey, WEIGHT_MULTISIG_SIGNER),
+                ]
+            );
+            // uninitialized accounts of every kind have no edges
+            for len in [Account::LEN, Mint::LEN, Multisig::LEN] {
+                let data = vec![0; len];
+                assert!(filter.edge(&header(program_id, &data), &data).is_empty());
+            }
+        }
+        // the token program has no other account sizes; these used to be taken for mints
+        for len in [0, 100, 170, 200, 400] {
+            let mut data = vec![0; len];
+            data[..Mint::LEN.min(len)].fill(1);
+            assert!(filter.edge(&header(token_id, &data), &data).is_empty());
+        }
+        // a multisig with more signers than fit is malformed
+        multisig[1] = 12;
+        assert!(filter
+            .edge(&header(token_id, &multisig), &multisig)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_nonce() {
+        let filter = SolToken::default();
+        let authority = Pubkey::new_unique();
+        let mut data = vec![0; NONCE_SIZE];
+        data[0] = 1;
+        data[4] = 1;
+        data[8..40].copy_from_slice(authority.as_ref());
+        data[40..72].fill(3);
+        let h = header(system_id, &data);
+        assert_eq!(
+            edge_list(&filter.edge(&h, &data)),
+            vec![
+                (system_id, h.pubkey, WEIGHT_PROGRAM | WEIGHT_NONCE),
+                (h.pubkey, authority, WEIGHT_AUTHORITY),
+            ]
+        );
+        assert_eq!(filter.node_type(&h, &data), NODE_TYPE_SYSTEM_NONCE);
+        // legacy nonces have the same layout
+        data[0] = 0;
+        assert_eq!(filter.edge(&h, &data).len(), 2);
+        // wallets, uninitialized nonces and other system accounts
+        let wallet = vec![(system_id, h.pubkey, WEIGHT_PROGRAM)];
+        assert_eq!(edge_list(&filter.edge(&h, &[])), wallet);
+        assert_eq!(edge_list(&filter.edge(&h, &data[..79])), wallet);
+        data[4] = 0;
+        assert_eq!(edge_list(&filter.edge(&h, &data)), wallet);
+        assert_eq!(filter.node_type(&h, &data), NODE_TYPE_SYSTEM_ACCOUNT);
+        data[4] = 1;
+        data[0] = 2;
+        assert_eq!(edge_list(&filter.edge(&h, &data)), wallet);
+
+        // a subscription to the nonce bit picks nonces but not wallets
+        let nonce = WEIGHT_PROGRAM | WEIGHT_NONCE;
+        assert_ne!(nonce, wallet[0].2);
+        assert!(weight_list(&nonce).contains(&WEIGHT_NONCE));
+        assert!(!weight_list(&wallet[0].2).contains(&WEIGHT_NONCE));
+    }
+
+    #[test]
+    fn test_upgradeable_loader() {
+        let filter = SolToken::default();
+        let [programdata, authority] = [0; 2].map(|_| Pubkey::new_unique());
+        let loader = bpf_loader_upgradeable_id;
+        // program
+        let data = [&2u32.to_le_bytes()[..], programdata.as_ref()].concat();
+        let h = header(loader, &data);
+        assert_eq!(
+            edge_list(&filter.edge(&h, &data)),
+            vec![(h.pubkey, programdata, WEIGHT_PROGRAM_DATA)]
+        );
+        // programdata with an upgrade authority, followed by the program bytes
+        let mut data = [
+            &3u32.to_le_bytes()[..],
+            &9u64.to_le_bytes(),
+            &[1],
+            authority.as_ref(),
+        ]
+        .concat();
+        data.extend_from_slice(&[0x7f; 100]);
+        let h = header(loader, &data);
+        assert_eq!(
+            edge_list(&filter.edge(&h, &data)),
+            vec![(h.pubkey, authority, WEIGHT_AUTHORITY)]
+        );
+        // immutable programdata
+        data[12] = 0;
+        assert_eq!(
+            edge_list(&filter.edge(&h, &data)),
+            vec![(loader, h.pubkey, WEIGHT_IMMUTABLE)]
+        );
+        data[12] = 2;
+        assert!(filter.edge(&h, &data).is_empty());
+        // buffers with and without an authority
+        let data = [&1u32.to_le_bytes()[..], &[1], authority.as_ref(), &[0; 10]].concat();
+        let h = header(loader, &data);
+        assert_eq!(
+            edge_list(&filter.edge(&h, &data)),
+            vec![(h.pubkey, authority, WEIGHT_AUTHORITY)]
+        );
+        let data = [&1u32.to_le_bytes()[..], &[0; 40]].concat();
+        assert!(filter.edge(&h, &data).is_empty());
+        // uninitialized, unknown and truncated accounts
+        assert!(filter.edge(&h, &[0; 4]).is_empty());
+        assert!(filter.edge(&h, &[4, 0, 0, 0]).is_empty());
+        assert!(filter.edge(&h, &[2, 0, 0, 0, 1]).is_empty());
+    }
+
+    #[test]
+    fn test_ata() {
+        let filter = SolToken::default();
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut data = vec![0; Account::LEN];
+        Account {
+            mint,
+            owner,
+            state: AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        for program_id in [token_id, TOKEN_2022_ID] {
+            let ata = associated_token_address(&owner, &mint, &program_id);
+            let mut h = header(program_id, &data);
+            assert_eq!(
+                edge_list(&filter.edge(&h, &data))[1],
+                (owner, h.pubkey, WEIGHT_SPLTOKEN_OWNER)
+            );
+            h.pubkey = ata;
+            assert_eq!(
+                edge_list(&filter.edge(&h, &data))[1],
+                (owner, ata, WEIGHT_SPLTOKEN_OWNER | WEIGHT_ATA)
+            );
+        }
+        // the token program is part of the seeds
+        assert_ne!(
+            associated_token_address(&owner, &mint, &token_id),
+            associated_token_address(&owner, &mint, &TOKEN_2022_ID)
+        );
+        // the ATA bit is indexed for subscriptions
+        let list = weight_list(&(WEIGHT_SPLTOKEN_OWNER | WEIGHT_ATA));
+        assert_eq!(list.len(), 4);
+        assert!(list.contains(&WEIGHT_ATA));
+        assert!(list.contains(&(WEIGHT_SPLTOKEN_OWNER | WEIGHT_ATA)));
+    }
+    #[test]
+    fn test_ata_memo() {
+        let mut memo = AtaMemo::new(2);
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let ata = associated_token_address(&owner, &mint, &token_id);
+        assert!(memo.is_ata(&ata, &owner, &mint, &token_id));
+        assert!(memo.is_ata(&ata, &owner, &mint, &token_id));
+        // a new owner is checked again
+        let other = Pubkey::new_unique();
+        assert!(!memo.is_ata(&ata, &other, &mint, &token_id));
+        assert!(memo.is_ata(&ata, &owner, &mint, &token_id));
+        assert_eq!(memo.len(), 1);
+        // the oldest account is forgotten first
+        for _ in 0..3 {
+            assert!(!memo.is_ata(&Pubkey::new_unique(), &owner, &mint, &token_id));
+        }
+        assert_eq!(memo.len(), 2);
+        assert!(memo.is_ata(&ata, &owner, &mint, &token_id));
+    }
+
+    #[test]
+    fn test_token_2022() {
+        let filter = SolToken::default();
+        assert!(filter.program_id_list().contains(&TOKEN_2022_ID));
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut base = [0u8; Account::LEN];
+        Account {
+            mint,
+            owner,
+            state: AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut base);
+        // a token account with the immutable owner and transfer hook account extensions
+        let data = extended(&base, 2, &[(7, &[]), (15, &[0])]);
+        let h = header(TOKEN_2022_ID, &data);
+        assert_eq!(
+            edge_list(&filter.edge(&h, &data)),
+            vec![
+                (mint, h.pubkey, WEIGHT_SPLTOKEN_MINT),
+                (owner, h.pubkey, WEIGHT_SPLTOKEN_OWNER),
+            ]
+        );
+        // the base layout without extensions gives the same edges
+        let h = header(TOKEN_2022_ID, &base);
+        assert_eq!(filter.edge(&h, &base).len(), 2);
+        // a truncated extension gives none
+        assert!(filter.edge(&h, &data[..data.len() - 1]).is_empty());
+
+        // an extended mint
+        let mut base = [0u8; Mint::LEN];
+        Mint {
+            is_initialized: true,
+            ..Default::default()
         }
-        list
+        .pack_into_slice(&mut base);
+        let data = extended(&base, 1, &[(10, &[3; 52])]);
+        let h = header(TOKEN_2022_ID, &data);
+        assert_eq!(
+            edge_list(&filter.edge(&h, &data)),
+            vec![(TOKEN_2022_ID, h.pubkey, WEIGHT_PROGRAM)]
+        );
+        // extension edges from a mint whose metadata lives on the mint itself
+        let hook = Pubkey::new_unique();
+        let h = header(TOKEN_2022_ID, &[]);
+        let pointer = [Pubkey::new_unique().to_bytes(), h.pubkey.to_bytes()].concat();
+        let hook_value = [Pubkey::default().to_bytes(), hook.to_bytes()].concat();
+        let data = extended(
+            &base,
+            1,
+            &[
+                (token2022::EXTENSION_METADATA_POINTER, &pointer),
+                (token2022::EXTENSION_TRANSFER_HOOK, &hook_value),
+            ],
+        );
+        assert_eq!(
+            edge_list(&filter.edge(&h, &data)),
+            vec![
+                (TOKEN_2022_ID, h.pubkey, WEIGHT_PROGRAM),
+                (hook, h.pubkey, WEIGHT_TRANSFER_HOOK),
+            ]
+        );
+        // an uninitialized mint has no extension edges either
+        let data = extended(
+            &[0; Mint::LEN],
+            1,
+            &[(token2022::EXTENSION_TRANSFER_HOOK, &hook_value)],
+        );
+        assert!(filter.edge(&header(TOKEN_2022_ID, &data), &data).is_empty());
+        let data = extended(&[0; Account::LEN], 0, &[]);
+        assert!(filter.edge(&header(TOKEN_2022_ID, &data), &data).is_empty());
     }
 }
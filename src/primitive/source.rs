@@ -0,0 +1,205 @@
+use std::{collections::VecDeque, marker::PhantomData};
+
+pub use catscope_edge_derive::EdgeSource;
+pub use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    primitive::{
+        common::AccountReader, err::CatscopeWasmError, header::AccountHeader, tree::FilterEdge,
+    },
+    DISCRIMINATOR_SIZE,
+};
+
+/// Fields of a derived account start after the Anchor discriminator.
+pub const FIELD_OFFSET: usize = DISCRIMINATOR_SIZE;
+
+/// An account struct whose parser and edges come from `#[derive(EdgeSource)]`; see the
+/// `catscope-edge-derive` crate for the attributes.
+///
+/// ```
+/// use catscope_edge_generator::primitive::source::{EdgeSource, Pubkey};
+///
+/// #[derive(EdgeSource)]
+/// #[edge(program, weight = PROGRAM)]
+/// struct Account {
+///     #[edge(out, weight = DIRECT, skip_default)]
+///     owner: Pubkey,
+/// }
+/// ```
+///
+/// The attributes are checked when the struct is compiled. An edge has one direction:
+///
+/// ```compile_fail
+/// use catscope_edge_generator::primitive::source::{EdgeSource, Pubkey};
+///
+/// #[derive(EdgeSource)]
+/// #[edge(program, weight = PROGRAM)]
+/// struct Account {
+///     #[edge(in, out, weight = DIRECT, skip_default)]
+///     owner: Pubkey,
+/// }
+/// ```
+///
+/// and the program id is never the default pubkey, so `skip_default` is refused there:
+///
+/// ```compile_fail
+/// use catscope_edge_generator::primitive::source::{EdgeSource, Pubkey};
+///
+/// #[derive(EdgeSource)]
+/// #[edge(program, weight = PROGRAM, skip_default)]
+/// struct Account {
+///     #[edge(out, weight = DIRECT, skip_default)]
+///     owner: Pubkey,
+/// }
+/// ```
+pub trait EdgeSource: Sized {
+    /// Bytes taken by the declared fields, not counting the discriminator.
+    const SIZE: usize;
+
+    /// Read the declared fields; fails if the account is too short to hold all of them.
+    /// The discriminator is not checked.
+    fn parse(data: &[u8]) -> Result<Self, CatscopeWasmError>;
+
+    /// The edges of the account, in field order.
+    fn edge(&self, header: &AccountHeader, program_id: &Pubkey) -> VecDeque<FilterEdge>;
+}
+
+/// A fixed size field of an account, stored little endian.
+pub trait AccountField: Sized {
+    const SIZE: usize;
+
+    fn read(reader: &mut AccountReader) -> Result<Self, CatscopeWasmError>;
+}
+
+impl AccountField for Pubkey {
+    const SIZE: usize = 32;
+
+    fn read(reader: &mut AccountReader) -> Result<Self, CatscopeWasmError> {
+        reader.read_pubkey()
+    }
+}
+
+impl<const N: usize> AccountField for [u8; N] {
+    const SIZE: usize = N;
+
+    fn read(reader: &mut AccountReader) -> Result<Self, CatscopeWasmError> {
+        reader.read_array()
+    }
+}
+
+macro_rules! impl_account_field_int {
+    ($($t:ty),*) => {
+        $(
+            impl AccountField for $t {
+                const SIZE: usize = std::mem::size_of::<$t>();
+
+                fn read(reader: &mut AccountReader) -> Result<Self, CatscopeWasmError> {
+                    Ok(<$t>::from_le_bytes(reader.read_array()?))
+                }
+            }
+        )*
+    };
+}
+
+impl_account_field_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl AccountField for bool {
+    const SIZE: usize = 1;
+
+    fn read(reader: &mut AccountReader) -> Result<Self, CatscopeWasmError> {
+        Ok(reader.read_u8()? != 0)
+    }
+}
+
+/// `N` bytes the filter does not care about, eg the ticks in front of the whirlpool of an Orca
+/// tick array. Nothing is copied.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Skip<const N: usize>(PhantomData<[u8; N]>);
+
+impl<const N: usize> AccountField for Skip<N> {
+    const SIZE: usize = N;
+
+    fn read(reader: &mut AccountReader) -> Result<Self, CatscopeWasmError> {
+        reader.skip(N)?;
+        Ok(Self(PhantomData))
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use super::*; // import functions from the parent module
+    use crate::primitive::{
+        label::{LABEL_NONE, LABEL_SAFEJAR_CONTROLLER_OWNER},
+        tree::{WEIGHT_DIRECT, WEIGHT_PROGRAM, WEIGHT_SYMLINK},
+    };
+
+    #[derive(EdgeSource)]
+    #[edge(program, weight = PROGRAM)]
+    struct Sample {
+        flag: bool,
+        count: u16,
+        #[edge(out, weight = DIRECT, label = SAFEJAR_CONTROLLER_OWNER)]
+        owner: Pubkey,
+        _gap: Skip<5>,
+        #[edge(in, weight = DIRECT | SYMLINK, skip_default)]
+        parent: Pubkey,
+        tail: i64,
+    }
+
+    fn sample(parent: &Pubkey) -> (Pubkey, Vec<u8>) {
+        let owner = Pubkey::new_unique();
+        let mut data = vec![0xaa; FIELD_OFFSET];
+        data.push(1);
+        data.extend_from_slice(&7u16.to_le_bytes());
+        data.extend_from_slice(owner.as_ref());
+        data.extend_from_slice(&[0xff; 5]);
+        data.extend_from_slice(parent.as_ref());
+        data.extend_from_slice(&(-3i64).to_le_bytes());
+        (owner, data)
+    }
+
+    #[test]
+    fn test_edge_source() {
+        assert_eq!(Sample::SIZE, 1 + 2 + 32 + 5 + 32 + 8);
+        let program_id = Pubkey::new_unique();
+        let header = AccountHeader {
+            pubkey: Pubkey::new_unique(),
+            slot: 9,
+            ..Default::default()
+        };
+
+        let parent = Pubkey::new_unique();
+        let (owner, data) = sample(&parent);
+        let account = Sample::parse(&data).unwrap();
+        assert!(account.flag);
+        assert_eq!(account.count, 7);
+        assert_eq!(account.tail, -3);
+        let list: Vec<FilterEdge> = account.edge(&header, &program_id).into_iter().collect();
+        assert_eq!(list.len(), 3);
+        assert_eq!(
+            (list[0].from, list[0].to, list[0].weight, list[0].label),
+            (program_id, header.pubkey, WEIGHT_PROGRAM, LABEL_NONE)
+        );
+        assert_eq!(
+            (list[1].from, list[1].to, list[1].label),
+            (header.pubkey, owner, LABEL_SAFEJAR_CONTROLLER_OWNER)
+        );
+        assert_eq!(
+            (list[2].from, list[2].to, list[2].weight),
+            (parent, header.pubkey, WEIGHT_DIRECT | WEIGHT_SYMLINK)
+        );
+        assert!(list.iter().all(|e| e.slot == 9));
+
+        // no edge to the system program
+        let (_, data) = sample(&Pubkey::default());
+        let account = Sample::parse(&data).unwrap();
+        assert_eq!(account.edge(&header, &program_id).len(), 2);
+
+        // every declared field must be present
+        assert!(matches!(
+            Sample::parse(&data[..data.len() - 1]),
+            Err(CatscopeWasmError::ShortBuffer(_, _))
+        ));
+    }
+}
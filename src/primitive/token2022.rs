@@ -0,0 +1,307 @@
+use solana_sdk::{pubkey, pubkey::Pubkey};
+
+use super::{
+    common::{AccountReader, PUBKEY_LEN},
+    err::CatscopeWasmError,
+    label::{
+        EdgeLabel, LABEL_TOKEN_2022_MINT_CLOSE_AUTHORITY,
+        LABEL_TOKEN_2022_MINT_CONFIDENTIAL_TRANSFER_AUTHORITY, LABEL_TOKEN_2022_MINT_GROUP_ADDRESS,
+        LABEL_TOKEN_2022_MINT_METADATA_ADDRESS, LABEL_TOKEN_2022_MINT_PERMANENT_DELEGATE,
+        LABEL_TOKEN_2022_MINT_TRANSFER_FEE_CONFIG_AUTHORITY,
+        LABEL_TOKEN_2022_MINT_TRANSFER_HOOK_PROGRAM,
+        LABEL_TOKEN_2022_MINT_WITHDRAW_WITHHELD_AUTHORITY,
+    },
+    tree::{
+        Weight, WEIGHT_CLOSE_AUTHORITY, WEIGHT_CONFIDENTIAL_TRANSFER_AUTHORITY,
+        WEIGHT_GROUP_POINTER, WEIGHT_METADATA_POINTER, WEIGHT_PERMANENT_DELEGATE,
+        WEIGHT_TRANSFER_FEE_CONFIG_AUTHORITY, WEIGHT_TRANSFER_HOOK,
+        WEIGHT_WITHDRAW_WITHHELD_AUTHORITY,
+    },
+};
+
+/// The Token-2022 (token extensions) program.
+pub const TOKEN_2022_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Size of the base token account layout, shared with the legacy token program.
+pub const ACCOUNT_SIZE: usize = 165;
+/// Size of the base mint layout, shared with the legacy token program.
+pub const MINT_SIZE: usize = 82;
+/// Size of a multisig; multisigs never carry extensions.
+pub const MULTISIG_SIZE: usize = 355;
+
+// Extended mints are padded to the account size so the account type byte sits at the
+// same offset for both kinds; the TLV entries follow it.
+const ACCOUNT_TYPE_OFFSET: usize = ACCOUNT_SIZE;
+const TLV_OFFSET: usize = ACCOUNT_TYPE_OFFSET + 1;
+const TLV_HEADER_SIZE: usize = 4;
+
+// `ExtensionType` values of the mint extensions that name other accounts.
+pub const EXTENSION_TRANSFER_FEE_CONFIG: u16 = 1;
+pub const EXTENSION_MINT_CLOSE_AUTHORITY: u16 = 3;
+pub const EXTENSION_CONFIDENTIAL_TRANSFER_MINT: u16 = 4;
+pub const EXTENSION_PERMANENT_DELEGATE: u16 = 12;
+pub const EXTENSION_TRANSFER_HOOK: u16 = 14;
+pub const EXTENSION_METADATA_POINTER: u16 = 18;
+pub const EXTENSION_GROUP_POINTER: u16 = 20;
+
+/// Pubkeys read from mint extensions: (extension type, offset in the value, weight, label).
+/// An all zero pubkey means the field is unset.
+const EXTENSION_PUBKEY_LIST: [(u16, usize, Weight, EdgeLabel); 8] = [
+    // transfer_fee_config_authority, withdraw_withheld_authority, ..
+    (
+        EXTENSION_TRANSFER_FEE_CONFIG,
+        0,
+        WEIGHT_TRANSFER_FEE_CONFIG_AUTHORITY,
+        LABEL_TOKEN_2022_MINT_TRANSFER_FEE_CONFIG_AUTHORITY,
+    ),
+    (
+        EXTENSION_TRANSFER_FEE_CONFIG,
+        PUBKEY_LEN,
+        WEIGHT_WITHDRAW_WITHHELD_AUTHORITY,
+        LABEL_TOKEN_2022_MINT_WITHDRAW_WITHHELD_AUTHORITY,
+    ),
+    // close_authority
+    (
+        EXTENSION_MINT_CLOSE_AUTHORITY,
+        0,
+        WEIGHT_CLOSE_AUTHORITY,
+        LABEL_TOKEN_2022_MINT_CLOSE_AUTHORITY,
+    ),
+    // authority, auto_approve_new_accounts, auditor_elgamal_pubkey
+    (
+        EXTENSION_CONFIDENTIAL_TRANSFER_MINT,
+        0,
+        WEIGHT_CONFIDENTIAL_TRANSFER_AUTHORITY,
+        LABEL_TOKEN_2022_MINT_CONFIDENTIAL_TRANSFER_AUTHORITY,
+    ),
+    // delegate
+    (
+        EXTENSION_PERMANENT_DELEGATE,
+        0,
+        WEIGHT_PERMANENT_DELEGATE,
+        LABEL_TOKEN_2022_MINT_PERMANENT_DELEGATE,
+    ),
+    // authority, program_id
+    (
+        EXTENSION_TRANSFER_HOOK,
+        PUBKEY_LEN,
+        WEIGHT_TRANSFER_HOOK,
+        LABEL_TOKEN_2022_MINT_TRANSFER_HOOK_PROGRAM,
+    ),
+    // authority, metadata_address
+    (
+        EXTENSION_METADATA_POINTER,
+        PUBKEY_LEN,
+        WEIGHT_METADATA_POINTER,
+        LABEL_TOKEN_2022_MINT_METADATA_ADDRESS,
+    ),
+    // authority, group_address
+    (
+        EXTENSION_GROUP_POINTER,
+        PUBKEY_LEN,
+        WEIGHT_GROUP_POINTER,
+        LABEL_TOKEN_2022_MINT_GROUP_ADDRESS,
+    ),
+];
+
+/// What a Token-2022 account holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountType {
+    Mint,
+    Account,
+    Multisig,
+}
+
+/// One entry of the TLV extension area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Extension {
+    /// `ExtensionType` as stored on chain
+    pub kind: u16,
+    /// offset of the value within the account data
+    pub offset: usize,
+    pub len: usize,
+}
+
+/// Classify a Token-2022 account from its size and, for extended accounts, the account type byte.
+/// Returns `None` for accounts that are not initialized as a mint, account or multisig.
+pub fn account_type(reader: &AccountReader) -> Result<Option<AccountType>, CatscopeWasmError> {
+    let len = reader.len();
+    if len == MINT_SIZE {
+        return Ok(Some(AccountType::Mint));
+    }
+    if len == ACCOUNT_SIZE {
+        return Ok(Some(AccountType::Account));
+    }
+    if len == MULTISIG_SIZE {
+        return Ok(Some(AccountType::Multisig));
+    }
+    if len <= ACCOUNT_SIZE {
+        return Ok(None);
+    }
+    match reader.u8_at(ACCOUNT_TYPE_OFFSET)? {
+        1 => Ok(Some(AccountType::Mint)),
+        2 => Ok(Some(AccountType::Account)),
+        _ => Ok(None),
+    }
+}
+
+/// Walk the TLV extension area of an extended mint or token account.
+/// A truncated entry is an error; trailing zero bytes (an uninitialized entry) end the walk.
+pub fn extension_list(reader: &AccountReader) -> Result<Vec<Extension>, CatscopeWasmError> {
+    let mut list = Vec::new();
+    let mut i = TLV_OFFSET;
+    while i + TLV_HEADER_SIZE <= reader.len() {
+        let header = reader.bytes_at(i, TLV_HEADER_SIZE)?;
+        let kind = u16::from_le_bytes([header[0], header[1]]);
+        let len = u16::from_le_bytes([header[2], header[3]]) as usize;
+        if kind == 0 {
+            // ExtensionType::Uninitialized
+            break;
+        }
+        let offset = i + TLV_HEADER_SIZE;
+        reader.bytes_at(offset, len)?;
+        list.push(Extension { kind, offset, len });
+        i = offset + len;
+    }
+    Ok(list)
+}
+
+/// The pubkeys that mint extensions point at, each with the weight and label of its extension field.
+/// Unset fields are skipped; a value too short for its layout is an error.
+pub fn extension_pubkey_list(
+    reader: &AccountReader,
+    extension_list: &[Extension],
+) -> Result<Vec<(Pubkey, Weight, EdgeLabel)>, CatscopeWasmError> {
+    let mut list = Vec::new();
+    for ext in extension_list.iter() {
+        let value = AccountReader::new(reader.bytes_at(ext.offset, ext.len)?);
+        for (_, i, weight, label) in EXTENSION_PUBKEY_LIST.iter().filter(|x| x.0 == ext.kind) {
+            let pubkey = value.pubkey_at(*i)?;
+            if pubkey != Pubkey::default() {
+                list.push((pubkey, *weight, *label));
+            }
+        }
+    }
+    Ok(list)
+}
+
+// unit tests
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*; // import functions from the parent module
+    use crate::primitive::tree::weight_list;
+
+    // an extended account: base layout, account type and the TLV entries
+    pub fn extended(base: &[u8], account_type: u8, entry: &[(u16, &[u8])]) -> Vec<u8> {
+        let mut data = base.to_vec();
+        data.resize(ACCOUNT_SIZE, 0);
+        data.push(account_type);
+        for (kind, value) in entry.iter() {
+            data.extend_from_slice(&kind.to_le_bytes());
+            data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+            data.extend_from_slice(value);
+        }
+        data
+    }
+
+    #[test]
+    fn test_account_type() {
+        let check = |data: &[u8]| account_type(&AccountReader::new(data)).unwrap();
+        assert_eq!(check(&[0; MINT_SIZE]), Some(AccountType::Mint));
+        assert_eq!(check(&[0; ACCOUNT_SIZE]), Some(AccountType::Account));
+        assert_eq!(check(&[0; MULTISIG_SIZE]), Some(AccountType::Multisig));
+        assert_eq!(check(&[0; 100]), None);
+        let mint = extended(&[0; MINT_SIZE], 1, &[(18, &[7; 64])]);
+        assert_eq!(check(&mint), Some(AccountType::Mint));
+        let account = extended(&[0; ACCOUNT_SIZE], 2, &[(7, &[])]);
+        assert_eq!(check(&account), Some(AccountType::Account));
+        assert_eq!(check(&extended(&[0; ACCOUNT_SIZE], 0, &[])), None);
+    }
+
+    #[test]
+    fn test_extension_list() {
+        let data = extended(&[0; MINT_SIZE], 1, &[(18, &[7; 64]), (12, &[1; 32])]);
+        let list = extension_list(&AccountReader::new(&data)).unwrap();
+        assert_eq!(
+            list,
+            vec![
+                Extension {
+                    kind: 18,
+                    offset: TLV_OFFSET + 4,
+                    len: 64
+                },
+                Extension {
+                    kind: 12,
+                    offset: TLV_OFFSET + 4 + 64 + 4,
+                    len: 32
+                },
+            ]
+        );
+        // zero padding after the entries is not an error
+        let mut padded = data.clone();
+        padded.extend_from_slice(&[0; 10]);
+        assert_eq!(extension_list(&AccountReader::new(&padded)).unwrap(), list);
+        // a value that runs past the end is
+        assert!(extension_list(&AccountReader::new(&data[..data.len() - 1])).is_err());
+    }
+
+    #[test]
+    fn test_extension_pubkey_list() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let mut fee = [0u8; 108];
+        fee[PUBKEY_LEN..2 * PUBKEY_LEN].copy_from_slice(b.as_ref());
+        let hook = [a.to_bytes(), b.to_bytes()].concat();
+        let data = extended(
+            &[0; MINT_SIZE],
+            1,
+            &[
+                (EXTENSION_TRANSFER_FEE_CONFIG, &fee),
+                // interest bearing config is ignored
+                (10, &[9; 52]),
+                (EXTENSION_TRANSFER_HOOK, &hook),
+                (EXTENSION_PERMANENT_DELEGATE, a.as_ref()),
+            ],
+        );
+        let reader = AccountReader::new(&data);
+        let ext = extension_list(&reader).unwrap();
+        assert_eq!(
+            extension_pubkey_list(&reader, &ext).unwrap(),
+            vec![
+                // the unset fee config authority is skipped
+                (
+                    b,
+                    WEIGHT_WITHDRAW_WITHHELD_AUTHORITY,
+                    LABEL_TOKEN_2022_MINT_WITHDRAW_WITHHELD_AUTHORITY
+                ),
+                (
+                    b,
+                    WEIGHT_TRANSFER_HOOK,
+                    LABEL_TOKEN_2022_MINT_TRANSFER_HOOK_PROGRAM
+                ),
+                (
+                    a,
+                    WEIGHT_PERMANENT_DELEGATE,
+                    LABEL_TOKEN_2022_MINT_PERMANENT_DELEGATE
+                ),
+            ]
+        );
+        // a transfer hook value too short for the program id
+        let data = extended(&[0; MINT_SIZE], 1, &[(EXTENSION_TRANSFER_HOOK, &[1; 40])]);
+        let reader = AccountReader::new(&data);
+        let ext = extension_list(&reader).unwrap();
+        assert!(extension_pubkey_list(&reader, &ext).is_err());
+    }
+
+    #[test]
+    fn test_extension_weight() {
+        // each extension field has its own bit, so a subscription can pick one kind
+        let list: Vec<Weight> = EXTENSION_PUBKEY_LIST.iter().map(|x| x.2).collect();
+        for (i, weight) in list.iter().enumerate() {
+            assert_eq!(weight.count_ones(), 1);
+            assert!(list[i + 1..].iter().all(|x| x != weight));
+            assert!(weight_list(weight).contains(weight));
+            assert_eq!(weight_list(weight).len(), 2);
+        }
+    }
+}
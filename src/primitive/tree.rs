@@ -1,9 +1,9 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Mutex};
 
 use once_cell::sync::Lazy;
 use solana_sdk::{clock::Slot, pubkey::Pubkey};
 
-use super::err::CatscopeWasmError;
+use super::{err::CatscopeWasmError, label::EdgeLabel};
 
 pub type Weight = u32;
 pub const WEIGHT_IS_OUTGOING: Weight = 1 << 0;
@@ -12,9 +12,12 @@ pub const WEIGHT_SLOT: Weight = 1 << 1;
 pub const WEIGHT_CLIENT: Weight = 1 << 2;
 /// upload the destination node to a client.
 pub const WEIGHT_UPLOAD: Weight = 1 << 3;
+/// the edge was emitted before and no longer exists; the host must drop it from the graph.
+/// This sits in the top bit so the account bits keep their positions.
+pub const WEIGHT_REMOVE: Weight = 1 << 31;
 
 pub const WEIGHT_NON_ACCOUNT: Weight =
-    WEIGHT_SLOT | WEIGHT_CLIENT | WEIGHT_UPLOAD | WEIGHT_IS_OUTGOING;
+    WEIGHT_SLOT | WEIGHT_CLIENT | WEIGHT_UPLOAD | WEIGHT_IS_OUTGOING | WEIGHT_REMOVE;
 pub const WEIGHT_ACCOUNT: Weight = !WEIGHT_NON_ACCOUNT;
 
 pub const MAX_WEIGHT_NONACCOUNT_EXPONENT: u8 = 3;
@@ -24,40 +27,163 @@ pub const WEIGHT_SPLTOKEN_OWNER: Weight = 1 << 5;
 pub const WEIGHT_SPLTOKEN_MINT: Weight = 1 << 6;
 pub const WEIGHT_DIRECT: Weight = 1 << 7;
 pub const WEIGHT_SYMLINK: Weight = 1 << 8;
+/// set on the owner edge of a token account that is the associated token account of its owner and mint.
+pub const WEIGHT_ATA: Weight = 1 << 9;
 
-// the memory requirements grow exponentionally (doubles) for every integer increment of this
-// variable.
-pub const MAX_WEIGHT_ACCOUNT_EXPONENT: u8 = 9;
+// Every bit below `WEIGHT_REMOVE` is indexed by `weight_list`. The subsets of a weight are
+// built the first time it is seen and kept, so the memory grows with the number of distinct
+// weights and doubles for every account bit set in one weight, not with this variable.
+pub const MAX_WEIGHT_ACCOUNT_EXPONENT: u8 = 31;
 pub const MAX_WEIGHT: Weight = 1 << MAX_WEIGHT_ACCOUNT_EXPONENT;
 
-/// Index weights for use when updating subscriptions.
-static WEIGHT_HASH_MAP: Lazy<HashMap<Weight, Vec<Weight>>> = Lazy::new(|| {
-    let mut lookup: HashMap<Weight, Vec<Weight>> = HashMap::new();
-    // 0..32
-    for mask in 0..(1 << (MAX_WEIGHT_ACCOUNT_EXPONENT - MAX_WEIGHT_NONACCOUNT_EXPONENT)) {
-        let mut subset = mask;
-        // Generate all subsets (masks) of 'b'
-        loop {
-            lookup
-                .entry(mask)
-                .or_default()
-                .push(subset << MAX_WEIGHT_NONACCOUNT_EXPONENT);
-            if subset == 0 {
-                break;
+// The relationship bits below are indexed like the account bits above. Each edge sets at most
+// two of them, so the subset lists of their weights stay short.
+/// account -> authority that can change it, eg a stake account to its staker or programdata to
+/// its upgrade authority
+pub const WEIGHT_AUTHORITY: Weight = 1 << 10;
+/// account -> authority that can withdraw its lamports
+pub const WEIGHT_WITHDRAWER: Weight = 1 << 11;
+/// stake account -> lockup custodian
+pub const WEIGHT_CUSTODIAN: Weight = 1 << 12;
+/// stake account -> the vote account it delegates to
+pub const WEIGHT_VOTE_ACCOUNT: Weight = 1 << 13;
+/// vote account -> validator identity
+pub const WEIGHT_NODE_IDENTITY: Weight = 1 << 14;
+/// vote account -> authorized voter of the current epoch
+pub const WEIGHT_VOTER: Weight = 1 << 15;
+/// transfer hook program -> Token-2022 mint
+pub const WEIGHT_TRANSFER_HOOK: Weight = 1 << 16;
+/// metadata account named by the metadata pointer -> Token-2022 mint
+pub const WEIGHT_METADATA_POINTER: Weight = 1 << 17;
+/// group account named by the group pointer -> Token-2022 mint
+pub const WEIGHT_GROUP_POINTER: Weight = 1 << 18;
+/// permanent delegate -> Token-2022 mint
+pub const WEIGHT_PERMANENT_DELEGATE: Weight = 1 << 19;
+/// transfer fee config authority -> Token-2022 mint
+pub const WEIGHT_TRANSFER_FEE_CONFIG_AUTHORITY: Weight = 1 << 20;
+/// withdraw withheld fees authority -> Token-2022 mint
+pub const WEIGHT_WITHDRAW_WITHHELD_AUTHORITY: Weight = 1 << 21;
+/// confidential transfer authority -> Token-2022 mint
+pub const WEIGHT_CONFIDENTIAL_TRANSFER_AUTHORITY: Weight = 1 << 22;
+/// mint authority -> mint
+pub const WEIGHT_MINT_AUTHORITY: Weight = 1 << 23;
+/// freeze authority -> mint
+pub const WEIGHT_FREEZE_AUTHORITY: Weight = 1 << 24;
+/// delegate -> token account
+pub const WEIGHT_DELEGATE: Weight = 1 << 25;
+/// close authority -> token account or Token-2022 mint
+pub const WEIGHT_CLOSE_AUTHORITY: Weight = 1 << 26;
+/// signer -> token multisig
+pub const WEIGHT_MULTISIG_SIGNER: Weight = 1 << 27;
+/// upgradeable program -> programdata
+pub const WEIGHT_PROGRAM_DATA: Weight = 1 << 28;
+/// upgradeable loader -> programdata without an upgrade authority
+pub const WEIGHT_IMMUTABLE: Weight = 1 << 29;
+/// system program -> durable nonce account, set along with `WEIGHT_PROGRAM`
+pub const WEIGHT_NONCE: Weight = 1 << 30;
+
+/// Names of the account weight bits, as used in configuration files and tool output.
+pub const WEIGHT_NAME_LIST: [(Weight, &str); 27] = [
+    (WEIGHT_PROGRAM, "PROGRAM"),
+    (WEIGHT_SPLTOKEN_OWNER, "SPLTOKEN_OWNER"),
+    (WEIGHT_SPLTOKEN_MINT, "SPLTOKEN_MINT"),
+    (WEIGHT_DIRECT, "DIRECT"),
+    (WEIGHT_SYMLINK, "SYMLINK"),
+    (WEIGHT_ATA, "ATA"),
+    (WEIGHT_AUTHORITY, "AUTHORITY"),
+    (WEIGHT_WITHDRAWER, "WITHDRAWER"),
+    (WEIGHT_CUSTODIAN, "CUSTODIAN"),
+    (WEIGHT_VOTE_ACCOUNT, "VOTE_ACCOUNT"),
+    (WEIGHT_NODE_IDENTITY, "NODE_IDENTITY"),
+    (WEIGHT_VOTER, "VOTER"),
+    (WEIGHT_TRANSFER_HOOK, "TRANSFER_HOOK"),
+    (WEIGHT_METADATA_POINTER, "METADATA_POINTER"),
+    (WEIGHT_GROUP_POINTER, "GROUP_POINTER"),
+    (WEIGHT_PERMANENT_DELEGATE, "PERMANENT_DELEGATE"),
+    (
+        WEIGHT_TRANSFER_FEE_CONFIG_AUTHORITY,
+        "TRANSFER_FEE_CONFIG_AUTHORITY",
+    ),
+    (
+        WEIGHT_WITHDRAW_WITHHELD_AUTHORITY,
+        "WITHDRAW_WITHHELD_AUTHORITY",
+    ),
+    (
+        WEIGHT_CONFIDENTIAL_TRANSFER_AUTHORITY,
+        "CONFIDENTIAL_TRANSFER_AUTHORITY",
+    ),
+    (WEIGHT_MINT_AUTHORITY, "MINT_AUTHORITY"),
+    (WEIGHT_FREEZE_AUTHORITY, "FREEZE_AUTHORITY"),
+    (WEIGHT_DELEGATE, "DELEGATE"),
+    (WEIGHT_CLOSE_AUTHORITY, "CLOSE_AUTHORITY"),
+    (WEIGHT_MULTISIG_SIGNER, "MULTISIG_SIGNER"),
+    (WEIGHT_PROGRAM_DATA, "PROGRAM_DATA"),
+    (WEIGHT_IMMUTABLE, "IMMUTABLE"),
+    (WEIGHT_NONCE, "NONCE"),
+];
+
+/// Names of the account weight bits set in `weight`, in the order of [`WEIGHT_NAME_LIST`].
+pub fn weight_names(weight: Weight) -> Vec<&'static str> {
+    WEIGHT_NAME_LIST
+        .iter()
+        .filter(|(w, _)| weight & w != 0)
+        .map(|(_, n)| *n)
+        .collect()
+}
+
+/// Parse an account weight written as `|` separated names (`DIRECT|SYMLINK`) or as an integer.
+pub fn parse_weight(input: &str) -> Result<Weight, CatscopeWasmError> {
+    let input = input.trim();
+    let weight = if let Ok(x) = input.parse::<Weight>() {
+        x
+    } else {
+        let mut weight = 0;
+        for name in input.split('|') {
+            let name = name.trim();
+            match WEIGHT_NAME_LIST
+                .iter()
+                .find(|(_, n)| n.eq_ignore_ascii_case(name))
+            {
+                Some((w, _)) => weight |= *w,
+                None => {
+                    return Err(CatscopeWasmError::InvalidInput(format!(
+                        "unknown weight `{}`",
+                        name
+                    )))
+                }
             }
-            subset = (subset - 1) & mask; // Generate next subset
         }
+        weight
+    };
+    let named = WEIGHT_NAME_LIST.iter().fold(0, |acc, (w, _)| acc | w);
+    if weight == 0 || weight & !named != 0 {
+        return Err(CatscopeWasmError::InvalidInput(format!(
+            "weight {} is not an account weight",
+            input
+        )));
     }
-    lookup
-});
+    Ok(weight)
+}
+
+/// Index weights for use when updating subscriptions.
+static WEIGHT_HASH_MAP: Lazy<Mutex<HashMap<Weight, &'static [Weight]>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The maximum number of program ids that can be tracked by one filter.
+pub const MAX_PROGRAM_COUNT: usize = 32;
 
 #[repr(C, align(8))]
 #[derive(Debug, Clone, Default)]
 pub struct ProgramList {
     pub count: u16,
-    pub list: [Pubkey; 32], // have a max length
+    pub list: [Pubkey; MAX_PROGRAM_COUNT], // have a max length
 }
 
+/// The layout of the edges returned by the `edge` export, written to the edge node next to the
+/// edge. Version 0 guests predate labels: they never write this byte and the label bytes of
+/// their edges are padding. Version 1 sets [`FilterEdge::label`].
+pub const EDGE_LAYOUT_VERSION: u8 = 1;
+
 /// The edge goes in the graph determined by the `from` `program_id`.
 /// `from` is the account_id.
 #[repr(C, align(8))]
@@ -67,6 +193,9 @@ pub struct FilterEdge {
     pub to: Pubkey,
     pub from: Pubkey,
     pub weight: Weight, // weight of zero is not allowed
+    /// the account field that produced the edge; this sits in what used to be padding, so
+    /// the size and the other offsets are the same as before labels were added
+    pub label: EdgeLabel,
 }
 impl FilterEdge {
     pub fn from_raw_parts<'a, 'b: 'a>(data: &'b [u8]) -> Result<&'a Self, CatscopeWasmError> {
@@ -88,6 +217,11 @@ pub fn edge_is_outgoing(weight: &Weight) -> bool {
     0 < *weight & WEIGHT_IS_OUTGOING
 }
 
+#[inline(always)]
+pub fn edge_is_removal(weight: &Weight) -> bool {
+    0 < *weight & WEIGHT_REMOVE
+}
+
 #[inline]
 fn zero_out_bits_above_n(value: u32, n: u32) -> u32 {
     // Create a mask with 1s for bits <= n and 0s for bits > n.
@@ -105,15 +239,25 @@ fn zero_out_bits_above_n(value: u32, n: u32) -> u32 {
 pub fn weight_list(weight: &Weight) -> &'static [Weight] {
     let reduced = zero_out_bits_above_n(*weight, MAX_WEIGHT_ACCOUNT_EXPONENT as u32 - 1);
     let k = reduced >> MAX_WEIGHT_NONACCOUNT_EXPONENT;
-    let result = match WEIGHT_HASH_MAP.get(&k) {
-        Some(x) => x,
-        None => panic!(
-            "failed to get k {}; reduced {}; weight {};",
-            k, reduced, weight
-        ),
+    let mut lookup = match WEIGHT_HASH_MAP.lock() {
+        Ok(x) => x,
+        Err(e) => e.into_inner(),
     };
-    result
+    lookup.entry(k).or_insert_with(|| {
+        let mut list = Vec::with_capacity(1 << k.count_ones());
+        let mut subset = k;
+        // Generate all subsets (masks) of 'k'
+        loop {
+            list.push(subset << MAX_WEIGHT_NONACCOUNT_EXPONENT);
+            if subset == 0 {
+                break;
+            }
+            subset = (subset - 1) & k; // Generate next subset
+        }
+        Box::leak(list.into_boxed_slice())
+    })
 }
+
 pub fn parse_program_list(input: &[u8]) -> Result<Vec<Pubkey>, CatscopeWasmError> {
     let input_str = match std::str::from_utf8(input) {
         Ok(x) => x,
@@ -121,8 +265,8 @@ pub fn parse_program_list(input: &[u8]) -> Result<Vec<Pubkey>, CatscopeWasmError
     };
     let pre_list: Vec<&str> = input_str.split(',').collect();
     let mut list = Vec::with_capacity(pre_list.len());
-    for i in 0..pre_list.len() {
-        let y = pre_list[i].trim();
+    for pre in pre_list.iter() {
+        let y = pre.trim();
         let x: Pubkey = match y.try_into() {
             Ok(z) => z,
             Err(e) => return Err(CatscopeWasmError::Unknown(e.to_string())),
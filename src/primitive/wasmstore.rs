@@ -226,6 +226,8 @@ impl TryFrom<GuestBlob> for AccountOnGuest {
 #[derive(Debug, Clone, Default)]
 pub struct FilterEdgeWithNextPointer {
     pub is_empty: bool, // return true if there are no edges
+    /// `EDGE_LAYOUT_VERSION`; this sits in what used to be padding after `is_empty`
+    pub version: u8,
     pub edge: FilterEdge,
     pub next_pointer: u64,
 }
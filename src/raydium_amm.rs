@@ -0,0 +1,174 @@
+use std::collections::VecDeque;
+
+use solana_sdk::{pubkey::Pubkey, system_program::ID as system_id};
+
+#[cfg(target_os = "wasi")]
+use crate::primitive::wasmimport::HostImport;
+use crate::primitive::{
+    common::AccountReader,
+    err::CatscopeWasmError,
+    guest::{edge_or_log, GuestFilter},
+    header::AccountHeader,
+    label::{
+        LABEL_RAYDIUM_AMM_AMM_INFO_COIN_VAULT, LABEL_RAYDIUM_AMM_AMM_INFO_LP_MINT,
+        LABEL_RAYDIUM_AMM_AMM_INFO_MARKET, LABEL_RAYDIUM_AMM_AMM_INFO_OPEN_ORDERS,
+        LABEL_RAYDIUM_AMM_AMM_INFO_PC_VAULT,
+    },
+    node::{NodeType, NODE_TYPE_RAYDIUM_AMM_AMM_INFO, NODE_TYPE_UNKNOWN},
+    tree::{FilterEdge, WEIGHT_DIRECT},
+};
+
+/// The legacy AMM v4 program is not an Anchor program, so `AmmInfo` accounts
+/// carry no discriminator and are recognized by their size.
+pub const AMM_INFO_SIZE: usize = 752;
+
+// offsets into AmmInfo; 16 u64 parameters, Fees (8 x u64) and StateData (144 bytes) come first.
+const OFFSET_COIN_VAULT: usize = 336;
+const OFFSET_PC_VAULT: usize = 368;
+const OFFSET_LP_MINT: usize = 464;
+const OFFSET_OPEN_ORDERS: usize = 496;
+const OFFSET_MARKET: usize = 528;
+
+pub struct RaydiumAmm {
+    pub program_id: Pubkey,
+}
+impl GuestFilter for RaydiumAmm {
+    fn program_id_list(&self) -> Vec<Pubkey> {
+        vec![self.program_id]
+    }
+
+    fn edge(&self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge> {
+        edge_or_log("raydium_amm", header, self.try_edge(header, data))
+    }
+
+    // the same size check as `try_edge`
+    fn node_type(&self, _header: &AccountHeader, data: &[u8]) -> NodeType {
+        if data.len() == AMM_INFO_SIZE {
+            NODE_TYPE_RAYDIUM_AMM_AMM_INFO
+        } else {
+            NODE_TYPE_UNKNOWN
+        }
+    }
+}
+
+impl RaydiumAmm {
+    fn try_edge(
+        &self,
+        header: &AccountHeader,
+        data: &[u8],
+    ) -> Result<VecDeque<FilterEdge>, CatscopeWasmError> {
+        let mut list = VecDeque::new();
+        let id = header.pubkey;
+        let reader = AccountReader::new(data);
+        #[cfg(target_os = "wasi")]
+        HostImport::log(format!(
+            "raydium_amm_edge - 1 - pubkey {}; data len {}",
+            id,
+            data.len()
+        ));
+        if data.len() != AMM_INFO_SIZE {
+            return Ok(list);
+        }
+        #[cfg(target_os = "wasi")]
+        HostImport::log(format!("raydium_amm_edge - 2 - amm info - pubkey {};", id));
+        // coin vault, pc vault, lp mint, open orders, market
+        for (i, label) in [
+            (OFFSET_COIN_VAULT, LABEL_RAYDIUM_AMM_AMM_INFO_COIN_VAULT),
+            (OFFSET_PC_VAULT, LABEL_RAYDIUM_AMM_AMM_INFO_PC_VAULT),
+            (OFFSET_LP_MINT, LABEL_RAYDIUM_AMM_AMM_INFO_LP_MINT),
+            (OFFSET_OPEN_ORDERS, LABEL_RAYDIUM_AMM_AMM_INFO_OPEN_ORDERS),
+            (OFFSET_MARKET, LABEL_RAYDIUM_AMM_AMM_INFO_MARKET),
+        ] {
+            let pubkey = reader.pubkey_at(i)?;
+            if pubkey != system_id {
+                list.push_back(FilterEdge {
+                    slot: header.slot,
+                    weight: WEIGHT_DIRECT,
+                    from: id,
+                    to: pubkey,
+                    label,
+                });
+            }
+        }
+        #[cfg(target_os = "wasi")]
+        HostImport::log(format!("raydium_amm_edge - 4 - pubkey {};", id));
+        Ok(list)
+    }
+
+    pub fn new(program_id: &Pubkey) -> Self {
+        Self {
+            program_id: *program_id,
+        }
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::primitive::tree::Weight;
+
+    use super::*; // import functions from the parent module
+    use std::sync::Once;
+
+    static INIT_LOGGER: Once = Once::new();
+
+    fn init_logger() {
+        INIT_LOGGER.call_once(|| {
+            let _ = env_logger::try_init();
+        });
+    }
+
+    use log::info;
+    #[test]
+    fn test_amm_info() {
+        init_logger();
+        let program_id = Pubkey::try_from("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8").unwrap();
+        let data = std::fs::read("tests/data/raydium-ammInfo.bin").unwrap();
+        assert_eq!(data.len(), AMM_INFO_SIZE);
+        info!("data length {}", data.len());
+        let header = AccountHeader {
+            pubkey: Pubkey::new_unique(),
+            lamports: 6124800,
+            data_size: data.len() as u32,
+            node_id: 100432,
+            owner: program_id,
+            rent_epoch: 1,
+            slot: 1,
+            executable: false,
+        };
+        let filter = RaydiumAmm::new(&program_id);
+        let mut list = filter.edge(&header, &data);
+        assert_eq!(list.len(), 5, "amm info must have 5 edges");
+        let mut m_edge: HashMap<Pubkey, Weight> = HashMap::default();
+        while let Some(edge) = list.pop_front() {
+            assert_eq!(edge.from, header.pubkey, "all amm info edges are outgoing");
+            m_edge.insert(edge.to, edge.weight);
+        }
+        info!("m_edge {:?}", m_edge);
+        let coin_vault = Pubkey::try_from("DVWRhoXKCoRbvC5QUeTECRNyUSU1gwUM48dBMDSZ88U").unwrap();
+        assert_eq!(*m_edge.get(&coin_vault).unwrap(), WEIGHT_DIRECT);
+        let pc_vault = Pubkey::try_from("HftKFJJcUTu6xYcS75cDkm3y8HEkGgutcbGsdREDWdMr").unwrap();
+        assert_eq!(*m_edge.get(&pc_vault).unwrap(), WEIGHT_DIRECT);
+        let lp_mint = Pubkey::try_from("3529SBnMCDW3S3xQ52aABbRHo7PcHvpQA4no8J12L5eK").unwrap();
+        assert_eq!(*m_edge.get(&lp_mint).unwrap(), WEIGHT_DIRECT);
+        let open_orders = Pubkey::try_from("EiUs4KYLKJ2h9mUUKbRkrMbP8HP2NRrPNnjHG6Cjui99").unwrap();
+        assert_eq!(*m_edge.get(&open_orders).unwrap(), WEIGHT_DIRECT);
+        let market = Pubkey::try_from("BbJgE7HZMaDp5NTYvRh5jZSkQPVDTU8ubPFtpogUkEj4").unwrap();
+        assert_eq!(*m_edge.get(&market).unwrap(), WEIGHT_DIRECT);
+    }
+    #[test]
+    fn test_amm_info_wrong_size() {
+        let program_id = Pubkey::try_from("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8").unwrap();
+        let data = std::fs::read("tests/data/raydium-ammInfo.bin").unwrap();
+        let header = AccountHeader {
+            pubkey: Pubkey::new_unique(),
+            owner: program_id,
+            slot: 1,
+            ..Default::default()
+        };
+        let filter = RaydiumAmm::new(&program_id);
+        assert!(filter.edge(&header, &data[..600]).is_empty());
+    }
+}
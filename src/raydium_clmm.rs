@@ -4,22 +4,54 @@ use solana_sdk::{pubkey::Pubkey, system_program};
 
 #[cfg(target_os = "wasi")]
 use crate::primitive::wasmimport::HostImport;
-use crate::primitive::{
-    common::match_discriminator,
-    guest::GuestFilter,
-    header::AccountHeader,
-    tree::{FilterEdge, WEIGHT_DIRECT, WEIGHT_SYMLINK},
+use crate::{
+    primitive::{
+        common::{anchor_account_discriminator, AccountReader},
+        dispatch::DispatchTable,
+        err::CatscopeWasmError,
+        guest::{edge_or_log, GuestFilter},
+        header::AccountHeader,
+        label::{
+            LABEL_RAYDIUM_CLMM_AMM_CONFIG_FUND_OWNER, LABEL_RAYDIUM_CLMM_AMM_CONFIG_PROTOCOL_OWNER,
+            LABEL_RAYDIUM_CLMM_OBSERVATION_POOL,
+            LABEL_RAYDIUM_CLMM_OPERATION_STATE_OPERATION_OWNER,
+            LABEL_RAYDIUM_CLMM_OPERATION_STATE_WHITELIST_MINT,
+            LABEL_RAYDIUM_CLMM_PERSONAL_POSITION_NFT_MINT,
+            LABEL_RAYDIUM_CLMM_PERSONAL_POSITION_POOL, LABEL_RAYDIUM_CLMM_POOL_STATE_AMM_CONFIG,
+            LABEL_RAYDIUM_CLMM_POOL_STATE_OBSERVATION, LABEL_RAYDIUM_CLMM_POOL_STATE_OWNER,
+            LABEL_RAYDIUM_CLMM_POOL_STATE_TOKEN_VAULT_0,
+            LABEL_RAYDIUM_CLMM_POOL_STATE_TOKEN_VAULT_1, LABEL_RAYDIUM_CLMM_PROTOCOL_POSITION_POOL,
+            LABEL_RAYDIUM_CLMM_TICK_ARRAY_BITMAP_EXTENSION_POOL,
+            LABEL_RAYDIUM_CLMM_TICK_ARRAY_POOL,
+        },
+        node::{
+            NodeType, NODE_TYPE_RAYDIUM_CLMM_AMM_CONFIG, NODE_TYPE_RAYDIUM_CLMM_OBSERVATION,
+            NODE_TYPE_RAYDIUM_CLMM_OPERATION_STATE, NODE_TYPE_RAYDIUM_CLMM_PERSONAL_POSITION,
+            NODE_TYPE_RAYDIUM_CLMM_POOL_STATE, NODE_TYPE_RAYDIUM_CLMM_PROTOCOL_POSITION,
+            NODE_TYPE_RAYDIUM_CLMM_SUPPORT_MINT_ASSOCIATED, NODE_TYPE_RAYDIUM_CLMM_TICK_ARRAY,
+            NODE_TYPE_RAYDIUM_CLMM_TICK_ARRAY_BITMAP_EXTENSION, NODE_TYPE_UNKNOWN,
+        },
+        tree::{FilterEdge, WEIGHT_DIRECT, WEIGHT_SYMLINK},
+    },
+    DISCRIMINATOR_SIZE,
 };
+
+/// The accounts this filter parses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AccountKind {
+    AmmConfig,
+    ProtocolPositionState,
+    SupportMintAssociated,
+    TickArrayState,
+    PersonalPositionState,
+    ObservationState,
+    TickArrayBitmapExtension,
+    PoolState,
+    OperationState,
+}
+
 pub struct Raydium {
-    d_amm_config: [u8; 8],
-    d_observation_state: [u8; 8],
-    d_operation_state: [u8; 8],
-    d_personal_position_state: [u8; 8],
-    d_pool_state: [u8; 8],
-    d_protocol_position_state: [u8; 8],
-    d_support_mint_associated: [u8; 8],
-    d_tick_array_bitmap_extension: [u8; 8],
-    d_tick_array_state: [u8; 8],
+    dispatch: DispatchTable<AccountKind>,
     pub program_id: Pubkey,
 }
 impl GuestFilter for Raydium {
@@ -28,12 +60,42 @@ impl GuestFilter for Raydium {
     }
 
     fn edge(&self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge> {
+        edge_or_log("raydium", header, self.try_edge(header, data))
+    }
+
+    fn node_type(&self, _header: &AccountHeader, data: &[u8]) -> NodeType {
+        match self.dispatch.get(data) {
+            Some(AccountKind::AmmConfig) => NODE_TYPE_RAYDIUM_CLMM_AMM_CONFIG,
+            Some(AccountKind::ProtocolPositionState) => NODE_TYPE_RAYDIUM_CLMM_PROTOCOL_POSITION,
+            Some(AccountKind::SupportMintAssociated) => {
+                NODE_TYPE_RAYDIUM_CLMM_SUPPORT_MINT_ASSOCIATED
+            }
+            Some(AccountKind::TickArrayState) => NODE_TYPE_RAYDIUM_CLMM_TICK_ARRAY,
+            Some(AccountKind::PersonalPositionState) => NODE_TYPE_RAYDIUM_CLMM_PERSONAL_POSITION,
+            Some(AccountKind::ObservationState) => NODE_TYPE_RAYDIUM_CLMM_OBSERVATION,
+            Some(AccountKind::TickArrayBitmapExtension) => {
+                NODE_TYPE_RAYDIUM_CLMM_TICK_ARRAY_BITMAP_EXTENSION
+            }
+            Some(AccountKind::PoolState) => NODE_TYPE_RAYDIUM_CLMM_POOL_STATE,
+            Some(AccountKind::OperationState) => NODE_TYPE_RAYDIUM_CLMM_OPERATION_STATE,
+            None => NODE_TYPE_UNKNOWN,
+        }
+    }
+}
+
+impl Raydium {
+    fn try_edge(
+        &self,
+        header: &AccountHeader,
+        data: &[u8],
+    ) -> Result<VecDeque<FilterEdge>, CatscopeWasmError> {
         let mut list = VecDeque::new();
         let id = header.pubkey;
         // all discriminators are the same length
-        let prefix = self.d_amm_config.len();
+        let prefix = DISCRIMINATOR_SIZE;
         let mut i = prefix;
         let pubkey_len = std::mem::size_of::<Pubkey>();
+        let reader = AccountReader::new(data);
         #[cfg(target_os = "wasi")]
         HostImport::log(format!(
             "raydium_edge - 1 - pubkey {}; data len {}",
@@ -41,242 +103,279 @@ impl GuestFilter for Raydium {
             data.len()
         ));
 
-        if match_discriminator(&self.d_amm_config, data) {
-            // protocol owner
-            {
-                i += 3;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                list.push_back(FilterEdge {
-                    slot: header.slot,
-                    weight: WEIGHT_SYMLINK,
-                    from: pubkey,
-                    to: id,
-                });
-            }
-            // fund owner
-            {
-                i += pubkey_len + 18;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                list.push_back(FilterEdge {
-                    slot: header.slot,
-                    weight: WEIGHT_DIRECT,
-                    from: id,
-                    to: pubkey,
-                });
-            }
-        } else if match_discriminator(&self.d_protocol_position_state, data) {
-            // pool
-            {
-                i += 1;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                list.push_back(FilterEdge {
-                    slot: header.slot,
-                    weight: WEIGHT_DIRECT,
-                    from: pubkey,
-                    to: id,
-                });
-            }
-        } else if match_discriminator(&self.d_support_mint_associated, data) {
-            // skipping mint edges
-        } else if match_discriminator(&self.d_tick_array_state, data) {
-            // pool
-            {
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                list.push_back(FilterEdge {
-                    slot: header.slot,
-                    weight: WEIGHT_DIRECT,
-                    from: pubkey,
-                    to: id,
-                });
-            }
-        } else if match_discriminator(&self.d_personal_position_state, data) {
-            // nft mint
-            {
-                i += 1;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                list.push_back(FilterEdge {
-                    slot: header.slot,
-                    weight: WEIGHT_SYMLINK,
-                    from: pubkey,
-                    to: id,
-                });
-            }
-            // pool
-            {
-                i += pubkey_len;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                list.push_back(FilterEdge {
-                    slot: header.slot,
-                    weight: WEIGHT_DIRECT,
-                    from: pubkey,
-                    to: id,
-                });
-            }
-        } else if match_discriminator(&self.d_observation_state, data) {
-            // pool
-            {
-                i += 11;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                list.push_back(FilterEdge {
-                    slot: header.slot,
-                    weight: WEIGHT_DIRECT,
-                    from: pubkey,
-                    to: id,
-                });
+        match self.dispatch.get(data) {
+            Some(AccountKind::AmmConfig) => {
+                // protocol owner
+                {
+                    i += 3;
+                    let pubkey = reader.pubkey_at(i)?;
+                    list.push_back(FilterEdge {
+                        slot: header.slot,
+                        weight: WEIGHT_SYMLINK,
+                        from: pubkey,
+                        to: id,
+                        label: LABEL_RAYDIUM_CLMM_AMM_CONFIG_PROTOCOL_OWNER,
+                    });
+                }
+                // fund owner
+                {
+                    i += pubkey_len + 18;
+                    let pubkey = reader.pubkey_at(i)?;
+                    list.push_back(FilterEdge {
+                        slot: header.slot,
+                        weight: WEIGHT_DIRECT,
+                        from: id,
+                        to: pubkey,
+                        label: LABEL_RAYDIUM_CLMM_AMM_CONFIG_FUND_OWNER,
+                    });
+                }
             }
-        } else if match_discriminator(&self.d_tick_array_bitmap_extension, data) {
-            // pool id
-            {
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                list.push_back(FilterEdge {
-                    slot: header.slot,
-                    weight: WEIGHT_DIRECT,
-                    from: pubkey,
-                    to: id,
-                });
+            Some(AccountKind::ProtocolPositionState) => {
+                // pool
+                {
+                    i += 1;
+                    let pubkey = reader.pubkey_at(i)?;
+                    list.push_back(FilterEdge {
+                        slot: header.slot,
+                        weight: WEIGHT_DIRECT,
+                        from: pubkey,
+                        to: id,
+                        label: LABEL_RAYDIUM_CLMM_PROTOCOL_POSITION_POOL,
+                    });
+                }
             }
-        } else if match_discriminator(&self.d_pool_state, data) {
-            // amm config
-            {
-                i += 1;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                list.push_back(FilterEdge {
-                    slot: header.slot,
-                    weight: WEIGHT_DIRECT,
-                    from: pubkey,
-                    to: id,
-                });
+            Some(AccountKind::SupportMintAssociated) => {
+                // skipping mint edges
             }
-            // owner
-            {
-                i += pubkey_len;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                list.push_back(FilterEdge {
-                    slot: header.slot,
-                    weight: WEIGHT_DIRECT,
-                    from: id,
-                    to: pubkey,
-                });
+            Some(AccountKind::TickArrayState) => {
+                // pool
+                {
+                    let pubkey = reader.pubkey_at(i)?;
+                    list.push_back(FilterEdge {
+                        slot: header.slot,
+                        weight: WEIGHT_DIRECT,
+                        from: pubkey,
+                        to: id,
+                        label: LABEL_RAYDIUM_CLMM_TICK_ARRAY_POOL,
+                    });
+                }
             }
-            // token vault 0
-            {
-                i += pubkey_len + 2 * pubkey_len;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                list.push_back(FilterEdge {
-                    slot: header.slot,
-                    weight: WEIGHT_DIRECT,
-                    from: id,
-                    to: pubkey,
-                });
+            Some(AccountKind::PersonalPositionState) => {
+                // nft mint
+                {
+                    i += 1;
+                    let pubkey = reader.pubkey_at(i)?;
+                    list.push_back(FilterEdge {
+                        slot: header.slot,
+                        weight: WEIGHT_SYMLINK,
+                        from: pubkey,
+                        to: id,
+                        label: LABEL_RAYDIUM_CLMM_PERSONAL_POSITION_NFT_MINT,
+                    });
+                }
+                // pool
+                {
+                    i += pubkey_len;
+                    let pubkey = reader.pubkey_at(i)?;
+                    list.push_back(FilterEdge {
+                        slot: header.slot,
+                        weight: WEIGHT_DIRECT,
+                        from: pubkey,
+                        to: id,
+                        label: LABEL_RAYDIUM_CLMM_PERSONAL_POSITION_POOL,
+                    });
+                }
             }
-            // token vault 1
-            {
-                i += pubkey_len;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                list.push_back(FilterEdge {
-                    slot: header.slot,
-                    weight: WEIGHT_DIRECT,
-                    from: id,
-                    to: pubkey,
-                });
+            Some(AccountKind::ObservationState) => {
+                // pool
+                {
+                    i += 11;
+                    let pubkey = reader.pubkey_at(i)?;
+                    list.push_back(FilterEdge {
+                        slot: header.slot,
+                        weight: WEIGHT_DIRECT,
+                        from: pubkey,
+                        to: id,
+                        label: LABEL_RAYDIUM_CLMM_OBSERVATION_POOL,
+                    });
+                }
             }
-            // observation account
-            {
-                i += pubkey_len;
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                list.push_back(FilterEdge {
-                    slot: header.slot,
-                    weight: WEIGHT_DIRECT,
-                    from: id,
-                    to: pubkey,
-                });
+            Some(AccountKind::TickArrayBitmapExtension) => {
+                // pool id
+                {
+                    let pubkey = reader.pubkey_at(i)?;
+                    list.push_back(FilterEdge {
+                        slot: header.slot,
+                        weight: WEIGHT_DIRECT,
+                        from: pubkey,
+                        to: id,
+                        label: LABEL_RAYDIUM_CLMM_TICK_ARRAY_BITMAP_EXTENSION_POOL,
+                    });
+                }
             }
-        } else if match_discriminator(&self.d_operation_state, data) {
-            i += 1;
-            // operation owners
-            for _k in 0..10 {
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_program::ID {
+            Some(AccountKind::PoolState) => {
+                // amm config
+                {
+                    i += 1;
+                    let pubkey = reader.pubkey_at(i)?;
+                    list.push_back(FilterEdge {
+                        slot: header.slot,
+                        weight: WEIGHT_DIRECT,
+                        from: pubkey,
+                        to: id,
+                        label: LABEL_RAYDIUM_CLMM_POOL_STATE_AMM_CONFIG,
+                    });
+                }
+                // owner
+                {
+                    i += pubkey_len;
+                    let pubkey = reader.pubkey_at(i)?;
                     list.push_back(FilterEdge {
                         slot: header.slot,
                         weight: WEIGHT_DIRECT,
                         from: id,
                         to: pubkey,
+                        label: LABEL_RAYDIUM_CLMM_POOL_STATE_OWNER,
                     });
                 }
-                i += pubkey_len;
-            }
-            // whitelist mints
-            for _k in 0..100 {
-                let pubkey = Pubkey::try_from(&data[i..(i + pubkey_len)]).unwrap();
-                if pubkey != system_program::ID {
+                // token vault 0
+                {
+                    i += pubkey_len + 2 * pubkey_len;
+                    let pubkey = reader.pubkey_at(i)?;
                     list.push_back(FilterEdge {
                         slot: header.slot,
                         weight: WEIGHT_DIRECT,
                         from: id,
                         to: pubkey,
+                        label: LABEL_RAYDIUM_CLMM_POOL_STATE_TOKEN_VAULT_0,
                     });
                 }
-                i += pubkey_len;
+                // token vault 1
+                {
+                    i += pubkey_len;
+                    let pubkey = reader.pubkey_at(i)?;
+                    list.push_back(FilterEdge {
+                        slot: header.slot,
+                        weight: WEIGHT_DIRECT,
+                        from: id,
+                        to: pubkey,
+                        label: LABEL_RAYDIUM_CLMM_POOL_STATE_TOKEN_VAULT_1,
+                    });
+                }
+                // observation account
+                {
+                    i += pubkey_len;
+                    let pubkey = reader.pubkey_at(i)?;
+                    list.push_back(FilterEdge {
+                        slot: header.slot,
+                        weight: WEIGHT_DIRECT,
+                        from: id,
+                        to: pubkey,
+                        label: LABEL_RAYDIUM_CLMM_POOL_STATE_OBSERVATION,
+                    });
+                }
+            }
+            Some(AccountKind::OperationState) => {
+                i += 1;
+                // operation owners
+                for _k in 0..10 {
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_program::ID {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_DIRECT,
+                            from: id,
+                            to: pubkey,
+                            label: LABEL_RAYDIUM_CLMM_OPERATION_STATE_OPERATION_OWNER,
+                        });
+                    }
+                    i += pubkey_len;
+                }
+                // whitelist mints
+                for _k in 0..100 {
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_program::ID {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_DIRECT,
+                            from: id,
+                            to: pubkey,
+                            label: LABEL_RAYDIUM_CLMM_OPERATION_STATE_WHITELIST_MINT,
+                        });
+                    }
+                    i += pubkey_len;
+                }
             }
+            None => {}
         }
 
         #[cfg(target_os = "wasi")]
         HostImport::log(format!("raydium_edge - 4 - pubkey {};", id));
-        list
+        Ok(list)
     }
-}
 
-impl Raydium {
     pub fn new(program_id: &Pubkey) -> Self {
-        let d_amm_config = discriminator_amm_config();
-        let d_observation_state = discriminator_observation_state();
-        let d_operation_state = discriminator_operation_state();
-        let d_personal_position_state = discriminator_personal_position_state();
-        let d_pool_state = discriminator_pool_state();
-        let d_protocol_position_state = discriminator_protocol_position_state();
-        let d_support_mint_associated = discriminator_support_mint_associated();
-        let d_tick_array_bitmap_extension = discriminator_tick_array_bitmap_extension();
-        let d_tick_array_state = discriminator_tick_array_state();
         Self {
             program_id: *program_id,
-            d_amm_config,
-            d_observation_state,
-            d_operation_state,
-            d_personal_position_state,
-            d_pool_state,
-            d_protocol_position_state,
-            d_support_mint_associated,
-            d_tick_array_bitmap_extension,
-            d_tick_array_state,
+            dispatch: [
+                (discriminator_amm_config(), AccountKind::AmmConfig),
+                (
+                    discriminator_protocol_position_state(),
+                    AccountKind::ProtocolPositionState,
+                ),
+                (
+                    discriminator_support_mint_associated(),
+                    AccountKind::SupportMintAssociated,
+                ),
+                (
+                    discriminator_tick_array_state(),
+                    AccountKind::TickArrayState,
+                ),
+                (
+                    discriminator_personal_position_state(),
+                    AccountKind::PersonalPositionState,
+                ),
+                (
+                    discriminator_observation_state(),
+                    AccountKind::ObservationState,
+                ),
+                (
+                    discriminator_tick_array_bitmap_extension(),
+                    AccountKind::TickArrayBitmapExtension,
+                ),
+                (discriminator_pool_state(), AccountKind::PoolState),
+                (discriminator_operation_state(), AccountKind::OperationState),
+            ]
+            .into_iter()
+            .collect(),
         }
     }
 }
 
 pub fn discriminator_amm_config() -> [u8; 8] {
-    [218, 244, 33, 104, 203, 203, 43, 111]
+    anchor_account_discriminator("AmmConfig")
 }
 pub fn discriminator_observation_state() -> [u8; 8] {
-    [122, 174, 197, 53, 129, 9, 165, 132]
+    anchor_account_discriminator("ObservationState")
 }
 pub fn discriminator_operation_state() -> [u8; 8] {
-    [19, 236, 58, 237, 81, 222, 183, 252]
+    anchor_account_discriminator("OperationState")
 }
 pub fn discriminator_personal_position_state() -> [u8; 8] {
-    [70, 111, 150, 126, 230, 15, 25, 117]
+    anchor_account_discriminator("PersonalPositionState")
 }
 pub fn discriminator_pool_state() -> [u8; 8] {
-    [247, 237, 227, 245, 215, 195, 222, 70]
+    anchor_account_discriminator("PoolState")
 }
 pub fn discriminator_protocol_position_state() -> [u8; 8] {
-    [100, 226, 145, 99, 146, 218, 160, 106]
+    anchor_account_discriminator("ProtocolPositionState")
 }
 pub fn discriminator_support_mint_associated() -> [u8; 8] {
-    [134, 40, 183, 79, 12, 112, 162, 53]
+    anchor_account_discriminator("SupportMintAssociated")
 }
 pub fn discriminator_tick_array_bitmap_extension() -> [u8; 8] {
-    [60, 150, 36, 219, 97, 128, 139, 153]
+    anchor_account_discriminator("TickArrayBitmapExtension")
 }
 pub fn discriminator_tick_array_state() -> [u8; 8] {
-    [192, 155, 85, 205, 49, 249, 129, 42]
+    anchor_account_discriminator("TickArrayState")
 }
@@ -0,0 +1,275 @@
+use std::collections::VecDeque;
+
+use solana_sdk::{pubkey::Pubkey, system_program::ID as system_id};
+
+#[cfg(target_os = "wasi")]
+use crate::primitive::wasmimport::HostImport;
+use crate::{
+    primitive::{
+        common::AccountReader,
+        dispatch::DispatchTable,
+        err::CatscopeWasmError,
+        guest::{edge_or_log, GuestFilter},
+        header::AccountHeader,
+        label::{
+            LABEL_RAYDIUM_CPMM_AMM_CONFIG_FUND_OWNER, LABEL_RAYDIUM_CPMM_AMM_CONFIG_PROTOCOL_OWNER,
+            LABEL_RAYDIUM_CPMM_POOL_STATE_AMM_CONFIG, LABEL_RAYDIUM_CPMM_POOL_STATE_LP_MINT,
+            LABEL_RAYDIUM_CPMM_POOL_STATE_OBSERVATION, LABEL_RAYDIUM_CPMM_POOL_STATE_TOKEN_0_VAULT,
+            LABEL_RAYDIUM_CPMM_POOL_STATE_TOKEN_1_VAULT,
+        },
+        node::{
+            NodeType, NODE_TYPE_RAYDIUM_CPMM_AMM_CONFIG, NODE_TYPE_RAYDIUM_CPMM_POOL_STATE,
+            NODE_TYPE_UNKNOWN,
+        },
+        tree::{FilterEdge, WEIGHT_DIRECT, WEIGHT_SYMLINK},
+    },
+    raydium_clmm::{discriminator_amm_config, discriminator_pool_state},
+};
+
+// CPMM reuses the Anchor account names of CLMM, so the discriminators are identical
+// and only the program id tells the two layouts apart.
+pub const POOL_STATE_SIZE: usize = 637;
+pub const AMM_CONFIG_SIZE: usize = 236;
+
+/// The accounts this filter parses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AccountKind {
+    AmmConfig,
+    PoolState,
+}
+
+pub struct RaydiumCpmm {
+    dispatch: DispatchTable<AccountKind>,
+    pub program_id: Pubkey,
+}
+impl GuestFilter for RaydiumCpmm {
+    fn program_id_list(&self) -> Vec<Pubkey> {
+        vec![self.program_id]
+    }
+
+    fn edge(&self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge> {
+        edge_or_log("raydium_cpmm", header, self.try_edge(header, data))
+    }
+
+    fn node_type(&self, _header: &AccountHeader, data: &[u8]) -> NodeType {
+        match self.dispatch.get(data) {
+            Some(AccountKind::AmmConfig) => NODE_TYPE_RAYDIUM_CPMM_AMM_CONFIG,
+            Some(AccountKind::PoolState) => NODE_TYPE_RAYDIUM_CPMM_POOL_STATE,
+            None => NODE_TYPE_UNKNOWN,
+        }
+    }
+}
+
+impl RaydiumCpmm {
+    fn try_edge(
+        &self,
+        header: &AccountHeader,
+        data: &[u8],
+    ) -> Result<VecDeque<FilterEdge>, CatscopeWasmError> {
+        let mut list = VecDeque::new();
+        let id = header.pubkey;
+        let mut i;
+        let pubkey_len = std::mem::size_of::<Pubkey>();
+        let reader = AccountReader::new(data);
+        #[cfg(target_os = "wasi")]
+        HostImport::log(format!(
+            "raydium_cpmm_edge - 1 - pubkey {}; data len {}",
+            id,
+            data.len()
+        ));
+        match self.dispatch.get(data) {
+            Some(AccountKind::AmmConfig) => {
+                reader.require(AMM_CONFIG_SIZE)?;
+                #[cfg(target_os = "wasi")]
+                HostImport::log(format!(
+                    "raydium_cpmm_edge - 2 - amm config - pubkey {};",
+                    id
+                ));
+                // protocol owner
+                {
+                    i = 44;
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_id {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_SYMLINK,
+                            from: pubkey,
+                            to: id,
+                            label: LABEL_RAYDIUM_CPMM_AMM_CONFIG_PROTOCOL_OWNER,
+                        });
+                    }
+                }
+                // fund owner
+                {
+                    i = 76;
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_id {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_DIRECT,
+                            from: id,
+                            to: pubkey,
+                            label: LABEL_RAYDIUM_CPMM_AMM_CONFIG_FUND_OWNER,
+                        });
+                    }
+                }
+            }
+            Some(AccountKind::PoolState) => {
+                reader.require(POOL_STATE_SIZE)?;
+                #[cfg(target_os = "wasi")]
+                HostImport::log(format!(
+                    "raydium_cpmm_edge - 2 - pool state - pubkey {};",
+                    id
+                ));
+                // amm config
+                {
+                    i = 8;
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_id {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_DIRECT,
+                            from: pubkey,
+                            to: id,
+                            label: LABEL_RAYDIUM_CPMM_POOL_STATE_AMM_CONFIG,
+                        });
+                    }
+                }
+                // token vault 0, token vault 1, lp mint; skip the pool creator
+                for (k, label) in [
+                    LABEL_RAYDIUM_CPMM_POOL_STATE_TOKEN_0_VAULT,
+                    LABEL_RAYDIUM_CPMM_POOL_STATE_TOKEN_1_VAULT,
+                    LABEL_RAYDIUM_CPMM_POOL_STATE_LP_MINT,
+                ]
+                .into_iter()
+                .enumerate()
+                {
+                    i = 72 + k * pubkey_len;
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_id {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_DIRECT,
+                            from: id,
+                            to: pubkey,
+                            label,
+                        });
+                    }
+                }
+                // observation account; skip the token mints and token programs
+                {
+                    i = 296;
+                    let pubkey = reader.pubkey_at(i)?;
+                    if pubkey != system_id {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            weight: WEIGHT_DIRECT,
+                            from: id,
+                            to: pubkey,
+                            label: LABEL_RAYDIUM_CPMM_POOL_STATE_OBSERVATION,
+                        });
+                    }
+                }
+            }
+            None => {}
+        }
+        #[cfg(target_os = "wasi")]
+        HostImport::log(format!("raydium_cpmm_edge - 4 - pubkey {};", id));
+        Ok(list)
+    }
+
+    pub fn new(program_id: &Pubkey) -> Self {
+        Self {
+            program_id: *program_id,
+            dispatch: [
+                (discriminator_amm_config(), AccountKind::AmmConfig),
+                (discriminator_pool_state(), AccountKind::PoolState),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::primitive::tree::Weight;
+
+    use super::*; // import functions from the parent module
+
+    fn header(program_id: &Pubkey, data: &[u8]) -> AccountHeader {
+        AccountHeader {
+            pubkey: Pubkey::new_unique(),
+            lamports: 488832,
+            data_size: data.len() as u32,
+            node_id: 100432,
+            owner: *program_id,
+            rent_epoch: 1,
+            slot: 1,
+            executable: false,
+        }
+    }
+
+    #[test]
+    fn test_pool_state() {
+        let program_id = Pubkey::try_from("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C").unwrap();
+        let amm_config = Pubkey::new_unique();
+        let token_vault_0 = Pubkey::new_unique();
+        let token_vault_1 = Pubkey::new_unique();
+        let lp_mint = Pubkey::new_unique();
+        let observation = Pubkey::new_unique();
+        let mut data = vec![0u8; POOL_STATE_SIZE];
+        data[0..8].copy_from_slice(&discriminator_pool_state());
+        data[8..40].copy_from_slice(amm_config.as_ref());
+        data[40..72].copy_from_slice(Pubkey::new_unique().as_ref()); // pool creator
+        data[72..104].copy_from_slice(token_vault_0.as_ref());
+        data[104..136].copy_from_slice(token_vault_1.as_ref());
+        data[136..168].copy_from_slice(lp_mint.as_ref());
+        data[296..328].copy_from_slice(observation.as_ref());
+        let header = header(&program_id, &data);
+        let filter = RaydiumCpmm::new(&program_id);
+        let mut list = filter.edge(&header, &data);
+        assert_eq!(list.len(), 5, "pool state must have 5 edges");
+        let mut m_edge: HashMap<Pubkey, Weight> = HashMap::default();
+        while let Some(edge) = list.pop_front() {
+            if edge.from == header.pubkey {
+                m_edge.insert(edge.to, edge.weight);
+            } else {
+                assert_eq!(edge.from, amm_config, "only the config edge is incoming");
+                m_edge.insert(edge.from, edge.weight);
+            }
+        }
+        for pubkey in [
+            amm_config,
+            token_vault_0,
+            token_vault_1,
+            lp_mint,
+            observation,
+        ] {
+            assert_eq!(*m_edge.get(&pubkey).unwrap(), WEIGHT_DIRECT);
+        }
+        // truncated accounts produce no edges
+        assert!(filter.edge(&header, &data[..300]).is_empty());
+    }
+    #[test]
+    fn test_amm_config() {
+        let program_id = Pubkey::try_from("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C").unwrap();
+        let protocol_owner = Pubkey::new_unique();
+        let fund_owner = Pubkey::new_unique();
+        let mut data = vec![0u8; AMM_CONFIG_SIZE];
+        data[0..8].copy_from_slice(&discriminator_amm_config());
+        data[44..76].copy_from_slice(protocol_owner.as_ref());
+        data[76..108].copy_from_slice(fund_owner.as_ref());
+        let header = header(&program_id, &data);
+        let filter = RaydiumCpmm::new(&program_id);
+        let list = filter.edge(&header, &data);
+        assert_eq!(list.len(), 2, "amm config must have 2 edges");
+        assert_eq!(list[0].from, protocol_owner);
+        assert_eq!(list[0].weight, WEIGHT_SYMLINK);
+        assert_eq!(list[1].to, fund_owner);
+        assert_eq!(list[1].weight, WEIGHT_DIRECT);
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use solana_sdk::{pubkey::Pubkey, system_program::ID as system_id};
+
+#[cfg(target_os = "wasi")]
+use crate::primitive::wasmimport::HostImport;
+use crate::{
+    primitive::{
+        common::{AccountReader, PUBKEY_LEN},
+        dispatch::DispatchTable,
+        err::CatscopeWasmError,
+        guest::{edge_or_log, GuestFilter},
+        header::AccountHeader,
+        label::LABEL_RULE_OFFSET,
+        tree::{parse_weight, FilterEdge, Weight},
+    },
+    DISCRIMINATOR_SIZE,
+};
+
+/// Which end of the edge the account sits on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleDirection {
+    /// account -> pubkey
+    Out,
+    /// pubkey -> account
+    In,
+}
+
+/// Read one pubkey at a fixed offset of accounts that match a discriminator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OffsetRule {
+    pub owner: Pubkey,
+    /// `None` matches every account of the owner, as needed for programs without discriminators.
+    pub discriminator: Option<[u8; DISCRIMINATOR_SIZE]>,
+    pub min_len: usize,
+    pub offset: usize,
+    pub direction: RuleDirection,
+    pub weight: Weight,
+    /// skip the edge when the pubkey is unset (all zeros, the system program id).
+    pub skip_default: bool,
+}
+
+impl OffsetRule {
+    /// Parse a rule from comma separated fields:
+    /// `owner,discriminator,min_len,offset,direction,weight,skip_default`.
+    ///
+    /// * `discriminator` is 16 hex characters or `-` to match every account.
+    /// * `direction` is `out` (account to pubkey) or `in` (pubkey to account).
+    /// * `weight` is parsed with [`parse_weight`], eg `DIRECT` or `DIRECT|SYMLINK`.
+    /// * `skip_default` is `true` or `false`.
+    pub fn parse(input: &str) -> Result<Self, CatscopeWasmError> {
+        let fields: Vec<&str> = input.split(',').map(|x| x.trim()).collect();
+        if fields.len() != 7 {
+            return Err(CatscopeWasmError::InvalidInput(format!(
+                "rule needs 7 fields but got {}: `{}`",
+                fields.len(),
+                input
+            )));
+        }
+        let owner = Pubkey::try_from(fields[0]).map_err(|e| {
+            CatscopeWasmError::InvalidInput(format!("bad rule owner {}: {}", fields[0], e))
+        })?;
+        let discriminator = if fields[1] == "-" {
+            None
+        } else {
+            let mut d = [0u8; DISCRIMINATOR_SIZE];
+            hex::decode_to_slice(fields[1], &mut d).map_err(|e| {
+                CatscopeWasmError::InvalidInput(format!(
+                    "bad rule discriminator {}: {}",
+                    fields[1], e
+                ))
+            })?;
+            Some(d)
+        };
+        let min_len = fields[2].parse::<usize>().map_err(|e| {
+            CatscopeWasmError::InvalidInput(format!("bad rule min_len {}: {}", fields[2], e))
+        })?;
+        let offset = fields[3].parse::<usize>().map_err(|e| {
+            CatscopeWasmError::InvalidInput(format!("bad rule offset {}: {}", fields[3], e))
+        })?;
+        let direction = match fields[4] {
+            "out" => RuleDirection::Out,
+            "in" => RuleDirection::In,
+            x => {
+                return Err(CatscopeWasmError::InvalidInput(format!(
+                    "bad rule direction `{}`; expected in or out",
+                    x
+                )))
+            }
+        };
+        let weight = parse_weight(fields[5])?;
+        let skip_default = fields[6].parse::<bool>().map_err(|e| {
+            CatscopeWasmError::InvalidInput(format!("bad rule skip_default {}: {}", fields[6], e))
+        })?;
+        // the pubkey must always fit inside of accounts that pass the length check
+        let end = offset.checked_add(PUBKEY_LEN).ok_or_else(|| {
+            CatscopeWasmError::InvalidInput(format!("bad rule offset {}", offset))
+        })?;
+        let min_len = min_len.max(end);
+        Ok(Self {
+            owner,
+            discriminator,
+            min_len,
+            offset,
+            direction,
+            weight,
+            skip_default,
+        })
+    }
+}
+
+/// The rules of one owner. Rules with a discriminator are looked up by the discriminator of the
+/// account; the rest are tried on every account.
+#[derive(Default)]
+struct RuleTable {
+    dispatch: DispatchTable<Vec<OffsetRule>>,
+    l_wildcard: Vec<OffsetRule>,
+}
+
+/// A filter driven by offset rules loaded from the init args.
+pub struct RuleFilter {
+    m_rule: BTreeMap<Pubkey, RuleTable>,
+}
+
+impl GuestFilter for RuleFilter {
+    fn program_id_list(&self) -> Vec<Pubkey> {
+        self.m_rule.keys().copied().collect()
+    }
+
+    fn edge(&self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge> {
+        edge_or_log("rule", header, self.try_edge(header, data))
+    }
+}
+
+impl RuleFilter {
+    fn try_edge(
+        &self,
+        header: &AccountHeader,
+        data: &[u8],
+    ) -> Result<VecDeque<FilterEdge>, CatscopeWasmError> {
+        let mut list = VecDeque::new();
+        let id = header.pubkey;
+        let table = match self.m_rule.get(&header.owner) {
+            Some(x) => x,
+            None => return Ok(list),
+        };
+        // the rules for the discriminator of the account come before the rules for every account
+        let rule_list = table
+            .dispatch
+            .get(data)
+            .map(|x| x.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .chain(table.l_wildcard.iter());
+        let reader = AccountReader::new(data);
+        #[cfg(target_os = "wasi")]
+        HostImport::log(format!(
+            "rule_edge - 1 - pubkey {}; data len {}",
+            id,
+            data.len()
+        ));
+        for rule in rule_list.filter(|r| r.min_len <= data.len()) {
+            let pubkey = reader.pubkey_at(rule.offset)?;
+            if rule.skip_default && pubkey == system_id {
+                continue;
+            }
+            let (from, to) = match rule.direction {
+                RuleDirection::Out => (id, pubkey),
+                RuleDirection::In => (pubkey, id),
+            };
+            list.push_back(FilterEdge {
+                slot: header.slot,
+                weight: rule.weight,
+                from,
+                to,
+                label: LABEL_RULE_OFFSET,
+            });
+        }
+        Ok(list)
+    }
+
+    pub fn new(rule_list: &[OffsetRule]) -> Self {
+        let mut m_rule: BTreeMap<Pubkey, RuleTable> = BTreeMap::new();
+        for rule in rule_list.iter() {
+            let table = m_rule.entry(rule.owner).or_default();
+            match rule.discriminator {
+                Some(d) => table.dispatch.get_or_default(d).push(rule.clone()),
+                None => table.l_wildcard.push(rule.clone()),
+            }
+        }
+        Self { m_rule }
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        orca::Orca,
+        primitive::tree::{WEIGHT_DIRECT, WEIGHT_SYMLINK},
+    };
+
+    use super::*; // import functions from the parent module
+
+    const ORCA: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+
+    #[test]
+    fn test_parse() {
+        let rule = OffsetRule::parse(&format!(
+            "{}, 3f95d10ce1806309, 0, 133, out, direct, true",
+            ORCA
+        ))
+        .unwrap();
+        assert_eq!(
+            rule.discriminator,
+            Some([63, 149, 209, 12, 225, 128, 99, 9])
+        );
+        assert_eq!(rule.min_len, 165);
+        assert_eq!(rule.direction, RuleDirection::Out);
+        assert_eq!(rule.weight, WEIGHT_DIRECT);
+        let rule =
+            OffsetRule::parse(&format!("{},-,752,336,in,DIRECT|SYMLINK,false", ORCA)).unwrap();
+        assert_eq!(rule.discriminator, None);
+        assert_eq!(rule.min_len, 752);
+        assert_eq!(rule.weight, WEIGHT_DIRECT | WEIGHT_SYMLINK);
+
+        assert!(OffsetRule::parse(&format!("{},-,0,0,out,DIRECT", ORCA)).is_err());
+        assert!(OffsetRule::parse(&format!("{},3f95,0,0,out,DIRECT,true", ORCA)).is_err());
+        assert!(OffsetRule::parse(&format!("{},-,0,0,up,DIRECT,true", ORCA)).is_err());
+        assert!(OffsetRule::parse(&format!("{},-,0,0,out,OWNER,true", ORCA)).is_err());
+        assert!(OffsetRule::parse(&format!("{},-,0,0,out,1,true", ORCA)).is_err());
+    }
+
+    // The rules below reproduce the whirlpool edges of the Orca filter.
+    #[test]
+    fn test_whirlpool_rules() {
+        let program_id = Pubkey::try_from(ORCA).unwrap();
+        let rule_list: Vec<OffsetRule> = [
+            "3f95d10ce1806309,0,8,in,DIRECT,true",
+            "3f95d10ce1806309,0,133,out,DIRECT,true",
+            "3f95d10ce1806309,0,213,out,DIRECT,true",
+            // never matches the whirlpool
+            "9d1431e0d957c1fe,0,8,out,DIRECT,true",
+        ]
+        .iter()
+        .map(|x| OffsetRule::parse(&format!("{},{}", ORCA, x)).unwrap())
+        .collect();
+        let data = std::fs::read("tests/data/whirlpool1.bin").unwrap();
+        let header = AccountHeader {
+            pubkey: Pubkey::try_from("DtYKbQELgMZ3ihFUrCcCs9gy4djcUuhwgR7UpxVpP2Tg").unwrap(),
+            lamports: 488832,
+            data_size: data.len() as u32,
+            node_id: 100432,
+            owner: program_id,
+            rent_epoch: 1,
+            slot: 1,
+            executable: false,
+        };
+        let filter = RuleFilter::new(&rule_list);
+        assert_eq!(filter.program_id_list(), vec![program_id]);
+        let list = filter.edge(&header, &data);
+        let expected = Orca::new(&program_id).edge(&header, &data);
+        let to_map = |l: &VecDeque<FilterEdge>| -> HashMap<(Pubkey, Pubkey), Weight> {
+            l.iter().map(|e| ((e.from, e.to), e.weight)).collect()
+        };
+        assert_eq!(list.len(), 3);
+        assert_eq!(to_map(&list), to_map(&expected));
+        // short buffers produce no edges
+        assert!(filter.edge(&header, &data[..200]).len() < 3);
+        assert!(filter.edge(&header, &data[..8]).is_empty());
+
+        // a rule for every account comes after the rules for the discriminator
+        let mut rule_list = rule_list;
+        let rule = OffsetRule::parse(&format!("{},-,0,8,in,SYMLINK,false", ORCA)).unwrap();
+        rule_list.insert(0, rule);
+        let filter = RuleFilter::new(&rule_list);
+        let list = filter.edge(&header, &data);
+        assert_eq!(list.len(), 4);
+        assert_eq!(
+            (list[3].to, list[3].weight),
+            (header.pubkey, WEIGHT_SYMLINK)
+        );
+        // and is the only one to match other accounts
+        assert_eq!(filter.edge(&header, &[1; 40]).len(), 1);
+    }
+}
@@ -5,14 +5,42 @@ use solana_sdk::pubkey::Pubkey;
 #[cfg(target_os = "wasi")]
 use crate::primitive::wasmimport::HostImport;
 use crate::primitive::{
-    common::match_discriminator,
-    guest::GuestFilter,
+    common::anchor_account_discriminator,
+    dispatch::DispatchTable,
+    err::CatscopeWasmError,
+    guest::{edge_or_log, GuestFilter},
     header::AccountHeader,
-    tree::{FilterEdge, WEIGHT_DIRECT, WEIGHT_PROGRAM},
+    node::{
+        NodeType, NODE_TYPE_SAFEJAR_CONTROLLER, NODE_TYPE_SAFEJAR_DELEGATION, NODE_TYPE_UNKNOWN,
+    },
+    source::EdgeSource,
+    tree::FilterEdge,
 };
+
+/// The accounts this filter parses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AccountKind {
+    Controller,
+    Delegation,
+}
+
+#[derive(EdgeSource)]
+#[edge(program, weight = PROGRAM, label = SAFEJAR_CONTROLLER_PROGRAM)]
+pub struct Controller {
+    pub bump: u8,
+    #[edge(out, weight = DIRECT, label = SAFEJAR_CONTROLLER_OWNER)]
+    pub owner: Pubkey,
+}
+
+#[derive(EdgeSource)]
+pub struct Delegation {
+    pub bump: u8,
+    #[edge(in, weight = DIRECT, label = SAFEJAR_DELEGATION_CONTROLLER)]
+    pub controller: Pubkey,
+}
+
 pub struct Safejar {
-    d_controller: [u8; 8],
-    d_delegation: [u8; 8],
+    dispatch: DispatchTable<AccountKind>,
     pub program_id: Pubkey,
 }
 
@@ -22,74 +50,166 @@ impl GuestFilter for Safejar {
     }
 
     fn edge(&self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge> {
-        let mut list = VecDeque::new();
-        let id = header.pubkey;
-        // all discriminators are the same length
-        let prefix = self.d_controller.len();
-        let pubkey_len = std::mem::size_of::<Pubkey>();
+        edge_or_log("safejar", header, self.try_edge(header, data))
+    }
+
+    fn node_type(&self, _header: &AccountHeader, data: &[u8]) -> NodeType {
+        match self.dispatch.get(data) {
+            Some(AccountKind::Controller) => NODE_TYPE_SAFEJAR_CONTROLLER,
+            Some(AccountKind::Delegation) => NODE_TYPE_SAFEJAR_DELEGATION,
+            None => NODE_TYPE_UNKNOWN,
+        }
+    }
+}
+impl Safejar {
+    fn try_edge(
+        &self,
+        header: &AccountHeader,
+        data: &[u8],
+    ) -> Result<VecDeque<FilterEdge>, CatscopeWasmError> {
         #[cfg(target_os = "wasi")]
         HostImport::log(format!(
             "safejar_edge - 1 - pubkey {}; data len {}",
-            id,
+            header.pubkey,
             data.len()
         ));
-        if match_discriminator(&self.d_controller, data) {
-            #[cfg(target_os = "wasi")]
-            HostImport::log(format!("safejar_edge - 2 - controller - pubkey {};", id));
-            // program to controller
-            list.push_back(FilterEdge {
-                slot: header.slot,
-                weight: WEIGHT_PROGRAM,
-                from: self.program_id,
-                to: id,
-            });
-            // controller to owner
-            list.push_back(FilterEdge {
-                slot: header.slot,
-                from: id,
-                to: Pubkey::try_from(&data[(prefix + 1)..(prefix + 1 + pubkey_len)]).unwrap(),
-                weight: WEIGHT_DIRECT,
-            });
-        } else if match_discriminator(&self.d_delegation, data) {
-            #[cfg(target_os = "wasi")]
-            HostImport::log(format!("safejar_edge - 3 - delegation - pubkey {};", id));
-            // controller to delegation
-            list.push_back(FilterEdge {
-                slot: header.slot,
-                from: Pubkey::try_from(&data[(prefix + 1)..(prefix + 1 + pubkey_len)]).unwrap(),
-                to: id,
-                weight: WEIGHT_DIRECT,
-            });
-        }
+        let list = match self.dispatch.get(data) {
+            Some(AccountKind::Controller) => {
+                Controller::parse(data)?.edge(header, &self.program_id)
+            }
+            Some(AccountKind::Delegation) => {
+                Delegation::parse(data)?.edge(header, &self.program_id)
+            }
+            None => VecDeque::new(),
+        };
         #[cfg(target_os = "wasi")]
-        HostImport::log(format!("safejar_edge - 4 - pubkey {};", id));
-        list
+        HostImport::log(format!("safejar_edge - 4 - pubkey {};", header.pubkey));
+        Ok(list)
     }
-}
-impl Safejar {
+
     pub fn new(program_id: &Pubkey) -> Self {
-        let d_controller = controller_discriminator();
-        let d_delegation = delegation_discriminator();
         Self {
             program_id: *program_id,
-            d_controller,
-            d_delegation,
+            dispatch: [
+                (controller_discriminator(), AccountKind::Controller),
+                (delegation_discriminator(), AccountKind::Delegation),
+            ]
+            .into_iter()
+            .collect(),
         }
     }
 }
 
 pub fn controller_discriminator() -> [u8; 8] {
-    [184, 79, 171, 0, 183, 43, 113, 110]
+    anchor_account_discriminator("Controller")
 }
 
 pub fn delegation_discriminator() -> [u8; 8] {
-    [237, 90, 140, 159, 124, 255, 243, 80]
+    anchor_account_discriminator("Delegation")
 }
 
 pub fn ruleaccumulator_discriminator() -> [u8; 8] {
-    [127, 132, 189, 170, 68, 38, 206, 135]
+    anchor_account_discriminator("RuleAccumulator")
 }
 
 pub fn spendrequest_discriminator() -> [u8; 8] {
-    [71, 251, 215, 71, 98, 153, 90, 25]
+    anchor_account_discriminator("SpendRequest")
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::primitive::{
+        label::{
+            EdgeLabel, LABEL_SAFEJAR_CONTROLLER_OWNER, LABEL_SAFEJAR_CONTROLLER_PROGRAM,
+            LABEL_SAFEJAR_DELEGATION_CONTROLLER,
+        },
+        tree::{Weight, WEIGHT_DIRECT, WEIGHT_PROGRAM},
+    };
+
+    use super::*; // import functions from the parent module
+
+    fn account(discriminator: [u8; 8], pubkey: &Pubkey) -> Vec<u8> {
+        [&discriminator[..], &[254], pubkey.as_ref(), &[0; 16]].concat()
+    }
+
+    fn edge_list(list: &VecDeque<FilterEdge>) -> Vec<(Pubkey, Pubkey, Weight, EdgeLabel)> {
+        list.iter()
+            .map(|e| (e.from, e.to, e.weight, e.label))
+            .collect()
+    }
+
+    #[test]
+    fn test_safejar() {
+        let program_id = Pubkey::new_unique();
+        let filter = Safejar::new(&program_id);
+        let header = AccountHeader {
+            pubkey: Pubkey::new_unique(),
+            owner: program_id,
+            slot: 3,
+            ..Default::default()
+        };
+        let id = header.pubkey;
+
+        // program -> controller, controller -> owner
+        let owner = Pubkey::new_unique();
+        let data = account(controller_discriminator(), &owner);
+        assert_eq!(
+            edge_list(&filter.edge(&header, &data)),
+            vec![
+                (
+                    program_id,
+                    id,
+                    WEIGHT_PROGRAM,
+                    LABEL_SAFEJAR_CONTROLLER_PROGRAM
+                ),
+                (id, owner, WEIGHT_DIRECT, LABEL_SAFEJAR_CONTROLLER_OWNER),
+            ]
+        );
+        assert_eq!(
+            filter.node_type(&header, &data),
+            NODE_TYPE_SAFEJAR_CONTROLLER
+        );
+
+        // controller -> delegation
+        let controller = Pubkey::new_unique();
+        let data = account(delegation_discriminator(), &controller);
+        assert_eq!(
+            edge_list(&filter.edge(&header, &data)),
+            vec![(
+                controller,
+                id,
+                WEIGHT_DIRECT,
+                LABEL_SAFEJAR_DELEGATION_CONTROLLER
+            )]
+        );
+        assert_eq!(
+            filter.node_type(&header, &data),
+            NODE_TYPE_SAFEJAR_DELEGATION
+        );
+
+        // other accounts of the program have no edges
+        let data = account(spendrequest_discriminator(), &owner);
+        assert!(filter.edge(&header, &data).is_empty());
+        assert_eq!(filter.node_type(&header, &data), NODE_TYPE_UNKNOWN);
+    }
+
+    #[test]
+    fn test_short_buffer() {
+        let filter = Safejar::new(&Pubkey::new_unique());
+        let header = AccountHeader::default();
+        for discriminator in [controller_discriminator(), delegation_discriminator()] {
+            let data = account(discriminator, &Pubkey::new_unique());
+            // the bump and the pubkey must be present
+            let size = 8 + 1 + 32;
+            assert!(!filter.try_edge(&header, &data[..size]).unwrap().is_empty());
+            for len in [8, 9, size - 1] {
+                assert!(matches!(
+                    filter.try_edge(&header, &data[..len]),
+                    Err(CatscopeWasmError::ShortBuffer(_, _))
+                ));
+                assert!(filter.edge(&header, &data[..len]).is_empty());
+            }
+        }
+    }
 }
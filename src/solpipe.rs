@@ -6,22 +6,46 @@ use std::collections::VecDeque;
 use crate::primitive::wasmimport::HostImport;
 use crate::{
     primitive::{
-        common::{match_discriminator, PUBKEY_LEN, U32_LEN, U64_LEN},
-        guest::GuestFilter,
+        common::{anchor_account_discriminator, AccountReader, PUBKEY_LEN, U32_LEN, U64_LEN},
+        dispatch::DispatchTable,
+        err::CatscopeWasmError,
+        guest::{edge_or_log, GuestFilter},
         header::AccountHeader,
-        tree::{FilterEdge, WEIGHT_DIRECT, WEIGHT_IS_OUTGOING, WEIGHT_PROGRAM, WEIGHT_SYMLINK},
+        label::{
+            LABEL_SOLPIPE_AGENT_AUTHORIZER, LABEL_SOLPIPE_AGENT_CONTROLLER,
+            LABEL_SOLPIPE_AGENT_VAULT, LABEL_SOLPIPE_BID_LIST_BIDDER,
+            LABEL_SOLPIPE_BID_LIST_PAYOUT, LABEL_SOLPIPE_CONTROLLER_ADMIN,
+            LABEL_SOLPIPE_CONTROLLER_API_CONTROLLER, LABEL_SOLPIPE_CONTROLLER_PROGRAM,
+            LABEL_SOLPIPE_PAYOUT_PIPELINE, LABEL_SOLPIPE_PERIOD_RING_PIPELINE,
+            LABEL_SOLPIPE_PIPELINE_ADMIN, LABEL_SOLPIPE_PIPELINE_CONTROLLER,
+            LABEL_SOLPIPE_REFUNDS_BIDDER, LABEL_SOLPIPE_REFUNDS_PIPELINE,
+        },
+        node::{
+            NodeType, NODE_TYPE_SOLPIPE_AGENT, NODE_TYPE_SOLPIPE_BID_LIST,
+            NODE_TYPE_SOLPIPE_CONTROLLER, NODE_TYPE_SOLPIPE_CONTROLLER_API,
+            NODE_TYPE_SOLPIPE_PAYOUT, NODE_TYPE_SOLPIPE_PERIOD_RING, NODE_TYPE_SOLPIPE_PIPELINE,
+            NODE_TYPE_SOLPIPE_REFUNDS, NODE_TYPE_UNKNOWN,
+        },
+        tree::{FilterEdge, WEIGHT_DIRECT, WEIGHT_PROGRAM, WEIGHT_SYMLINK},
     },
     DISCRIMINATOR_SIZE,
 };
+
+/// The accounts this filter parses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AccountKind {
+    Controller,
+    ControllerApi,
+    Pipeline,
+    Payout,
+    PeriodRing,
+    Refunds,
+    BidList,
+    Agent,
+}
+
 pub struct Solpipe {
-    d_controller: [u8; 8],
-    d_controller_api: [u8; 8],
-    d_pipeline: [u8; 8],
-    d_period_ring: [u8; 8],
-    d_payout: [u8; 8],
-    d_agent: [u8; 8],
-    d_bidlist: [u8; 8],
-    d_refunds: [u8; 8],
+    dispatch: DispatchTable<AccountKind>,
     pub program_id: Pubkey,
 }
 
@@ -31,250 +55,291 @@ impl GuestFilter for Solpipe {
     }
 
     fn edge(&self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge> {
+        edge_or_log("solpipe", header, self.try_edge(header, data))
+    }
+
+    fn node_type(&self, _header: &AccountHeader, data: &[u8]) -> NodeType {
+        match self.dispatch.get(data) {
+            Some(AccountKind::Controller) => NODE_TYPE_SOLPIPE_CONTROLLER,
+            Some(AccountKind::ControllerApi) => NODE_TYPE_SOLPIPE_CONTROLLER_API,
+            Some(AccountKind::Pipeline) => NODE_TYPE_SOLPIPE_PIPELINE,
+            Some(AccountKind::Payout) => NODE_TYPE_SOLPIPE_PAYOUT,
+            Some(AccountKind::PeriodRing) => NODE_TYPE_SOLPIPE_PERIOD_RING,
+            Some(AccountKind::Refunds) => NODE_TYPE_SOLPIPE_REFUNDS,
+            Some(AccountKind::BidList) => NODE_TYPE_SOLPIPE_BID_LIST,
+            Some(AccountKind::Agent) => NODE_TYPE_SOLPIPE_AGENT,
+            None => NODE_TYPE_UNKNOWN,
+        }
+    }
+}
+impl Solpipe {
+    fn try_edge(
+        &self,
+        header: &AccountHeader,
+        data: &[u8],
+    ) -> Result<VecDeque<FilterEdge>, CatscopeWasmError> {
         let mut list = VecDeque::new();
         if data.len() < DISCRIMINATOR_SIZE {
-            return list;
+            return Ok(list);
         }
         let id = header.pubkey;
         let pubkey_len = std::mem::size_of::<Pubkey>();
         let subbuf = &data[DISCRIMINATOR_SIZE..];
+        let reader = AccountReader::new(subbuf);
 
         #[cfg(target_os = "wasi")]
         HostImport::log(format!("_edge - 1 - pubkey {};", id));
-        if match_discriminator(&self.d_controller, data) {
-            let program = FilterEdge {
-                slot: header.slot,
-                weight: WEIGHT_PROGRAM,
-                from: self.program_id,
-                to: id,
-            };
-            let mut i = 1; // start after bump
-            let mut length = pubkey_len;
-            let admin_pk = Pubkey::try_from(&subbuf[i..(i + length)]).unwrap();
-            let admin = FilterEdge {
-                slot: header.slot,
-                weight: WEIGHT_DIRECT,
-                from: id,
-                to: admin_pk,
-            };
-            i += length;
-            i += 2 * pubkey_len + 2 * 8; // skip until pc_vault
-            let pc_vault_pk = Pubkey::try_from(&subbuf[i..(i + length)]).unwrap();
-            //let pcvault = FilterEdge {
-            //    slot: header.slot,
-            //    weight: WEIGHT_SYMLINK | WEIGHT_IS_OUTGOING,
-            //    from: id,
-            //    to: pc_vault_pk,
-            //};
-            i += length;
-            let pc_mint_pk = Pubkey::try_from(&subbuf[i..(i + length)]).unwrap();
-            #[cfg(target_os = "wasi")]
-            HostImport::log(format!(
-                "edge - 2 - pubkey {}; controller; admin {}; pcmint {}; pcvault {};",
-                id, admin_pk, pc_mint_pk, pc_vault_pk
-            ));
-            list.push_back(program);
-            list.push_back(admin);
-        } else if match_discriminator(&self.d_controller_api, data) {
-            let i = 1; // start after bump
-            let controller_pk = Pubkey::try_from(&subbuf[i..(i + pubkey_len)]).unwrap();
-            #[cfg(target_os = "wasi")]
-            HostImport::log(format!(
-                "edge - 2 - controller_api {}; controller {}",
-                id, controller_pk
-            ));
-            let controller = FilterEdge {
-                slot: header.slot,
-                weight: WEIGHT_DIRECT,
-                from: controller_pk,
-                to: id,
-            };
-            list.push_back(controller);
-        } else if match_discriminator(&self.d_pipeline, data) {
-            #[cfg(target_os = "wasi")]
-            HostImport::log(format!("edge - 2 - pubkey {}; pipeline", id));
-            let mut i = 0;
-            let length = pubkey_len;
-            let controller = FilterEdge {
-                slot: header.slot,
-                weight: WEIGHT_DIRECT,
-                from: Pubkey::try_from(&subbuf[i..(i + length)]).unwrap(),
-                to: id,
-            };
-            i += length;
-            i += length + 2;
-            let admin = FilterEdge {
-                slot: header.slot,
-                weight: WEIGHT_SYMLINK,
-                from: id,
-                to: Pubkey::try_from(&subbuf[i..(i + length)]).unwrap(),
-            };
-            // the pipeline account owns the vault account, so we let the token program add the
-            // graph edge
-            list.push_back(controller);
-            list.push_back(admin);
-        } else if match_discriminator(&self.d_payout, data) {
-            #[cfg(target_os = "wasi")]
-            HostImport::log(format!("edge - 2 - pubkey {}; payout", id));
-            let i = 2 + pubkey_len;
-            let pipeline = Pubkey::try_from(&subbuf[i..(i + pubkey_len)]).unwrap();
-            list.push_back(FilterEdge {
-                slot: header.slot,
-                from: pipeline,
-                to: id,
-                weight: WEIGHT_DIRECT,
-            });
-        } else if match_discriminator(&self.d_period_ring, data) {
-            let i = 0;
-            #[cfg(target_os = "wasi")]
-            HostImport::log(format!("edge - 2 - pubkey {}; period_ring", id));
-            let pipeline = Pubkey::try_from(&subbuf[i..(i + pubkey_len)]).unwrap();
-            list.push_back(FilterEdge {
-                slot: header.slot,
-                from: pipeline,
-                to: id,
-                weight: WEIGHT_DIRECT,
-            });
-        } else if match_discriminator(&self.d_refunds, data) {
-            let i = 0;
-            #[cfg(target_os = "wasi")]
-            HostImport::log(format!("edge - 2 - pubkey {}; refunds", id));
-            let pipeline = Pubkey::try_from(&subbuf[i..(i + pubkey_len)]).unwrap();
-            list.push_back(FilterEdge {
-                slot: header.slot,
-                from: pipeline,
-                to: id,
-                weight: WEIGHT_DIRECT,
-            });
-            if let Some(claim_count) = Refunds::count(subbuf) {
+        match self.dispatch.get(data) {
+            Some(AccountKind::Controller) => {
+                let program = FilterEdge {
+                    slot: header.slot,
+                    weight: WEIGHT_PROGRAM,
+                    from: self.program_id,
+                    to: id,
+                    label: LABEL_SOLPIPE_CONTROLLER_PROGRAM,
+                };
+                let mut i = 1; // start after bump
+                let length = pubkey_len;
+                let admin_pk = reader.pubkey_at(i)?;
+                let admin = FilterEdge {
+                    slot: header.slot,
+                    weight: WEIGHT_DIRECT,
+                    from: id,
+                    to: admin_pk,
+                    label: LABEL_SOLPIPE_CONTROLLER_ADMIN,
+                };
+                i += length;
+                i += 2 * pubkey_len + 2 * 8; // skip until pc_vault
+                #[allow(unused_variables)]
+                let pc_vault_pk = reader.pubkey_at(i)?;
+                //let pcvault = FilterEdge {
+                //    slot: header.slot,
+                //    weight: WEIGHT_SYMLINK | WEIGHT_IS_OUTGOING,
+                //    from: id,
+                //    to: pc_vault_pk,
+                //};
+                i += length;
+                #[allow(unused_variables)]
+                let pc_mint_pk = reader.pubkey_at(i)?;
+                #[cfg(target_os = "wasi")]
+                HostImport::log(format!(
+                    "edge - 2 - pubkey {}; controller; admin {}; pcmint {}; pcvault {};",
+                    id, admin_pk, pc_mint_pk, pc_vault_pk
+                ));
+                list.push_back(program);
+                list.push_back(admin);
+            }
+            Some(AccountKind::ControllerApi) => {
+                let i = 1; // start after bump
+                let controller_pk = reader.pubkey_at(i)?;
+                #[cfg(target_os = "wasi")]
+                HostImport::log(format!(
+                    "edge - 2 - controller_api {}; controller {}",
+                    id, controller_pk
+                ));
+                let controller = FilterEdge {
+                    slot: header.slot,
+                    weight: WEIGHT_DIRECT,
+                    from: controller_pk,
+                    to: id,
+                    label: LABEL_SOLPIPE_CONTROLLER_API_CONTROLLER,
+                };
+                list.push_back(controller);
+            }
+            Some(AccountKind::Pipeline) => {
+                #[cfg(target_os = "wasi")]
+                HostImport::log(format!("edge - 2 - pubkey {}; pipeline", id));
+                let mut i = 0;
+                let length = pubkey_len;
+                let controller = FilterEdge {
+                    slot: header.slot,
+                    weight: WEIGHT_DIRECT,
+                    from: reader.pubkey_at(i)?,
+                    to: id,
+                    label: LABEL_SOLPIPE_PIPELINE_CONTROLLER,
+                };
+                i += length;
+                i += length + 2;
+                let admin = FilterEdge {
+                    slot: header.slot,
+                    weight: WEIGHT_SYMLINK,
+                    from: id,
+                    to: reader.pubkey_at(i)?,
+                    label: LABEL_SOLPIPE_PIPELINE_ADMIN,
+                };
+                // the pipeline account owns the vault account, so we let the token program add the
+                // graph edge
+                list.push_back(controller);
+                list.push_back(admin);
+            }
+            Some(AccountKind::Payout) => {
+                #[cfg(target_os = "wasi")]
+                HostImport::log(format!("edge - 2 - pubkey {}; payout", id));
+                let i = 2 + pubkey_len;
+                let pipeline = reader.pubkey_at(i)?;
+                list.push_back(FilterEdge {
+                    slot: header.slot,
+                    from: pipeline,
+                    to: id,
+                    weight: WEIGHT_DIRECT,
+                    label: LABEL_SOLPIPE_PAYOUT_PIPELINE,
+                });
+            }
+            Some(AccountKind::PeriodRing) => {
+                let i = 0;
+                #[cfg(target_os = "wasi")]
+                HostImport::log(format!("edge - 2 - pubkey {}; period_ring", id));
+                let pipeline = reader.pubkey_at(i)?;
+                list.push_back(FilterEdge {
+                    slot: header.slot,
+                    from: pipeline,
+                    to: id,
+                    weight: WEIGHT_DIRECT,
+                    label: LABEL_SOLPIPE_PERIOD_RING_PIPELINE,
+                });
+            }
+            Some(AccountKind::Refunds) => {
+                let i = 0;
+                #[cfg(target_os = "wasi")]
+                HostImport::log(format!("edge - 2 - pubkey {}; refunds", id));
+                let pipeline = reader.pubkey_at(i)?;
+                list.push_back(FilterEdge {
+                    slot: header.slot,
+                    from: pipeline,
+                    to: id,
+                    weight: WEIGHT_DIRECT,
+                    label: LABEL_SOLPIPE_REFUNDS_PIPELINE,
+                });
+                let claim_count = Refunds::count(subbuf)?;
                 for i in 0..claim_count {
-                    let claim = Refunds::parse(subbuf, i);
+                    let claim = Refunds::parse(subbuf, i)?;
                     if 0 < claim.balance {
                         list.push_back(FilterEdge {
                             slot: header.slot,
                             from: id,
                             to: claim.bidder,
                             weight: WEIGHT_SYMLINK,
+                            label: LABEL_SOLPIPE_REFUNDS_BIDDER,
                         });
                     }
                 }
             }
-        } else if match_discriminator(&self.d_bidlist, data) {
-            // map from bidder (agent) to payout;
-            // parse this for now because the logic is more complicated than before;
-            // TODO: do a zerocopy parse.
-            let bidlist = match BidList::parse(subbuf) {
-                Some(x) => x,
-                None => return VecDeque::new(),
-            };
-            let payout = bidlist.payout;
-            #[cfg(target_os = "wasi")]
-            HostImport::log(format!(
-                "edge - 2 - pubkey {}; bidlist; payout {}",
-                id, payout
-            ));
+            Some(AccountKind::BidList) => {
+                // map from bidder (agent) to payout;
+                // parse this for now because the logic is more complicated than before;
+                // TODO: do a zerocopy parse.
+                let bidlist = BidList::parse(subbuf)?;
+                let payout = bidlist.payout;
+                #[cfg(target_os = "wasi")]
+                HostImport::log(format!(
+                    "edge - 2 - pubkey {}; bidlist; payout {}",
+                    id, payout
+                ));
 
-            list.push_back(FilterEdge {
-                slot: header.slot,
-                from: payout,
-                to: id,
-                weight: WEIGHT_DIRECT,
-            });
-            for bid in bidlist.book.iter() {
-                // map to agent accounts
-                if !bid.is_blank {
-                    list.push_back(FilterEdge {
-                        slot: header.slot,
-                        from: Pubkey::from(bid.bidder.to_bytes()),
-                        to: id,
-                        weight: WEIGHT_SYMLINK,
-                    });
+                list.push_back(FilterEdge {
+                    slot: header.slot,
+                    from: payout,
+                    to: id,
+                    weight: WEIGHT_DIRECT,
+                    label: LABEL_SOLPIPE_BID_LIST_PAYOUT,
+                });
+                for bid in bidlist.book.iter() {
+                    // map to agent accounts
+                    if !bid.is_blank {
+                        list.push_back(FilterEdge {
+                            slot: header.slot,
+                            from: Pubkey::from(bid.bidder.to_bytes()),
+                            to: id,
+                            weight: WEIGHT_SYMLINK,
+                            label: LABEL_SOLPIPE_BID_LIST_BIDDER,
+                        });
+                    }
                 }
             }
-        } else if match_discriminator(&self.d_agent, data) {
-            // 1;1+32;i=1+2*32+4*8
-            let mut i = 1;
-            let controller = FilterEdge {
-                slot: header.slot,
-                from: Pubkey::try_from(&subbuf[i..(i + pubkey_len)]).unwrap(),
-                to: id,
-                weight: WEIGHT_DIRECT,
-            };
-            i += pubkey_len;
-            let authorizer = FilterEdge {
-                slot: header.slot,
-                from: id,
-                to: Pubkey::try_from(&subbuf[i..(i + pubkey_len)]).unwrap(),
-                weight: WEIGHT_DIRECT,
-            };
-            i += pubkey_len + 4 * 8;
-            // this may or may not get replaced with a token owner graph edge
-            let vault = FilterEdge {
-                slot: header.slot,
-                from: id,
-                to: Pubkey::try_from(&subbuf[i..(i + pubkey_len)]).unwrap(),
-                weight: WEIGHT_DIRECT,
-            };
-            list.push_back(controller);
-            list.push_back(authorizer);
-            list.push_back(vault);
+            Some(AccountKind::Agent) => {
+                // 1;1+32;i=1+2*32+4*8
+                let mut i = 1;
+                let controller = FilterEdge {
+                    slot: header.slot,
+                    from: reader.pubkey_at(i)?,
+                    to: id,
+                    weight: WEIGHT_DIRECT,
+                    label: LABEL_SOLPIPE_AGENT_CONTROLLER,
+                };
+                i += pubkey_len;
+                let authorizer = FilterEdge {
+                    slot: header.slot,
+                    from: id,
+                    to: reader.pubkey_at(i)?,
+                    weight: WEIGHT_DIRECT,
+                    label: LABEL_SOLPIPE_AGENT_AUTHORIZER,
+                };
+                i += pubkey_len + 4 * 8;
+                // this may or may not get replaced with a token owner graph edge
+                let vault = FilterEdge {
+                    slot: header.slot,
+                    from: id,
+                    to: reader.pubkey_at(i)?,
+                    weight: WEIGHT_DIRECT,
+                    label: LABEL_SOLPIPE_AGENT_VAULT,
+                };
+                list.push_back(controller);
+                list.push_back(authorizer);
+                list.push_back(vault);
+            }
+            None => {}
         }
-        list
+        Ok(list)
     }
-}
-impl Solpipe {
+
     pub fn new(program_id: &Pubkey) -> Self {
-        let d_controller = controller_discriminator();
-        let d_controller_api = controllerapi_discriminator();
-        let d_pipeline = pipeline_discriminator();
-        let d_payout = payout_discriminator();
-        let d_agent = agent_discriminator();
-        let d_bidlist = bidlist_discriminator();
-        let d_period_ring = periodring_discriminator();
-        let d_refunds = refunds_discriminator();
         Self {
             program_id: *program_id,
-            d_controller,
-            d_controller_api,
-            d_pipeline,
-            d_payout,
-            d_agent,
-            d_bidlist,
-            d_period_ring,
-            d_refunds,
+            dispatch: [
+                (controller_discriminator(), AccountKind::Controller),
+                (controllerapi_discriminator(), AccountKind::ControllerApi),
+                (pipeline_discriminator(), AccountKind::Pipeline),
+                (payout_discriminator(), AccountKind::Payout),
+                (periodring_discriminator(), AccountKind::PeriodRing),
+                (refunds_discriminator(), AccountKind::Refunds),
+                (bidlist_discriminator(), AccountKind::BidList),
+                (agent_discriminator(), AccountKind::Agent),
+            ]
+            .into_iter()
+            .collect(),
         }
     }
 }
 
 pub fn agent_discriminator() -> [u8; 8] {
-    [47, 166, 112, 147, 155, 197, 86, 7]
+    anchor_account_discriminator("Agent")
 }
 pub fn bidlist_discriminator() -> [u8; 8] {
-    [233, 127, 13, 29, 123, 209, 192, 79]
+    anchor_account_discriminator("BidList")
 }
 pub fn refunds_discriminator() -> [u8; 8] {
-    [169, 83, 174, 99, 135, 161, 12, 150]
+    anchor_account_discriminator("Refunds")
 }
 pub fn controller_discriminator() -> [u8; 8] {
-    [184, 79, 171, 0, 183, 43, 113, 110]
+    anchor_account_discriminator("Controller")
 }
 pub fn controllerapi_discriminator() -> [u8; 8] {
-    [224, 136, 168, 42, 53, 0, 84, 163]
+    anchor_account_discriminator("ControllerApi")
 }
 pub fn payout_discriminator() -> [u8; 8] {
-    [69, 45, 245, 131, 218, 101, 158, 228]
+    anchor_account_discriminator("Payout")
 }
 pub fn periodring_discriminator() -> [u8; 8] {
-    [61, 191, 59, 143, 226, 235, 104, 26]
+    anchor_account_discriminator("PeriodRing")
 }
 pub fn pipeline_discriminator() -> [u8; 8] {
-    [30, 82, 16, 218, 196, 77, 115, 224]
+    anchor_account_discriminator("Pipeline")
 }
 pub fn protocol_discriminator() -> [u8; 8] {
-    [45, 39, 101, 43, 115, 72, 131, 40]
+    anchor_account_discriminator("Protocol")
 }
 pub fn bidreceipt_discriminator() -> [u8; 8] {
-    [186, 150, 141, 135, 59, 122, 39, 99]
+    anchor_account_discriminator("BidReceipt")
 }
 
 pub struct Bid {
@@ -297,68 +362,29 @@ pub struct BidList {
 }
 
 impl BidList {
-    pub fn parse(data: &[u8]) -> Option<Self> {
-        if data.len() < 1 + 32 + 4 {
-            #[cfg(target_os = "wasi")]
-            HostImport::log(String::from("bidlist parse - 1"));
-            return None;
-        }
-        let mut i = 0;
-        let bidding_finished = 0 < data[i];
-        i += 1;
-        let mut payout_data = [0u8; 32];
-        payout_data.copy_from_slice(&data[i..i + 32]);
-        i += 32;
-        let payout = Pubkey::from(payout_data);
-        let size = u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]) as usize;
-        i += 4;
-        let mut book = Vec::new();
+    pub fn parse(data: &[u8]) -> Result<Self, CatscopeWasmError> {
+        let mut reader = AccountReader::new(data);
+        let bidding_finished = 0 < reader.read_u8()?;
+        let payout = reader.read_pubkey()?;
+        let size = reader.read_u32()? as usize;
+        // check the whole book up front so a bogus size cannot reserve a huge vector
+        let book_len = size
+            .checked_mul(1 + PUBKEY_LEN + U64_LEN)
+            .ok_or(CatscopeWasmError::ShortBuffer(usize::MAX, data.len()))?;
+        reader.require(reader.position().saturating_add(book_len))?;
+        let mut book = Vec::with_capacity(size);
         for _k in 0..size {
-            if data.len() < i + 1 + 32 + 8 {
-                #[cfg(target_os = "wasi")]
-                HostImport::log(format!("bidlist parse - 2 - i {}", i));
-                return None;
-            }
-            let is_blank = 0 < data[i];
-            i += 1;
-
-            let mut bidder_data = [0u8; 32];
-            bidder_data.copy_from_slice(&data[i..i + 32]);
-            let bidder = Pubkey::from(bidder_data);
-            i += 32;
-            let deposit = u64::from_le_bytes([
-                data[i],
-                data[i + 1],
-                data[i + 2],
-                data[i + 3],
-                data[i + 4],
-                data[i + 5],
-                data[i + 6],
-                data[i + 7],
-            ]);
-            i += 8;
+            let is_blank = 0 < reader.read_u8()?;
+            let bidder = reader.read_pubkey()?;
+            let deposit = reader.read_u64()?;
             book.push(Bid {
                 is_blank,
                 bidder,
                 deposit,
             });
         }
-        if data.len() < i + 8 {
-            #[cfg(target_os = "wasi")]
-            HostImport::log(format!("bidlist parse - 3 - i {}", i));
-            return None;
-        }
-        let total_deposits = u64::from_le_bytes([
-            data[i],
-            data[i + 1],
-            data[i + 2],
-            data[i + 3],
-            data[i + 4],
-            data[i + 5],
-            data[i + 6],
-            data[i + 7],
-        ]);
-        Some(Self {
+        let total_deposits = reader.read_u64()?;
+        Ok(Self {
             payout,
             bidding_finished,
             book,
@@ -375,58 +401,32 @@ pub struct Refunds {
 const CLAIM_SIZE: usize = PUBKEY_LEN + U64_LEN;
 const CLAIM_HEADER_SIZE: usize = PUBKEY_LEN + U32_LEN;
 impl Refunds {
-    pub fn count(data: &[u8]) -> Option<usize> {
-        #[cfg(target_os = "wasi")]
-        HostImport::log(format!("edge - Refunds::count - 1 - data {}", data.len()));
-        if data.len() < CLAIM_HEADER_SIZE {
-            return None;
-        }
-        #[cfg(target_os = "wasi")]
-        HostImport::log(format!("edge - Refunds::count - 2 - data {}", data.len()));
-        let nsubbuf = &data[PUBKEY_LEN..(PUBKEY_LEN + U32_LEN)];
-        let x: [u8; U32_LEN] = nsubbuf.try_into().unwrap();
-        let n = u32::from_le_bytes(x) as usize;
-        #[cfg(target_os = "wasi")]
-        HostImport::log(format!("edge - Refunds::count - 3 - data {}", data.len()));
-        if data.len() < CLAIM_HEADER_SIZE + n * CLAIM_SIZE {
-            return None;
-        }
+    /// Read the number of claims and check that all of them fit in the buffer.
+    pub fn count(data: &[u8]) -> Result<usize, CatscopeWasmError> {
+        let reader = AccountReader::new(data);
+        let n = reader.u32_at(PUBKEY_LEN)? as usize;
+        let len = n
+            .checked_mul(CLAIM_SIZE)
+            .and_then(|x| x.checked_add(CLAIM_HEADER_SIZE))
+            .ok_or(CatscopeWasmError::ShortBuffer(usize::MAX, data.len()))?;
+        reader.require(len)?;
         #[cfg(target_os = "wasi")]
         HostImport::log(format!(
-            "edge - Refunds::count - 4 - data {}; count {}",
+            "edge - Refunds::count - 1 - data {}; count {}",
             data.len(),
             n
         ));
-        Some(n)
+        Ok(n)
     }
-    // Get the claims; length are not checked
-    pub fn parse(data: &[u8], claim_i: usize) -> Claim {
-        #[cfg(target_os = "wasi")]
-        HostImport::log(format!(
-            "edge - Refunds::parse - 1 - data {}; claim_i {}",
-            data.len(),
-            claim_i
-        ));
-        let start = CLAIM_HEADER_SIZE + claim_i * CLAIM_SIZE;
-        #[cfg(target_os = "wasi")]
-        HostImport::log(format!("edge - Refunds::parse - 2 - data {}", data.len()));
-        let finish = start + CLAIM_SIZE;
-        let subbuf = &data[start..finish];
-        #[cfg(target_os = "wasi")]
-        HostImport::log(format!("edge - Refunds::parse - 3 - data {}", data.len()));
-        let mut i = 0;
-        let bidder = Pubkey::try_from(&subbuf[i..(i + PUBKEY_LEN)]).unwrap();
-        #[cfg(target_os = "wasi")]
-        HostImport::log(format!("edge - Refunds::parse - 4 - data {}", data.len()));
-        i += PUBKEY_LEN;
-        let xsubbuf = &subbuf[i..(i + U64_LEN)];
-        let x: [u8; U64_LEN] = xsubbuf.try_into().unwrap();
-        #[cfg(target_os = "wasi")]
-        HostImport::log(format!("edge - Refunds::parse - 5 - data {}", data.len()));
-        let balance = u64::from_le_bytes(x);
-        #[cfg(target_os = "wasi")]
-        HostImport::log(format!("edge - Refunds::parse - 6 - data {}", data.len()));
-        Claim { bidder, balance }
+    pub fn parse(data: &[u8], claim_i: usize) -> Result<Claim, CatscopeWasmError> {
+        let reader = AccountReader::new(data);
+        let start = claim_i
+            .checked_mul(CLAIM_SIZE)
+            .and_then(|x| x.checked_add(CLAIM_HEADER_SIZE))
+            .ok_or(CatscopeWasmError::ShortBuffer(usize::MAX, data.len()))?;
+        let bidder = reader.pubkey_at(start)?;
+        let balance = reader.u64_at(start + PUBKEY_LEN)?;
+        Ok(Claim { bidder, balance })
     }
 }
 #[derive(BorshDeserialize, BorshSerialize, Clone)]
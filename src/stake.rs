@@ -0,0 +1,219 @@
+use std::collections::VecDeque;
+
+use borsh::BorshDeserialize;
+use solana_sdk::{pubkey::Pubkey, system_program::ID as system_id};
+use solana_stake_interface::state::{Meta, StakeStateV2};
+
+#[cfg(target_os = "wasi")]
+use crate::primitive::wasmimport::HostImport;
+use crate::primitive::{
+    err::CatscopeWasmError,
+    guest::{edge_or_log, GuestFilter},
+    header::AccountHeader,
+    label::{
+        LABEL_STAKE_ACCOUNT_CUSTODIAN, LABEL_STAKE_ACCOUNT_STAKER, LABEL_STAKE_ACCOUNT_VOTER,
+        LABEL_STAKE_ACCOUNT_WITHDRAWER,
+    },
+    node::{NodeType, NODE_TYPE_STAKE_ACCOUNT, NODE_TYPE_UNKNOWN},
+    tree::{
+        FilterEdge, WEIGHT_AUTHORITY, WEIGHT_CUSTODIAN, WEIGHT_VOTE_ACCOUNT, WEIGHT_WITHDRAWER,
+    },
+};
+
+/// Links stake accounts to their authorities and to the vote account they delegate to.
+pub struct Stake {
+    pub program_id: Pubkey,
+}
+
+impl GuestFilter for Stake {
+    fn program_id_list(&self) -> Vec<Pubkey> {
+        vec![self.program_id]
+    }
+
+    fn edge(&self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge> {
+        edge_or_log("stake", header, self.try_edge(header, data))
+    }
+
+    fn node_type(&self, _header: &AccountHeader, data: &[u8]) -> NodeType {
+        match StakeStateV2::deserialize(&mut &data[..]) {
+            Ok(StakeStateV2::Initialized(_)) | Ok(StakeStateV2::Stake(..)) => {
+                NODE_TYPE_STAKE_ACCOUNT
+            }
+            _ => NODE_TYPE_UNKNOWN,
+        }
+    }
+}
+
+impl Stake {
+    fn try_edge(
+        &self,
+        header: &AccountHeader,
+        data: &[u8],
+    ) -> Result<VecDeque<FilterEdge>, CatscopeWasmError> {
+        let mut list = VecDeque::new();
+        let id = header.pubkey;
+        #[cfg(target_os = "wasi")]
+        HostImport::log(format!(
+            "stake_edge - 1 - pubkey {}; data len {}",
+            id,
+            data.len()
+        ));
+        // the state does not fill the account, so read it as a prefix
+        let state = StakeStateV2::deserialize(&mut &data[..])
+            .map_err(|_| CatscopeWasmError::FailedToParse)?;
+        match state {
+            StakeStateV2::Initialized(meta) => {
+                self.meta_edge(header, &meta, &mut list);
+            }
+            StakeStateV2::Stake(meta, stake, _) => {
+                self.meta_edge(header, &meta, &mut list);
+                // stake to vote account
+                list.push_back(FilterEdge {
+                    slot: header.slot,
+                    from: id,
+                    to: stake.delegation.voter_pubkey,
+                    weight: WEIGHT_VOTE_ACCOUNT,
+                    label: LABEL_STAKE_ACCOUNT_VOTER,
+                });
+            }
+            StakeStateV2::Uninitialized | StakeStateV2::RewardsPool => {}
+        }
+        Ok(list)
+    }
+
+    // stake to staker, withdrawer and lockup custodian
+    fn meta_edge(&self, header: &AccountHeader, meta: &Meta, list: &mut VecDeque<FilterEdge>) {
+        list.push_back(FilterEdge {
+            slot: header.slot,
+            from: header.pubkey,
+            to: meta.authorized.staker,
+            weight: WEIGHT_AUTHORITY,
+            label: LABEL_STAKE_ACCOUNT_STAKER,
+        });
+        list.push_back(FilterEdge {
+            slot: header.slot,
+            from: header.pubkey,
+            to: meta.authorized.withdrawer,
+            weight: WEIGHT_WITHDRAWER,
+            label: LABEL_STAKE_ACCOUNT_WITHDRAWER,
+        });
+        if meta.lockup.custodian != system_id {
+            list.push_back(FilterEdge {
+                slot: header.slot,
+                from: header.pubkey,
+                to: meta.lockup.custodian,
+                weight: WEIGHT_CUSTODIAN,
+                label: LABEL_STAKE_ACCOUNT_CUSTODIAN,
+            });
+        }
+    }
+
+    pub fn new(program_id: &Pubkey) -> Self {
+        Self {
+            program_id: *program_id,
+        }
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+    use solana_stake_interface::{
+        program::ID as stake_id,
+        stake_flags::StakeFlags,
+        state::{Authorized, Delegation, Lockup, Stake as StakeData},
+    };
+
+    use super::*; // import functions from the parent module
+    use crate::primitive::tree::{weight_list, WEIGHT_DIRECT, WEIGHT_PROGRAM, WEIGHT_SYMLINK};
+
+    fn account(state: &StakeStateV2) -> Vec<u8> {
+        let mut data = borsh::to_vec(state).unwrap();
+        data.resize(StakeStateV2::size_of(), 0);
+        data
+    }
+
+    #[test]
+    fn test_stake() {
+        let [staker, withdrawer, custodian, voter] = [0; 4].map(|_| Pubkey::new_unique());
+        let mut meta = Meta {
+            authorized: Authorized { staker, withdrawer },
+            ..Default::default()
+        };
+        let filter = Stake::new(&stake_id);
+        let header = AccountHeader {
+            pubkey: Pubkey::new_unique(),
+            owner: stake_id,
+            slot: 4,
+            ..Default::default()
+        };
+        let edge_list = |data: &[u8]| -> Vec<(Pubkey, Pubkey, u32)> {
+            filter
+                .edge(&header, data)
+                .iter()
+                .map(|e| (e.from, e.to, e.weight))
+                .collect()
+        };
+        let id = header.pubkey;
+        // no custodian
+        let data = account(&StakeStateV2::Initialized(meta));
+        assert_eq!(
+            edge_list(&data),
+            vec![
+                (id, staker, WEIGHT_AUTHORITY),
+                (id, withdrawer, WEIGHT_WITHDRAWER),
+            ]
+        );
+        meta.lockup = Lockup {
+            custodian,
+            ..Default::default()
+        };
+        let stake = StakeData {
+            delegation: Delegation {
+                voter_pubkey: voter,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let data = account(&StakeStateV2::Stake(meta, stake, StakeFlags::empty()));
+        assert_eq!(data.len(), 200);
+        assert_eq!(
+            edge_list(&data),
+            vec![
+                (id, staker, WEIGHT_AUTHORITY),
+                (id, withdrawer, WEIGHT_WITHDRAWER),
+                (id, custodian, WEIGHT_CUSTODIAN),
+                (id, voter, WEIGHT_VOTE_ACCOUNT),
+            ]
+        );
+        // truncated and unknown states have no edges
+        assert!(edge_list(&data[..150]).is_empty());
+        assert!(edge_list(&account(&StakeStateV2::Uninitialized)).is_empty());
+        let mut data = data;
+        data[0] = 9;
+        assert!(edge_list(&data).is_empty());
+        let mut out = Vec::new();
+        StakeStateV2::RewardsPool.serialize(&mut out).unwrap();
+        assert!(edge_list(&out).is_empty());
+    }
+
+    #[test]
+    fn test_stake_weight() {
+        // each stake relationship has its own indexed bit, apart from plain links
+        let list = [
+            WEIGHT_AUTHORITY,
+            WEIGHT_WITHDRAWER,
+            WEIGHT_CUSTODIAN,
+            WEIGHT_VOTE_ACCOUNT,
+        ];
+        for (i, weight) in list.iter().enumerate() {
+            assert!(list[i + 1..].iter().all(|x| x & weight == 0));
+            assert_eq!(
+                weight & (WEIGHT_DIRECT | WEIGHT_SYMLINK | WEIGHT_PROGRAM),
+                0
+            );
+            assert_eq!(weight_list(weight), &[*weight, 0]);
+        }
+    }
+}
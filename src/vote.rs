@@ -0,0 +1,308 @@
+use std::collections::VecDeque;
+
+use solana_sdk::{epoch_schedule::EpochSchedule, pubkey::Pubkey, system_program::ID as system_id};
+use solana_vote_interface::state::VoteState;
+
+#[cfg(target_os = "wasi")]
+use crate::primitive::wasmimport::HostImport;
+use crate::primitive::{
+    common::AccountReader,
+    err::CatscopeWasmError,
+    guest::{edge_or_log, GuestFilter},
+    header::AccountHeader,
+    label::{LABEL_VOTE_ACCOUNT_NODE, LABEL_VOTE_ACCOUNT_VOTER, LABEL_VOTE_ACCOUNT_WITHDRAWER},
+    node::{NodeType, NODE_TYPE_UNKNOWN, NODE_TYPE_VOTE_ACCOUNT},
+    tree::{FilterEdge, WEIGHT_NODE_IDENTITY, WEIGHT_VOTER, WEIGHT_WITHDRAWER},
+};
+
+/// The stored vote state versions: 0.23.5, 1.14.11 and current.
+const VOTE_STATE_VERSION_COUNT: u32 = 3;
+
+/// Links vote accounts to the validator identity and to their authorities.
+pub struct Vote {
+    pub program_id: Pubkey,
+    // maps the slot of an update to the epoch that picks the authorized voter
+    epoch_schedule: EpochSchedule,
+}
+
+impl GuestFilter for Vote {
+    fn program_id_list(&self) -> Vec<Pubkey> {
+        vec![self.program_id]
+    }
+
+    fn edge(&self, header: &AccountHeader, data: &[u8]) -> VecDeque<FilterEdge> {
+        edge_or_log("vote", header, self.try_edge(header, data))
+    }
+
+    fn node_type(&self, _header: &AccountHeader, data: &[u8]) -> NodeType {
+        // every version starts with its tag and the node identity, which is unset until the
+        // account is initialized
+        let reader = AccountReader::new(data);
+        match (reader.u32_at(0), reader.pubkey_at(4)) {
+            (Ok(version), Ok(node)) if version < VOTE_STATE_VERSION_COUNT && node != system_id => {
+                NODE_TYPE_VOTE_ACCOUNT
+            }
+            _ => NODE_TYPE_UNKNOWN,
+        }
+    }
+}
+
+impl Vote {
+    fn try_edge(
+        &self,
+        header: &AccountHeader,
+        data: &[u8],
+    ) -> Result<VecDeque<FilterEdge>, CatscopeWasmError> {
+        let mut list = VecDeque::new();
+        let id = header.pubkey;
+        #[cfg(target_os = "wasi")]
+        HostImport::log(format!(
+            "vote_edge - 1 - pubkey {}; data len {}",
+            id,
+            data.len()
+        ));
+        // every stored version is converted to the current layout
+        let state = VoteState::deserialize(data).map_err(|_| CatscopeWasmError::FailedToParse)?;
+        if state.authorized_voters().is_empty() || state.node_pubkey == system_id {
+            // uninitialized
+            return Ok(list);
+        }
+        // vote to node identity
+        list.push_back(FilterEdge {
+            slot: header.slot,
+            from: id,
+            to: state.node_pubkey,
+            weight: WEIGHT_NODE_IDENTITY,
+            label: LABEL_VOTE_ACCOUNT_NODE,
+        });
+        // vote to withdrawer
+        list.push_back(FilterEdge {
+            slot: header.slot,
+            from: id,
+            to: state.authorized_withdrawer,
+            weight: WEIGHT_WITHDRAWER,
+            label: LABEL_VOTE_ACCOUNT_WITHDRAWER,
+        });
+        // Vote to the voter of the epoch of this update. A voter change that is scheduled for a
+        // later epoch shows up with the first update of that epoch, and the edge cache then
+        // removes the edge to the old voter.
+        let epoch = self.epoch_schedule.get_epoch(header.slot);
+        if let Some(voter) = state.get_authorized_voter(epoch) {
+            list.push_back(FilterEdge {
+                slot: header.slot,
+                from: id,
+                to: voter,
+                weight: WEIGHT_VOTER,
+                label: LABEL_VOTE_ACCOUNT_VOTER,
+            });
+        }
+        Ok(list)
+    }
+
+    /// `epoch_schedule` is the schedule of the cluster, see [`FilterConfig::epoch_schedule`].
+    ///
+    /// [`FilterConfig::epoch_schedule`]: crate::config::FilterConfig::epoch_schedule
+    pub fn new(program_id: &Pubkey, epoch_schedule: EpochSchedule) -> Self {
+        Self {
+            program_id: *program_id,
+            epoch_schedule,
+        }
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use solana_sdk::clock::Clock;
+    use solana_vote_interface::{
+        program::ID as vote_id,
+        state::{VoteInit, VoteState1_14_11, VoteStateVersions},
+    };
+
+    use crate::primitive::{
+        filter::EdgeCache,
+        tree::{Weight, WEIGHT_REMOVE},
+    };
+
+    use super::*; // import functions from the parent module
+
+    // The 0.23.5 state is not exported by the vote interface crate, so its bincode layout is
+    // written out here, with no votes, no root and no credits.
+    fn v0_23_5(node: &Pubkey, voter: &Pubkey, epoch: u64, withdrawer: &Pubkey) -> Vec<u8> {
+        let mut data = 0u32.to_le_bytes().to_vec();
+        data.extend_from_slice(node.as_ref());
+        data.extend_from_slice(voter.as_ref());
+        data.extend_from_slice(&epoch.to_le_bytes());
+        // prior voters: 32 x (pubkey, epoch, epoch, slot) and the index
+        data.extend_from_slice(&[0; 32 * 56]);
+        data.extend_from_slice(&31u64.to_le_bytes());
+        data.extend_from_slice(withdrawer.as_ref());
+        // commission, votes, root slot, epoch credits and the last timestamp
+        data.push(5);
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.push(0);
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&[0; 16]);
+        data
+    }
+
+    #[test]
+    fn test_vote() {
+        let [node, voter, next_voter, withdrawer] = [0; 4].map(|_| Pubkey::new_unique());
+        let init = VoteInit {
+            node_pubkey: node,
+            authorized_voter: voter,
+            authorized_withdrawer: withdrawer,
+            commission: 5,
+        };
+        let clock = Clock {
+            epoch: 10,
+            ..Default::default()
+        };
+        let mut state = VoteState::new(&init, &clock);
+        // the voter rotates at epoch 11
+        state.authorized_voters.insert(11, next_voter);
+        let filter = Vote::new(&vote_id, EpochSchedule::without_warmup());
+        let slots_per_epoch = filter.epoch_schedule.slots_per_epoch;
+        let id = Pubkey::new_unique();
+        let edge_list = |data: &[u8], slot: u64| -> Vec<(Pubkey, Pubkey, u32)> {
+            let header = AccountHeader {
+                pubkey: id,
+                owner: vote_id,
+                slot,
+                ..Default::default()
+            };
+            filter
+                .edge(&header, data)
+                .iter()
+                .map(|e| (e.from, e.to, e.weight))
+                .collect()
+        };
+        let current = VoteStateVersions::new_current(state.clone());
+        let old = VoteStateVersions::V1_14_11(Box::new(VoteState1_14_11::from(state.clone())));
+        for (versioned, size) in [
+            (current, VoteState::size_of()),
+            (old, VoteState1_14_11::size_of()),
+        ] {
+            let mut data = vec![0; size];
+            VoteState::serialize(&versioned, &mut data).unwrap();
+            assert_eq!(
+                filter.node_type(&AccountHeader::default(), &data),
+                NODE_TYPE_VOTE_ACCOUNT
+            );
+            assert_eq!(
+                edge_list(&data, 10 * slots_per_epoch + 5),
+                vec![
+                    (id, node, WEIGHT_NODE_IDENTITY),
+                    (id, withdrawer, WEIGHT_WITHDRAWER),
+                    (id, voter, WEIGHT_VOTER),
+                ]
+            );
+            assert_eq!(
+                edge_list(&data, 11 * slots_per_epoch)[2],
+                (id, next_voter, WEIGHT_VOTER)
+            );
+            assert!(edge_list(&data[..100], 0).is_empty());
+        }
+        // the rotation replaces the voter edge in the host graph
+        let mut data = vec![0; VoteState::size_of()];
+        VoteState::serialize(&VoteStateVersions::new_current(state), &mut data).unwrap();
+        let mut cache = EdgeCache::default();
+        let mut header = AccountHeader {
+            pubkey: id,
+            owner: vote_id,
+            slot: 10 * slots_per_epoch + 5,
+            lamports: 1,
+            ..Default::default()
+        };
+        assert_eq!(cache.update(&header, filter.edge(&header, &data)).len(), 3);
+        header.slot = 11 * slots_per_epoch + 1;
+        let list = cache.update(&header, filter.edge(&header, &data));
+        let got: Vec<(Pubkey, Weight)> = list.iter().map(|e| (e.to, e.weight)).collect();
+        assert_eq!(
+            got,
+            vec![
+                (voter, WEIGHT_VOTER | WEIGHT_REMOVE),
+                (node, WEIGHT_NODE_IDENTITY),
+                (withdrawer, WEIGHT_WITHDRAWER),
+                (next_voter, WEIGHT_VOTER),
+            ]
+        );
+
+        // 0.23.5 keeps a single voter, set at the epoch in front of the prior voters
+        let mut data = v0_23_5(&node, &voter, 10, &withdrawer);
+        assert_eq!(
+            filter.node_type(&AccountHeader::default(), &data),
+            NODE_TYPE_VOTE_ACCOUNT
+        );
+        assert_eq!(
+            edge_list(&data, 10 * slots_per_epoch + 5),
+            vec![
+                (id, node, WEIGHT_NODE_IDENTITY),
+                (id, withdrawer, WEIGHT_WITHDRAWER),
+                (id, voter, WEIGHT_VOTER),
+            ]
+        );
+        // there is no voter before that epoch
+        assert_eq!(
+            edge_list(&data, 9 * slots_per_epoch),
+            vec![
+                (id, node, WEIGHT_NODE_IDENTITY),
+                (id, withdrawer, WEIGHT_WITHDRAWER),
+            ]
+        );
+        data[4..36].fill(0);
+        assert!(edge_list(&data, 10 * slots_per_epoch).is_empty());
+        assert_eq!(
+            filter.node_type(&AccountHeader::default(), &data),
+            NODE_TYPE_UNKNOWN
+        );
+
+        // uninitialized accounts have no edges
+        let mut data = vec![0; VoteState::size_of()];
+        VoteState::serialize(
+            &VoteStateVersions::new_current(VoteState::default()),
+            &mut data,
+        )
+        .unwrap();
+        assert!(edge_list(&data, 0).is_empty());
+        let header = AccountHeader::default();
+        assert_eq!(filter.node_type(&header, &data), NODE_TYPE_UNKNOWN);
+        // the node identity marks an initialized account, unless the version tag is unknown
+        data[4..36].fill(1);
+        assert_eq!(filter.node_type(&header, &data), NODE_TYPE_VOTE_ACCOUNT);
+        data[0] = 3;
+        assert_eq!(filter.node_type(&header, &data), NODE_TYPE_UNKNOWN);
+        assert_eq!(filter.node_type(&header, &data[..20]), NODE_TYPE_UNKNOWN);
+    }
+
+    #[test]
+    fn test_epoch_schedule() {
+        let [node, voter, next_voter, withdrawer] = [0; 4].map(|_| Pubkey::new_unique());
+        let init = VoteInit {
+            node_pubkey: node,
+            authorized_voter: voter,
+            authorized_withdrawer: withdrawer,
+            commission: 5,
+        };
+        let mut state = VoteState::new(&init, &Clock::default());
+        state.authorized_voters.insert(11, next_voter);
+        let mut data = vec![0; VoteState::size_of()];
+        VoteState::serialize(&VoteStateVersions::new_current(state), &mut data).unwrap();
+        // a test validator with warmup: the epochs start short and double
+        let schedule = EpochSchedule::custom(8192, 8192, true);
+        let first_slot = schedule.get_first_slot_in_epoch(11);
+        let filter = Vote::new(&vote_id, schedule);
+        let voter_at = |slot: u64| {
+            let header = AccountHeader {
+                slot,
+                ..Default::default()
+            };
+            filter.edge(&header, &data).back().map(|e| e.to)
+        };
+        assert_eq!(voter_at(first_slot - 1), Some(voter));
+        assert_eq!(voter_at(first_slot), Some(next_voter));
+        // the Mainnet schedule would still be in epoch 0 at that slot
+        assert_eq!(EpochSchedule::without_warmup().get_epoch(first_slot), 0);
+    }
+}
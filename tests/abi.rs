@@ -0,0 +1,271 @@
+//! End to end tests of the compiled guest through the native host in `host`.
+
+mod host;
+
+use std::collections::HashSet;
+
+use catscope_edge_generator::{
+    config::parse_filter_config,
+    filter_list,
+    primitive::{
+        header::AccountHeader,
+        label::{
+            EdgeLabel, EDGE_LABEL_LIST, LABEL_ORCA_WHIRLPOOL_TOKEN_VAULT_A,
+            LABEL_ORCA_WHIRLPOOL_TOKEN_VAULT_B, LABEL_ORCA_WHIRLPOOL_WHIRLPOOLS_CONFIG,
+        },
+        node::{
+            NODE_TYPE_ORCA_TICK_ARRAY, NODE_TYPE_ORCA_WHIRLPOOL, NODE_TYPE_ORCA_WHIRLPOOLS_CONFIG,
+            NODE_TYPE_RAYDIUM_AMM_AMM_INFO, NODE_TYPE_SYSTEM_ACCOUNT, NODE_TYPE_UNKNOWN,
+        },
+        tree::{edge_is_removal, FilterEdge, Weight, WEIGHT_IS_OUTGOING, WEIGHT_REMOVE},
+    },
+};
+use host::{AccountSet, Guest, EDGE_LABEL, EDGE_SIZE, HEADER_SIZE};
+use solana_sdk::{bpf_loader, bpf_loader_upgradeable, pubkey::Pubkey, system_program};
+
+const ORCA: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+const RAYDIUM_AMM: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+
+fn program_txt() -> String {
+    format!("orca={}\nraydium_amm={}\n", ORCA, RAYDIUM_AMM)
+}
+
+fn header(pubkey: &str, owner: &str) -> AccountHeader {
+    AccountHeader {
+        pubkey: Pubkey::try_from(pubkey).unwrap(),
+        lamports: 488832,
+        owner: Pubkey::try_from(owner).unwrap(),
+        rent_epoch: 1,
+        slot: 5,
+        ..Default::default()
+    }
+}
+
+// the fixtures in tests/data and the owner of each
+fn fixture_list() -> Vec<(AccountHeader, Vec<u8>)> {
+    [
+        (
+            "whirlpool1",
+            "DtYKbQELgMZ3ihFUrCcCs9gy4djcUuhwgR7UpxVpP2Tg",
+            ORCA,
+        ),
+        (
+            "whirlpoolconfig1",
+            "2LecshUwdy9xi7meFgHtFJQNSKk4KdTrcpvaB56dP2NQ",
+            ORCA,
+        ),
+        (
+            "tickarray1",
+            "AAgXUtyECrW4uXyRzzrwgZrnutJ6YnftSSN1DHPehtgw",
+            ORCA,
+        ),
+        (
+            "tickarray2",
+            "FmU2Tg7vvqPhcZELTqUZCnNA6QqjZwGhDwZnNKWQurqf",
+            ORCA,
+        ),
+        (
+            "raydium-ammInfo",
+            "GThUX1Atko4tqhN2NaiTazWSeFWMuiUvfFnyJyUghFMJ",
+            RAYDIUM_AMM,
+        ),
+    ]
+    .iter()
+    .map(|(name, pubkey, owner)| {
+        let data = std::fs::read(format!("tests/data/{}.bin", name)).unwrap();
+        let mut header = header(pubkey, owner);
+        header.data_size = data.len() as u32;
+        (header, data)
+    })
+    .collect()
+}
+
+fn to_set(list: &[FilterEdge]) -> HashSet<(Pubkey, Pubkey, Weight, EdgeLabel, u64)> {
+    list.iter()
+        .map(|e| (e.from, e.to, e.weight, e.label, e.slot))
+        .collect()
+}
+
+#[test]
+fn test_abi_layout() {
+    assert_eq!(std::mem::size_of::<AccountHeader>(), HEADER_SIZE);
+    assert_eq!(std::mem::size_of::<FilterEdge>(), EDGE_SIZE);
+    assert_eq!(std::mem::offset_of!(FilterEdge, label), EDGE_LABEL);
+}
+
+#[test]
+fn test_label_list() {
+    let mut guest = Guest::new(program_txt().as_bytes(), AccountSet::default()).unwrap();
+    let list = guest.label_list().unwrap();
+    let expected: Vec<(EdgeLabel, String)> = EDGE_LABEL_LIST
+        .iter()
+        .map(|(l, n)| (*l, n.to_string()))
+        .collect();
+    assert_eq!(list, expected);
+    // the labels come back with the edges
+    let (header, data) = fixture_list().remove(0);
+    let mut got: Vec<EdgeLabel> = guest
+        .edge(&header, &data)
+        .unwrap()
+        .iter()
+        .map(|e| e.label)
+        .collect();
+    got.sort();
+    assert_eq!(
+        got,
+        vec![
+            LABEL_ORCA_WHIRLPOOL_WHIRLPOOLS_CONFIG,
+            LABEL_ORCA_WHIRLPOOL_TOKEN_VAULT_A,
+            LABEL_ORCA_WHIRLPOOL_TOKEN_VAULT_B,
+        ]
+    );
+    guest.close().unwrap();
+}
+
+#[test]
+fn test_node_type() {
+    let mut guest = Guest::new(program_txt().as_bytes(), AccountSet::default()).unwrap();
+    let mut got = Vec::new();
+    for (header, data) in fixture_list() {
+        got.push(guest.node_type(&header, &data).unwrap());
+    }
+    assert_eq!(
+        got,
+        vec![
+            NODE_TYPE_ORCA_WHIRLPOOL,
+            NODE_TYPE_ORCA_WHIRLPOOLS_CONFIG,
+            NODE_TYPE_ORCA_TICK_ARRAY,
+            NODE_TYPE_ORCA_TICK_ARRAY,
+            NODE_TYPE_RAYDIUM_AMM_AMM_INFO,
+        ]
+    );
+    // wallets belong to the system program, which is always tracked
+    let (mut header, data) = fixture_list().remove(0);
+    header.owner = system_program::ID;
+    assert_eq!(
+        guest.node_type(&header, &[]).unwrap(),
+        NODE_TYPE_SYSTEM_ACCOUNT
+    );
+    // accounts of programs that are not tracked
+    header.owner = Pubkey::new_unique();
+    assert_eq!(guest.node_type(&header, &data).unwrap(), NODE_TYPE_UNKNOWN);
+    guest.close().unwrap();
+}
+
+#[test]
+fn test_program_list() {
+    let mut guest = Guest::new(program_txt().as_bytes(), AccountSet::default()).unwrap();
+    let list = guest.program_list().unwrap();
+    assert_eq!(
+        list,
+        vec![
+            system_program::ID,
+            spl_token::ID,
+            bpf_loader::ID,
+            Pubkey::try_from("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb").unwrap(),
+            bpf_loader_upgradeable::ID,
+            Pubkey::try_from(ORCA).unwrap(),
+            Pubkey::try_from(RAYDIUM_AMM).unwrap(),
+        ]
+    );
+    guest.close().unwrap();
+}
+
+#[test]
+fn test_bad_config() {
+    let err = Guest::new(b"orca=not-a-pubkey\n", AccountSet::default())
+        .err()
+        .unwrap();
+    assert!(
+        err.to_string().contains("failed to parse filter config"),
+        "{}",
+        err
+    );
+}
+
+// The guest must produce the same edges as the native filters, plus the outgoing bit.
+#[test]
+fn test_edge_matches_native() {
+    let mut accounts = AccountSet::default();
+    for (header, data) in fixture_list() {
+        accounts.insert(header, data);
+    }
+    let config = parse_filter_config(program_txt().as_bytes()).unwrap();
+    let native = filter_list(&config);
+    let mut guest = Guest::new(program_txt().as_bytes(), accounts).unwrap();
+    for (header, data) in fixture_list() {
+        let list = guest.edge_by_pubkey(&header.pubkey).unwrap();
+        assert!(!list.is_empty(), "no edges for {}", header.pubkey);
+        let filter = native
+            .iter()
+            .find(|f| f.program_id_list().contains(&header.owner))
+            .unwrap();
+        let mut expected: Vec<FilterEdge> = filter.edge(&header, &data).into_iter().collect();
+        expected
+            .iter_mut()
+            .for_each(|e| e.set_outgoing(&header.pubkey));
+        assert_eq!(to_set(&list), to_set(&expected), "{}", header.pubkey);
+        for edge in list.iter() {
+            assert_eq!(
+                edge.weight & WEIGHT_IS_OUTGOING != 0,
+                edge.from == header.pubkey
+            );
+        }
+    }
+    guest.close().unwrap();
+}
+
+// A truncated account is logged and yields no edges instead of trapping the instance.
+#[test]
+fn test_truncated_account() {
+    let mut guest = Guest::new(program_txt().as_bytes(), AccountSet::default()).unwrap();
+    let (header, data) = fixture_list().remove(0);
+    let list = guest.edge(&header, &data[..200]).unwrap();
+    assert!(list.is_empty());
+    assert!(guest
+        .state()
+        .log
+        .iter()
+        .any(|x| x.contains("failed to parse")));
+    // the instance is still usable
+    assert_eq!(guest.edge(&header, &data).unwrap().len(), 3);
+    // accounts of programs that are not tracked produce nothing
+    let mut other = header.clone();
+    other.pubkey = Pubkey::new_unique();
+    other.owner = Pubkey::new_unique();
+    assert!(guest.edge(&other, &data).unwrap().is_empty());
+    guest.close().unwrap();
+}
+
+// Edges that disappear between updates come back flagged for removal.
+#[test]
+fn test_edge_removal() {
+    let mut guest = Guest::new(program_txt().as_bytes(), AccountSet::default()).unwrap();
+    let (mut header, mut data) = fixture_list().remove(0);
+    let first = guest.edge(&header, &data).unwrap();
+    assert_eq!(first.len(), 3);
+    assert!(first.iter().all(|e| !edge_is_removal(&e.weight)));
+
+    // swap token vault B
+    let vault_b = Pubkey::try_from(&data[213..245]).unwrap();
+    let new_vault_b = Pubkey::new_unique();
+    data[213..245].copy_from_slice(new_vault_b.as_ref());
+    header.slot += 1;
+    let list = guest.edge(&header, &data).unwrap();
+    let removed: Vec<&FilterEdge> = list.iter().filter(|e| edge_is_removal(&e.weight)).collect();
+    assert_eq!(list.len(), 4);
+    assert_eq!(removed.len(), 1);
+    assert_eq!((removed[0].from, removed[0].to), (header.pubkey, vault_b));
+    assert_eq!(removed[0].slot, header.slot);
+    assert!(list
+        .iter()
+        .any(|e| e.to == new_vault_b && !edge_is_removal(&e.weight)));
+
+    // closing the account tears down the remaining edges
+    header.slot += 1;
+    header.lamports = 0;
+    let list = guest.edge(&header, &[]).unwrap();
+    assert_eq!(list.len(), 3);
+    assert!(list.iter().all(|e| e.weight & WEIGHT_REMOVE != 0));
+    guest.close().unwrap();
+}
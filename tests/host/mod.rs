@@ -0,0 +1,534 @@
+//! A native Catscope host for the compiled guest.
+//!
+//! It loads `catscope_edge_generator.wasm` into wasmtime, implements the `hf_*` imports
+//! against an in-memory account set and drives the exports the way the geyser plugin does.
+//! Structs cross the boundary as raw bytes, so the offsets below spell out the guest ABI.
+
+use std::{collections::HashMap, path::PathBuf, process::Command, sync::OnceLock};
+
+use catscope_edge_generator::primitive::{
+    header::{AccountHeader, AccountId},
+    label::{EdgeLabel, LABEL_NONE},
+    node::{NodeType, NODE_TYPE_UNKNOWN},
+    tree::FilterEdge,
+};
+use solana_sdk::pubkey::Pubkey;
+use wasmtime::{Caller, Engine, Linker, Memory, Module, Store, TypedFunc};
+
+const PUBKEY_LEN: usize = 32;
+
+/// `AccountHeader` is `repr(C, align(8))`.
+pub const HEADER_SIZE: usize = 112;
+const HEADER_PUBKEY: usize = 0;
+const HEADER_LAMPORTS: usize = 32;
+const HEADER_DATA_SIZE: usize = 40;
+const HEADER_NODE_ID: usize = 48;
+const HEADER_OWNER: usize = 56;
+const HEADER_RENT_EPOCH: usize = 88;
+const HEADER_SLOT: usize = 96;
+const HEADER_EXECUTABLE: usize = 104;
+
+/// `FilterEdge` is `repr(C, align(8))`.
+pub const EDGE_SIZE: usize = 80;
+const EDGE_SLOT: usize = 0;
+const EDGE_TO: usize = 8;
+const EDGE_FROM: usize = 40;
+const EDGE_WEIGHT: usize = 72;
+/// Only set from layout version 1 on; older guests leave padding here.
+pub const EDGE_LABEL: usize = 76;
+
+/// `FilterEdgeWithNextPointer` holds an `is_empty` flag, the layout version, the edge and the
+/// next node.
+pub const EDGE_NODE_SIZE: usize = 96;
+const EDGE_NODE_VERSION: usize = 1;
+const EDGE_NODE_EDGE: usize = 8;
+const EDGE_NODE_NEXT: usize = 88;
+
+/// `ProgramList` is a `u16` count followed by the pubkeys.
+const PROGRAM_LIST_COUNT: usize = 0;
+const PROGRAM_LIST_LIST: usize = 2;
+
+// wasi errno values
+const ERRNO_SUCCESS: i32 = 0;
+const ERRNO_BADF: i32 = 8;
+const ERRNO_FAULT: i32 = 21;
+
+/// Serialize a header the way the guest reads it.
+pub fn encode_header(header: &AccountHeader) -> [u8; HEADER_SIZE] {
+    let mut out = [0u8; HEADER_SIZE];
+    out[HEADER_PUBKEY..(HEADER_PUBKEY + PUBKEY_LEN)].copy_from_slice(header.pubkey.as_ref());
+    out[HEADER_LAMPORTS..(HEADER_LAMPORTS + 8)].copy_from_slice(&header.lamports.to_le_bytes());
+    out[HEADER_DATA_SIZE..(HEADER_DATA_SIZE + 4)].copy_from_slice(&header.data_size.to_le_bytes());
+    out[HEADER_NODE_ID..(HEADER_NODE_ID + 8)].copy_from_slice(&header.node_id.to_le_bytes());
+    out[HEADER_OWNER..(HEADER_OWNER + PUBKEY_LEN)].copy_from_slice(header.owner.as_ref());
+    out[HEADER_RENT_EPOCH..(HEADER_RENT_EPOCH + 8)]
+        .copy_from_slice(&header.rent_epoch.to_le_bytes());
+    out[HEADER_SLOT..(HEADER_SLOT + 8)].copy_from_slice(&header.slot.to_le_bytes());
+    out[HEADER_EXECUTABLE] = header.executable as u8;
+    out
+}
+
+/// Deserialize an edge written by the guest with the given layout version.
+pub fn decode_edge(data: &[u8], version: u8) -> FilterEdge {
+    let label = if 1 <= version {
+        u16::from_le_bytes(data[EDGE_LABEL..(EDGE_LABEL + 2)].try_into().unwrap())
+    } else {
+        LABEL_NONE
+    };
+    FilterEdge {
+        slot: u64::from_le_bytes(data[EDGE_SLOT..(EDGE_SLOT + 8)].try_into().unwrap()),
+        to: Pubkey::try_from(&data[EDGE_TO..(EDGE_TO + PUBKEY_LEN)]).unwrap(),
+        from: Pubkey::try_from(&data[EDGE_FROM..(EDGE_FROM + PUBKEY_LEN)]).unwrap(),
+        weight: u32::from_le_bytes(data[EDGE_WEIGHT..(EDGE_WEIGHT + 4)].try_into().unwrap()),
+        label,
+    }
+}
+
+/// Build the guest for `wasm32-wasip1`, unless `CATSCOPE_WASM` points at a prebuilt module.
+pub fn wasm_path() -> PathBuf {
+    static PATH: OnceLock<PathBuf> = OnceLock::new();
+    PATH.get_or_init(|| {
+        if let Ok(x) = std::env::var("CATSCOPE_WASM") {
+            return PathBuf::from(x);
+        }
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let target_dir = std::env::var("CARGO_TARGET_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| manifest_dir.join("target"));
+        let status = Command::new(env!("CARGO"))
+            .current_dir(&manifest_dir)
+            .args([
+                "build",
+                "--quiet",
+                "--lib",
+                "--release",
+                "--target",
+                "wasm32-wasip1",
+            ])
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .status()
+            .expect("failed to run cargo");
+        assert!(
+            status.success(),
+            "failed to build the guest; install it with `rustup target add wasm32-wasip1`"
+        );
+        target_dir.join("wasm32-wasip1/release/catscope_edge_generator.wasm")
+    })
+    .clone()
+}
+
+/// One account as stored by the host.
+#[derive(Debug, Clone)]
+pub struct Account {
+    pub header: AccountHeader,
+    pub data: Vec<u8>,
+}
+
+/// The accounts the guest can look up through `hf_pubkey_lookup`, `hf_account_header` and
+/// `hf_account_body`.
+#[derive(Debug, Default)]
+pub struct AccountSet {
+    m_account: HashMap<AccountId, Account>,
+    m_pubkey: HashMap<Pubkey, AccountId>,
+}
+impl AccountSet {
+    /// Add an account; the node id is assigned by the set.
+    pub fn insert(&mut self, mut header: AccountHeader, data: Vec<u8>) -> AccountId {
+        let node_id = match self.m_pubkey.get(&header.pubkey) {
+            Some(x) => *x,
+            None => self.m_pubkey.len() as AccountId + 1,
+        };
+        header.node_id = node_id;
+        header.data_size = data.len() as u32;
+        self.m_pubkey.insert(header.pubkey, node_id);
+        self.m_account.insert(node_id, Account { header, data });
+        node_id
+    }
+    pub fn node_id(&self, pubkey: &Pubkey) -> Option<AccountId> {
+        self.m_pubkey.get(pubkey).copied()
+    }
+    pub fn get(&self, node_id: AccountId) -> Option<&Account> {
+        self.m_account.get(&node_id)
+    }
+}
+
+/// Everything the imports can see.
+#[derive(Default)]
+pub struct HostState {
+    pub init_args: Vec<u8>,
+    pub accounts: AccountSet,
+    /// messages from `hf_simple_log` and the guest's stdout and stderr
+    pub log: Vec<String>,
+    pub tx_list: Vec<Vec<u8>>,
+}
+
+fn memory(caller: &mut Caller<'_, HostState>) -> wasmtime::Result<Memory> {
+    caller
+        .get_export("memory")
+        .and_then(|x| x.into_memory())
+        .ok_or_else(|| wasmtime::Error::msg("guest does not export memory"))
+}
+
+fn read(caller: &mut Caller<'_, HostState>, ptr: u64, len: usize) -> wasmtime::Result<Vec<u8>> {
+    let mut out = vec![0u8; len];
+    memory(caller)?.read(&mut *caller, ptr as usize, &mut out)?;
+    Ok(out)
+}
+
+fn write(caller: &mut Caller<'_, HostState>, ptr: u64, data: &[u8]) -> wasmtime::Result<()> {
+    memory(caller)?.write(&mut *caller, ptr as usize, data)?;
+    Ok(())
+}
+
+fn add_host_import(linker: &mut Linker<HostState>) -> wasmtime::Result<()> {
+    linker.func_wrap(
+        "",
+        "hf_simple_log",
+        |mut caller: Caller<'_, HostState>, ptr: u64, size: u32| -> wasmtime::Result<()> {
+            let msg = read(&mut caller, ptr, size as usize)?;
+            let msg = String::from_utf8_lossy(&msg).into_owned();
+            caller.data_mut().log.push(msg);
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        "",
+        "hf_init_args_size",
+        |caller: Caller<'_, HostState>| -> u32 { caller.data().init_args.len() as u32 },
+    )?;
+    linker.func_wrap(
+        "",
+        "hf_init_args",
+        |mut caller: Caller<'_, HostState>, ptr: u64| -> wasmtime::Result<i32> {
+            let args = caller.data().init_args.clone();
+            write(&mut caller, ptr, &args)?;
+            Ok(0)
+        },
+    )?;
+    linker.func_wrap(
+        "",
+        "hf_pubkey_lookup",
+        |mut caller: Caller<'_, HostState>,
+         req_ptr: u64,
+         req_len: u32,
+         resp_ptr: u64|
+         -> wasmtime::Result<i32> {
+            let req = read(&mut caller, req_ptr, req_len as usize)?;
+            let mut resp = Vec::with_capacity(req.len() / PUBKEY_LEN * 8);
+            for chunk in req.chunks_exact(PUBKEY_LEN) {
+                let pubkey = Pubkey::try_from(chunk).unwrap();
+                let node_id = caller.data().accounts.node_id(&pubkey).unwrap_or_default();
+                resp.extend_from_slice(&node_id.to_le_bytes());
+            }
+            write(&mut caller, resp_ptr, &resp)?;
+            Ok(0)
+        },
+    )?;
+    linker.func_wrap(
+        "",
+        "hf_account_header",
+        |mut caller: Caller<'_, HostState>,
+         ptr: u64,
+         node_id: u64,
+         _slot: u64|
+         -> wasmtime::Result<i32> {
+            let header = match caller.data().accounts.get(node_id) {
+                Some(x) => encode_header(&x.header),
+                None => return Ok(0),
+            };
+            write(&mut caller, ptr, &header)?;
+            Ok(1)
+        },
+    )?;
+    linker.func_wrap(
+        "",
+        "hf_account_body",
+        |mut caller: Caller<'_, HostState>,
+         ptr: u64,
+         node_id: u64,
+         _slot: u64|
+         -> wasmtime::Result<i32> {
+            let data = match caller.data().accounts.get(node_id) {
+                Some(x) => x.data.clone(),
+                None => return Ok(0),
+            };
+            write(&mut caller, ptr, &data)?;
+            Ok(1)
+        },
+    )?;
+    linker.func_wrap(
+        "",
+        "hf_tx_send",
+        |mut caller: Caller<'_, HostState>, ptr: u64, len: u32| -> wasmtime::Result<i32> {
+            let tx = read(&mut caller, ptr, len as usize)?;
+            caller.data_mut().tx_list.push(tx);
+            Ok(0)
+        },
+    )?;
+    Ok(())
+}
+
+// The guest only links against a handful of wasi calls; the rest trap if they are ever called.
+fn add_wasi(linker: &mut Linker<HostState>) -> wasmtime::Result<()> {
+    let module = "wasi_snapshot_preview1";
+    linker.func_wrap(
+        module,
+        "random_get",
+        |mut caller: Caller<'_, HostState>, ptr: u32, len: u32| -> i32 {
+            // deterministic bytes keep the hash maps of the guest reproducible
+            let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            match write(&mut caller, ptr as u64, &data) {
+                Ok(()) => ERRNO_SUCCESS,
+                Err(_) => ERRNO_FAULT,
+            }
+        },
+    )?;
+    linker.func_wrap(
+        module,
+        "environ_sizes_get",
+        |mut caller: Caller<'_, HostState>, count_ptr: u32, size_ptr: u32| -> i32 {
+            let zero = 0u32.to_le_bytes();
+            match write(&mut caller, count_ptr as u64, &zero)
+                .and_then(|_| write(&mut caller, size_ptr as u64, &zero))
+            {
+                Ok(()) => ERRNO_SUCCESS,
+                Err(_) => ERRNO_FAULT,
+            }
+        },
+    )?;
+    linker.func_wrap(
+        module,
+        "environ_get",
+        |_: Caller<'_, HostState>, _environ: u32, _buf: u32| -> i32 { ERRNO_SUCCESS },
+    )?;
+    linker.func_wrap(
+        module,
+        "fd_write",
+        |mut caller: Caller<'_, HostState>,
+         fd: u32,
+         iovs: u32,
+         iovs_len: u32,
+         nwritten_ptr: u32|
+         -> i32 {
+            if fd != 1 && fd != 2 {
+                return ERRNO_BADF;
+            }
+            let mut out = Vec::new();
+            for k in 0..iovs_len {
+                let iov = match read(&mut caller, iovs as u64 + 8 * k as u64, 8) {
+                    Ok(x) => x,
+                    Err(_) => return ERRNO_FAULT,
+                };
+                let ptr = u32::from_le_bytes(iov[0..4].try_into().unwrap());
+                let len = u32::from_le_bytes(iov[4..8].try_into().unwrap());
+                match read(&mut caller, ptr as u64, len as usize) {
+                    Ok(x) => out.extend_from_slice(&x),
+                    Err(_) => return ERRNO_FAULT,
+                }
+            }
+            let nwritten = (out.len() as u32).to_le_bytes();
+            if write(&mut caller, nwritten_ptr as u64, &nwritten).is_err() {
+                return ERRNO_FAULT;
+            }
+            caller
+                .data_mut()
+                .log
+                .push(String::from_utf8_lossy(&out).into_owned());
+            ERRNO_SUCCESS
+        },
+    )?;
+    linker.func_wrap(
+        module,
+        "proc_exit",
+        |_: Caller<'_, HostState>, code: u32| -> wasmtime::Result<()> {
+            Err(wasmtime::Error::msg(format!("guest exited with {}", code)))
+        },
+    )?;
+    Ok(())
+}
+
+/// A running guest filter, as created by the `init` export.
+pub struct Guest {
+    store: Store<HostState>,
+    memory: Memory,
+    cat_ptr: u64,
+    f_allocate: TypedFunc<(u64, u32), u64>,
+    f_deallocate: TypedFunc<(u64, u64), u64>,
+    f_close: TypedFunc<u64, u64>,
+    f_program_list: TypedFunc<u64, u64>,
+    f_edge: TypedFunc<(u64, u64, u32), u64>,
+    // guests that predate edge labels do not export this
+    f_label_list: Option<TypedFunc<u64, u64>>,
+    // nor this, which came with node types
+    f_node_type: Option<TypedFunc<(u64, u64, u32), u64>>,
+}
+
+impl Guest {
+    /// Instantiate the guest and call `init` with the contents of `program.txt`.
+    pub fn new(init_args: &[u8], accounts: AccountSet) -> wasmtime::Result<Self> {
+        let engine = Engine::default();
+        let module = Module::from_file(&engine, wasm_path())?;
+        let mut linker = Linker::new(&engine);
+        add_host_import(&mut linker)?;
+        add_wasi(&mut linker)?;
+        linker.define_unknown_imports_as_traps(&module)?;
+        let state = HostState {
+            init_args: init_args.to_vec(),
+            accounts,
+            ..Default::default()
+        };
+        let mut store = Store::new(&engine, state);
+        let instance = linker.instantiate(&mut store, &module)?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| wasmtime::Error::msg("guest does not export memory"))?;
+        let f_init = instance.get_typed_func::<(), u64>(&mut store, "init")?;
+        let cat_ptr = f_init.call(&mut store, ())?;
+        if cat_ptr == 0 {
+            return Err(wasmtime::Error::msg(format!(
+                "init failed: {:?}",
+                store.data().log
+            )));
+        }
+        Ok(Self {
+            cat_ptr,
+            memory,
+            f_allocate: instance.get_typed_func(&mut store, "allocate")?,
+            f_deallocate: instance.get_typed_func(&mut store, "deallocate")?,
+            f_close: instance.get_typed_func(&mut store, "close")?,
+            f_program_list: instance.get_typed_func(&mut store, "program_list")?,
+            f_edge: instance.get_typed_func(&mut store, "edge")?,
+            f_label_list: instance.get_typed_func(&mut store, "label_list").ok(),
+            f_node_type: instance.get_typed_func(&mut store, "node_type").ok(),
+            store,
+        })
+    }
+
+    pub fn state(&self) -> &HostState {
+        self.store.data()
+    }
+
+    fn read(&self, ptr: u64, len: usize) -> wasmtime::Result<Vec<u8>> {
+        let mut out = vec![0u8; len];
+        self.memory.read(&self.store, ptr as usize, &mut out)?;
+        Ok(out)
+    }
+
+    fn deallocate(&mut self, ptr: u64) -> wasmtime::Result<()> {
+        self.f_deallocate
+            .call(&mut self.store, (self.cat_ptr, ptr))?;
+        Ok(())
+    }
+
+    /// The program ids the guest wants to receive accounts for.
+    pub fn program_list(&mut self) -> wasmtime::Result<Vec<Pubkey>> {
+        let ptr = self.f_program_list.call(&mut self.store, self.cat_ptr)?;
+        let count = self.read(ptr + PROGRAM_LIST_COUNT as u64, 2)?;
+        let count = u16::from_le_bytes([count[0], count[1]]) as usize;
+        let list = self.read(ptr + PROGRAM_LIST_LIST as u64, count * PUBKEY_LEN)?;
+        self.deallocate(ptr)?;
+        Ok(list
+            .chunks_exact(PUBKEY_LEN)
+            .map(|x| Pubkey::try_from(x).unwrap())
+            .collect())
+    }
+
+    /// The label registry of the guest; empty for guests without labels.
+    pub fn label_list(&mut self) -> wasmtime::Result<Vec<(EdgeLabel, String)>> {
+        let f = match self.f_label_list.clone() {
+            Some(x) => x,
+            None => return Ok(Vec::new()),
+        };
+        let ptr = f.call(&mut self.store, self.cat_ptr)?;
+        let len = self.read(ptr, 4)?;
+        let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+        let text = self.read(ptr + 4, len)?;
+        self.deallocate(ptr)?;
+        let mut list = Vec::new();
+        for line in String::from_utf8_lossy(&text).lines() {
+            let (id, name) = line
+                .split_once(',')
+                .ok_or_else(|| wasmtime::Error::msg(format!("bad label line {}", line)))?;
+            list.push((id.parse()?, name.to_string()));
+        }
+        Ok(list)
+    }
+
+    /// Copy the header and data of an account into guest memory.
+    /// Returns the pointer and size of the blob.
+    fn write_account(
+        &mut self,
+        header: &AccountHeader,
+        data: &[u8],
+    ) -> wasmtime::Result<(u64, usize)> {
+        let mut header = header.clone();
+        header.data_size = data.len() as u32;
+        let size = HEADER_SIZE + data.len();
+        let ptr = self
+            .f_allocate
+            .call(&mut self.store, (self.cat_ptr, size as u32))?;
+        self.memory
+            .write(&mut self.store, ptr as usize, &encode_header(&header))?;
+        self.memory
+            .write(&mut self.store, ptr as usize + HEADER_SIZE, data)?;
+        Ok((ptr, size))
+    }
+
+    /// Copy the account into guest memory, call `edge` and walk the returned linked list.
+    pub fn edge(
+        &mut self,
+        header: &AccountHeader,
+        data: &[u8],
+    ) -> wasmtime::Result<Vec<FilterEdge>> {
+        let (ptr, size) = self.write_account(header, data)?;
+        let mut next = self
+            .f_edge
+            .call(&mut self.store, (self.cat_ptr, ptr, size as u32))?;
+        let mut list = Vec::new();
+        while next != 0 {
+            let node = self.read(next, EDGE_NODE_SIZE)?;
+            list.push(decode_edge(
+                &node[EDGE_NODE_EDGE..(EDGE_NODE_EDGE + EDGE_SIZE)],
+                node[EDGE_NODE_VERSION],
+            ));
+            self.deallocate(next)?;
+            next = u64::from_le_bytes(
+                node[EDGE_NODE_NEXT..(EDGE_NODE_NEXT + 8)]
+                    .try_into()
+                    .unwrap(),
+            );
+        }
+        self.deallocate(ptr)?;
+        Ok(list)
+    }
+
+    /// Classify an account; guests without node types know nothing.
+    pub fn node_type(&mut self, header: &AccountHeader, data: &[u8]) -> wasmtime::Result<NodeType> {
+        let f = match self.f_node_type.clone() {
+            Some(x) => x,
+            None => return Ok(NODE_TYPE_UNKNOWN),
+        };
+        let (ptr, size) = self.write_account(header, data)?;
+        let node_type = f.call(&mut self.store, (self.cat_ptr, ptr, size as u32))?;
+        self.deallocate(ptr)?;
+        Ok(node_type as NodeType)
+    }
+
+    /// Run `edge` on an account of the in-memory set.
+    pub fn edge_by_pubkey(&mut self, pubkey: &Pubkey) -> wasmtime::Result<Vec<FilterEdge>> {
+        let accounts = &self.state().accounts;
+        let account = accounts
+            .node_id(pubkey)
+            .and_then(|x| accounts.get(x))
+            .cloned()
+            .ok_or_else(|| wasmtime::Error::msg(format!("unknown account {}", pubkey)))?;
+        self.edge(&account.header, &account.data)
+    }
+
+    /// Drop the filter inside of the guest.
+    pub fn close(mut self) -> wasmtime::Result<HostState> {
+        self.f_close.call(&mut self.store, self.cat_ptr)?;
+        Ok(self.store.into_data())
+    }
+}